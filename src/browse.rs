@@ -0,0 +1,285 @@
+// src/browse.rs
+
+//! `sed-dl browse`: 全屏浏览教材目录, 多选后加入下载队列
+
+use crate::DownloadItem;
+use indicatif::HumanBytes;
+use ratatui::crossterm::event::{self, Event as TermEvent, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use sed_dl::{i18n, tr, AppError, CatalogEntry, TagNode};
+use std::collections::HashSet;
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum Focus {
+    Tags,
+    Books,
+}
+
+struct Browser {
+    roots: Vec<TagNode>,
+    entries: Vec<CatalogEntry>,
+    /// 已进入的分类, 每层为上一层子节点中的下标
+    path: Vec<usize>,
+    tag_state: ListState,
+    /// 当前分类和搜索条件下的教材 (`entries` 中的下标)
+    books: Vec<usize>,
+    book_state: ListState,
+    focus: Focus,
+    search: String,
+    searching: bool,
+    /// 已选中的教材, 按选择顺序
+    selected: Vec<usize>,
+    selected_set: HashSet<usize>,
+    message: Option<String>,
+}
+
+impl Browser {
+    fn new(roots: Vec<TagNode>, entries: Vec<CatalogEntry>) -> Browser {
+        let mut browser = Browser {
+            roots,
+            entries,
+            path: Vec::new(),
+            tag_state: ListState::default().with_selected(Some(0)),
+            books: Vec::new(),
+            book_state: ListState::default(),
+            focus: Focus::Tags,
+            search: String::new(),
+            searching: false,
+            selected: Vec::new(),
+            selected_set: HashSet::new(),
+            message: None,
+        };
+        browser.refresh_books();
+        browser
+    }
+
+    fn path_nodes(&self) -> Vec<&TagNode> {
+        let mut nodes = Vec::new();
+        let mut level = &self.roots;
+        for &index in &self.path {
+            let node = &level[index];
+            nodes.push(node);
+            level = &node.children;
+        }
+        nodes
+    }
+
+    fn current_tags(&self) -> &[TagNode] {
+        self.path_nodes().last().map_or(&self.roots, |node| &node.children)
+    }
+
+    /// 按当前分类和搜索词重新筛选教材。搜索词以空格分隔, 每个词须出现在标题或标签中。
+    fn refresh_books(&mut self) {
+        let tag_ids: Vec<String> = self.path_nodes().iter().map(|n| n.tag_id.clone()).collect();
+        let words: Vec<String> = self.search.split_whitespace().map(str::to_lowercase).collect();
+        self.books = self.entries.iter().enumerate()
+            .filter(|(_, e)| tag_ids.iter().all(|id| e.has_tag(id)))
+            .filter(|(_, e)| {
+                let haystack = format!("{} {}", e.title, e.tag_names().collect::<Vec<_>>().join(" ")).to_lowercase();
+                words.iter().all(|w| haystack.contains(w.as_str()))
+            })
+            .map(|(i, _)| i)
+            .collect();
+        self.book_state.select(if self.books.is_empty() { None } else { Some(0) });
+    }
+
+    fn enter_tag(&mut self) {
+        if let Some(index) = self.tag_state.selected().filter(|&i| i < self.current_tags().len()) {
+            self.path.push(index);
+            self.tag_state.select(Some(0));
+            self.refresh_books();
+        }
+    }
+
+    fn leave_tag(&mut self) {
+        if let Some(index) = self.path.pop() {
+            self.tag_state.select(Some(index));
+            self.refresh_books();
+        }
+    }
+
+    fn toggle(&mut self, index: usize) {
+        if self.selected_set.remove(&index) {
+            self.selected.retain(|&i| i != index);
+        } else {
+            self.selected_set.insert(index);
+            self.selected.push(index);
+        }
+    }
+
+    /// 当前列表全部已选中时取消全选, 否则全选
+    fn toggle_all(&mut self) {
+        let all_selected = self.books.iter().all(|i| self.selected_set.contains(i));
+        for index in self.books.clone() {
+            if all_selected == self.selected_set.contains(&index) {
+                self.toggle(index);
+            }
+        }
+    }
+
+    fn move_cursor(&mut self, delta: isize) {
+        let tag_count = self.current_tags().len();
+        let (state, len) = match self.focus {
+            Focus::Tags => (&mut self.tag_state, tag_count),
+            Focus::Books => (&mut self.book_state, self.books.len()),
+        };
+        if len == 0 { return; }
+        let current = state.selected().unwrap_or(0) as isize;
+        state.select(Some((current + delta).clamp(0, len as isize - 1) as usize));
+    }
+
+    /// 处理按键, 返回 `Some(true)` 表示开始下载, `Some(false)` 表示退出
+    fn on_key(&mut self, key: KeyEvent) -> Option<bool> {
+        self.message = None;
+        if self.searching {
+            match key.code {
+                KeyCode::Esc => {
+                    self.searching = false;
+                    self.search.clear();
+                    self.refresh_books();
+                }
+                KeyCode::Enter => self.searching = false,
+                KeyCode::Backspace => {
+                    self.search.pop();
+                    self.refresh_books();
+                }
+                KeyCode::Char(c) => {
+                    self.search.push(c);
+                    self.refresh_books();
+                }
+                _ => {}
+            }
+            return None;
+        }
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Some(false),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Some(false),
+            KeyCode::Char('d') => {
+                if self.selected.is_empty() {
+                    self.message = Some(tr!("browse.select_first"));
+                } else {
+                    return Some(true);
+                }
+            }
+            KeyCode::Char('/') => {
+                self.searching = true;
+                self.focus = Focus::Books;
+            }
+            KeyCode::Tab => {
+                self.focus = if self.focus == Focus::Tags { Focus::Books } else { Focus::Tags };
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.move_cursor(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.move_cursor(1),
+            KeyCode::PageUp => self.move_cursor(-10),
+            KeyCode::PageDown => self.move_cursor(10),
+            KeyCode::Left | KeyCode::Backspace => self.leave_tag(),
+            KeyCode::Right | KeyCode::Enter if self.focus == Focus::Tags => self.enter_tag(),
+            KeyCode::Char(' ') | KeyCode::Enter if self.focus == Focus::Books => {
+                if let Some(&index) = self.book_state.selected().and_then(|i| self.books.get(i)) {
+                    self.toggle(index);
+                    self.move_cursor(1);
+                }
+            }
+            KeyCode::Char('a') if self.focus == Focus::Books => self.toggle_all(),
+            _ => {}
+        }
+        None
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [header, body, detail, footer] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(5),
+            Constraint::Length(5),
+            Constraint::Length(1),
+        ]).areas(frame.area());
+        let [tags_area, books_area] = Layout::horizontal([Constraint::Percentage(30), Constraint::Percentage(70)]).areas(body);
+
+        let mut crumbs = vec![tr!("browse.root")];
+        crumbs.extend(self.path_nodes().iter().map(|n| n.tag_name.clone()));
+        let mut header_spans = vec![Span::raw(crumbs.join(" › ")).bold()];
+        if self.searching || !self.search.is_empty() {
+            header_spans.push(Span::raw(tr!("browse.search", self.search)).fg(Color::Yellow));
+            if self.searching { header_spans.push(Span::raw("▏").fg(Color::Yellow)); }
+        }
+        frame.render_widget(Line::from(header_spans), header);
+
+        let border = |focused: bool| if focused { Style::default().fg(Color::Cyan) } else { Style::default() };
+        let highlight = Style::default().add_modifier(Modifier::REVERSED);
+
+        let tags: Vec<ListItem> = self.current_tags().iter()
+            .map(|n| ListItem::new(if n.children.is_empty() { n.tag_name.clone() } else { format!("{} ›", n.tag_name) }))
+            .collect();
+        let tags = List::new(tags)
+            .block(Block::default().borders(Borders::ALL).title(tr!("browse.tags_title")).border_style(border(self.focus == Focus::Tags)))
+            .highlight_style(highlight);
+        frame.render_stateful_widget(tags, tags_area, &mut self.tag_state);
+
+        let books: Vec<ListItem> = self.books.iter().map(|&i| {
+            let entry = &self.entries[i];
+            let mark = if self.selected_set.contains(&i) { "[x]".green() } else { "[ ]".into() };
+            let size = entry.pdf_size().map(|s| format!("  {}", HumanBytes(s))).unwrap_or_default();
+            ListItem::new(Line::from(vec![mark, Span::raw(format!(" {}", entry.title)), Span::raw(size).dim()]))
+        }).collect();
+        let title = tr!("browse.books_title", self.books.len());
+        let books = List::new(books)
+            .block(Block::default().borders(Borders::ALL).title(title).border_style(border(self.focus == Focus::Books)))
+            .highlight_style(highlight);
+        frame.render_stateful_widget(books, books_area, &mut self.book_state);
+
+        let detail_text = match self.book_state.selected().and_then(|i| self.books.get(i)) {
+            Some(&i) => {
+                let entry = &self.entries[i];
+                let assets: Vec<String> = entry.assets.iter().map(|a| match a.size {
+                    Some(size) => format!("{} {} ({})", a.format, HumanBytes(size), a.flag),
+                    None => format!("{} ({})", a.format, a.flag),
+                }).collect();
+                vec![
+                    Line::from(vec![Span::raw("ID: ").dim(), Span::raw(entry.id.clone())]),
+                    Line::from(vec![Span::raw(i18n::t("browse.detail.tags")).dim(), Span::raw(entry.tag_names().collect::<Vec<_>>().join(" / "))]),
+                    Line::from(vec![Span::raw(i18n::t("browse.detail.files")).dim(), Span::raw(assets.join(", "))]),
+                ]
+            }
+            None => vec![Line::from(i18n::t("browse.no_match").dim())],
+        };
+        frame.render_widget(
+            Paragraph::new(detail_text).wrap(Wrap { trim: true }).block(Block::default().borders(Borders::TOP)),
+            detail,
+        );
+
+        let footer_text = match &self.message {
+            Some(message) => Line::from(message.clone().yellow()),
+            None => Line::from(tr!("browse.help", self.selected.len())).dim(),
+        };
+        frame.render_widget(footer_text, footer);
+    }
+
+    fn run(mut self, terminal: &mut DefaultTerminal) -> Result<Vec<DownloadItem>, AppError> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            if let TermEvent::Key(key) = event::read()?
+                && key.kind == KeyEventKind::Press
+                && let Some(start) = self.on_key(key)
+            {
+                if !start { return Ok(Vec::new()); }
+                return Ok(self.selected.iter().map(|&i| {
+                    let id = self.entries[i].id.clone();
+                    (id.clone(), id)
+                }).collect());
+            }
+        }
+    }
+}
+
+/// 显示目录浏览界面, 返回选中的教材; 未选择而退出时返回空列表
+pub fn run(roots: Vec<TagNode>, entries: Vec<CatalogEntry>) -> Result<Vec<DownloadItem>, AppError> {
+    let mut terminal = ratatui::init();
+    let result = Browser::new(roots, entries).run(&mut terminal);
+    ratatui::restore();
+    result
+}
+
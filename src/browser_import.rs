@@ -0,0 +1,253 @@
+// src/browser_import.rs
+
+use crate::{leveldb, tr, AppError};
+use log::debug;
+use rusqlite::{Connection, OpenFlags};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// 平台域名, 用于筛选 localStorage 的来源
+static SMARTEDU_DOMAIN: &str = "smartedu.cn";
+/// 平台登录信息在 localStorage 中的键前缀
+static AUTH_KEY_PREFIX: &str = "ND_UC_AUTH";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Browser {
+    Firefox,
+    /// Chrome / Edge / Brave 等基于 Chromium 的浏览器
+    Chromium,
+}
+
+/// 从浏览器本地的 localStorage 中读取平台的 access_token。
+/// 未指定 `profile` 时依次尝试所有已知的用户配置目录。
+pub fn import_token(browser: Browser, profile: Option<&Path>) -> Result<String, AppError> {
+    let profiles = match profile {
+        Some(p) => vec![p.to_path_buf()],
+        None => match browser {
+            Browser::Firefox => firefox_profiles(),
+            Browser::Chromium => chromium_profiles(),
+        },
+    };
+    if profiles.is_empty() {
+        return Err(AppError::BrowserImport(tr!("browser_import.no_profile")));
+    }
+    for profile in &profiles {
+        let values = match browser {
+            Browser::Firefox => firefox_auth_values(profile),
+            Browser::Chromium => chromium_auth_values(profile),
+        };
+        match values {
+            Ok(values) => {
+                if let Some(token) = values.iter().find_map(|v| extract_access_token(v)) {
+                    debug!("{}", tr!("browser_import.found", profile.display()));
+                    return Ok(token);
+                }
+            }
+            Err(e) => debug!("{}", tr!("browser_import.profile_failed", profile.display(), e)),
+        }
+    }
+    Err(AppError::BrowserImport(tr!("browser_import.not_logged_in")))
+}
+
+/// localStorage 中的值形如 `{"value": "{\"access_token\": ...}"}`,
+/// 与 ACCESS_TOKEN_GUIDE.txt 中的 JS 代码取值方式一致。
+fn extract_access_token(raw: &str) -> Option<String> {
+    let outer: serde_json::Value = serde_json::from_str(raw).ok()?;
+    let inner = match outer.get("value")? {
+        serde_json::Value::String(s) => serde_json::from_str(s).ok()?,
+        other => other.clone(),
+    };
+    inner.get("access_token")?.as_str().filter(|t| !t.is_empty()).map(str::to_string)
+}
+
+fn utf16le(bytes: &[u8]) -> Option<String> {
+    let units: Vec<u16> = bytes.chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]])).collect();
+    String::from_utf16(&units).ok()
+}
+
+// --- Firefox ---
+
+fn firefox_roots() -> Vec<PathBuf> {
+    let mut roots = Vec::new();
+    if cfg!(target_os = "linux") {
+        if let Some(home) = dirs::home_dir() {
+            roots.push(home.join(".mozilla").join("firefox"));
+            roots.push(home.join("snap").join("firefox").join("common").join(".mozilla").join("firefox"));
+        }
+    } else if cfg!(target_os = "macos") {
+        roots.extend(dirs::config_dir().map(|d| d.join("Firefox")));
+    } else {
+        roots.extend(dirs::config_dir().map(|d| d.join("Mozilla").join("Firefox")));
+    }
+    roots
+}
+
+/// 解析 profiles.ini, 返回所有用户配置目录 (默认配置排在最前)
+fn firefox_profiles() -> Vec<PathBuf> {
+    let mut profiles = Vec::new();
+    for root in firefox_roots() {
+        let Ok(ini) = std::fs::read_to_string(root.join("profiles.ini")) else { continue };
+        let mut preferred = Vec::new();
+        let mut others = Vec::new();
+        for (name, fields) in parse_ini(&ini) {
+            // [Install...] 段的 Default 字段指向当前安装使用的配置
+            if name.starts_with("Install") {
+                preferred.extend(fields.get("Default").map(|p| root.join(p)));
+                continue;
+            }
+            let Some(path) = fields.get("Path") else { continue };
+            let is_relative = fields.get("IsRelative").is_none_or(|v| v == "1");
+            let dir = if is_relative { root.join(path) } else { PathBuf::from(path) };
+            if fields.get("Default").map(String::as_str) == Some("1") { preferred.push(dir) } else { others.push(dir) }
+        }
+        for dir in preferred.into_iter().chain(others) {
+            if dir.is_dir() && !profiles.contains(&dir) { profiles.push(dir); }
+        }
+    }
+    profiles
+}
+
+fn parse_ini(ini: &str) -> Vec<(String, HashMap<String, String>)> {
+    let mut sections: Vec<(String, HashMap<String, String>)> = Vec::new();
+    for line in ini.lines().map(str::trim) {
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            sections.push((name.to_string(), HashMap::new()));
+        } else if let (Some((key, value)), Some((_, fields))) = (line.split_once('='), sections.last_mut()) {
+            fields.insert(key.trim().to_string(), value.trim().to_string());
+        }
+    }
+    sections
+}
+
+fn firefox_auth_values(profile: &Path) -> Result<Vec<String>, AppError> {
+    // 浏览器运行时数据库会被锁定, 复制一份再读取
+    let tmp = tempfile::tempdir()?;
+    let mut values = Vec::new();
+
+    // 新版 localStorage: storage/default/<来源>/ls/data.sqlite
+    if let Ok(entries) = std::fs::read_dir(profile.join("storage").join("default")) {
+        for entry in entries.flatten() {
+            if !entry.file_name().to_string_lossy().contains(SMARTEDU_DOMAIN) { continue; }
+            let db = entry.path().join("ls").join("data.sqlite");
+            if db.exists() {
+                values.extend(read_lsng_database(&copy_database(&db, tmp.path())?)?);
+            }
+        }
+    }
+
+    // 旧版 localStorage: webappsstore.sqlite, originKey 为倒序的域名
+    let legacy = profile.join("webappsstore.sqlite");
+    if legacy.exists() {
+        let conn = copy_database(&legacy, tmp.path())?;
+        let reversed: String = SMARTEDU_DOMAIN.chars().rev().collect();
+        let mut stmt = conn.prepare("SELECT value FROM webappsstore2 WHERE originKey LIKE ?1 AND key LIKE ?2")
+            .map_err(sqlite_error)?;
+        let rows = stmt.query_map([format!("{}.%", reversed), format!("{}%", AUTH_KEY_PREFIX)], |row| row.get::<_, String>(0))
+            .map_err(sqlite_error)?;
+        values.extend(rows.flatten());
+    }
+    Ok(values)
+}
+
+fn read_lsng_database(conn: &Connection) -> Result<Vec<String>, AppError> {
+    let pattern = format!("{}%", AUTH_KEY_PREFIX);
+    // compression_type / conversion_type 列在较新的版本中才有
+    let full = conn.prepare("SELECT value, compression_type, conversion_type FROM data WHERE key LIKE ?1");
+    let mut values = Vec::new();
+    match full {
+        Ok(mut stmt) => {
+            let rows = stmt.query_map([&pattern], |row| {
+                let value = row.get_ref(0)?.as_bytes_or_null()?.map(<[u8]>::to_vec);
+                Ok((value, row.get::<_, i64>(1)?, row.get::<_, i64>(2)?))
+            }).map_err(sqlite_error)?;
+            for (value, compression, conversion) in rows.flatten() {
+                values.extend(value.and_then(|v| decode_lsng_value(v, compression, conversion)));
+            }
+        }
+        Err(_) => {
+            let mut stmt = conn.prepare("SELECT value FROM data WHERE key LIKE ?1").map_err(sqlite_error)?;
+            let rows = stmt.query_map([&pattern], |row| row.get::<_, String>(0)).map_err(sqlite_error)?;
+            values.extend(rows.flatten());
+        }
+    }
+    Ok(values)
+}
+
+/// compression_type: 1 = Snappy; conversion_type: 1 = 已转为 UTF-8, 否则可能是 UTF-16
+fn decode_lsng_value(raw: Vec<u8>, compression: i64, conversion: i64) -> Option<String> {
+    let bytes = if compression == 1 { snap::raw::Decoder::new().decompress_vec(&raw).ok()? } else { raw };
+    match String::from_utf8(bytes) {
+        Ok(s) => Some(s),
+        Err(e) if conversion != 1 => utf16le(e.as_bytes()),
+        Err(_) => None,
+    }
+}
+
+fn copy_database(db: &Path, tmp_dir: &Path) -> Result<Connection, AppError> {
+    let target = tmp_dir.join(format!("{}.sqlite", rand::random::<u32>()));
+    std::fs::copy(db, &target)?;
+    let wal = PathBuf::from(format!("{}-wal", db.display()));
+    if wal.exists() {
+        std::fs::copy(&wal, format!("{}-wal", target.display()))?;
+    }
+    Connection::open_with_flags(&target, OpenFlags::SQLITE_OPEN_READ_WRITE).map_err(sqlite_error)
+}
+
+fn sqlite_error(e: rusqlite::Error) -> AppError {
+    AppError::BrowserImport(tr!("browser_import.sqlite", e))
+}
+
+// --- Chromium ---
+
+fn chromium_profiles() -> Vec<PathBuf> {
+    // Chrome、Chromium、Edge、Brave 的用户数据目录
+    let (base, vendors) = if cfg!(target_os = "windows") {
+        (dirs::data_local_dir(), ["Google/Chrome/User Data", "Chromium/User Data", "Microsoft/Edge/User Data", "BraveSoftware/Brave-Browser/User Data"])
+    } else if cfg!(target_os = "macos") {
+        (dirs::config_dir(), ["Google/Chrome", "Chromium", "Microsoft Edge", "BraveSoftware/Brave-Browser"])
+    } else {
+        (dirs::config_dir(), ["google-chrome", "chromium", "microsoft-edge", "BraveSoftware/Brave-Browser"])
+    };
+    let Some(base) = base else { return Vec::new() };
+    let roots = vendors.iter().map(|v| base.join(v));
+
+    let mut profiles = Vec::new();
+    for root in roots {
+        let Ok(entries) = std::fs::read_dir(&root) else { continue };
+        let mut found: Vec<PathBuf> = entries.flatten()
+            .map(|e| e.path())
+            .filter(|p| {
+                let name = p.file_name().unwrap_or_default().to_string_lossy();
+                (name == "Default" || name.starts_with("Profile ")) && p.join("Local Storage").join("leveldb").is_dir()
+            })
+            .collect();
+        // Default 优先
+        found.sort_by_key(|p| p.file_name() != Some("Default".as_ref()));
+        profiles.extend(found);
+    }
+    profiles
+}
+
+fn chromium_auth_values(profile: &Path) -> Result<Vec<String>, AppError> {
+    let dir = profile.join("Local Storage").join("leveldb");
+    let mut values = Vec::new();
+    for (key, value) in leveldb::read_all(&dir)? {
+        // 键格式: "_" + 来源 + "\0" + 编码后的键名
+        let Some(rest) = key.strip_prefix(b"_") else { continue };
+        let Some(sep) = rest.iter().position(|&b| b == 0) else { continue };
+        if !String::from_utf8_lossy(&rest[..sep]).contains(SMARTEDU_DOMAIN) { continue; }
+        let Some(name) = decode_chromium_string(&rest[sep + 1..]) else { continue };
+        if !name.starts_with(AUTH_KEY_PREFIX) { continue; }
+        values.extend(decode_chromium_string(&value));
+    }
+    Ok(values)
+}
+
+/// 首字节表示编码: 1 = Latin-1, 0 = UTF-16LE
+fn decode_chromium_string(data: &[u8]) -> Option<String> {
+    match data.split_first()? {
+        (1, rest) => Some(rest.iter().map(|&b| b as char).collect()),
+        (0, rest) => utf16le(rest),
+        _ => None,
+    }
+}
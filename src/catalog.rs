@@ -0,0 +1,106 @@
+// src/catalog.rs
+
+use crate::{tr, AppError, SmartEduClient};
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug)]
+struct DataVersion {
+    /// 逗号分隔的分片地址
+    urls: String,
+}
+
+/// 目录中的一本教材
+#[derive(Deserialize, Debug, Clone)]
+pub struct CatalogEntry {
+    /// 即 Content ID
+    pub id: String,
+    pub title: String,
+    #[serde(default, rename = "tag_list")]
+    pub tags: Vec<CatalogTag>,
+    /// 教材包含的文件, 如 PDF 原文和封面图片
+    #[serde(default, rename = "ti_items")]
+    pub assets: Vec<CatalogAsset>,
+}
+
+/// 教材包含的一个文件
+#[derive(Deserialize, Debug, Clone)]
+pub struct CatalogAsset {
+    /// 用途, 如 `source` (原文)、`thumbnail` (封面)
+    #[serde(default, rename = "ti_file_flag")]
+    pub flag: String,
+    #[serde(default, rename = "ti_format")]
+    pub format: String,
+    #[serde(rename = "ti_size")]
+    pub size: Option<u64>,
+}
+
+/// 分类标签树的节点
+#[derive(Debug, Clone)]
+pub struct TagNode {
+    pub tag_id: String,
+    pub tag_name: String,
+    pub children: Vec<TagNode>,
+}
+
+#[derive(Deserialize)]
+struct RawTagNode {
+    tag_id: String,
+    tag_name: String,
+    #[serde(default)]
+    hierarchies: Option<Vec<RawHierarchy>>,
+}
+
+#[derive(Deserialize)]
+struct RawHierarchy {
+    #[serde(default)]
+    children: Vec<RawTagNode>,
+}
+
+impl From<RawTagNode> for TagNode {
+    fn from(raw: RawTagNode) -> TagNode {
+        let children = raw.hierarchies.unwrap_or_default().into_iter()
+            .flat_map(|h| h.children)
+            .map(TagNode::from)
+            .collect();
+        TagNode { tag_id: raw.tag_id, tag_name: raw.tag_name, children }
+    }
+}
+
+/// 教材的分类标签, 如学段、学科、版本、年级
+#[derive(Deserialize, Debug, Clone)]
+pub struct CatalogTag {
+    pub tag_id: String,
+    pub tag_name: String,
+}
+
+impl CatalogEntry {
+    pub fn tag_names(&self) -> impl Iterator<Item = &str> {
+        self.tags.iter().map(|t| t.tag_name.as_str())
+    }
+
+    pub fn has_tag(&self, tag_id: &str) -> bool {
+        self.tags.iter().any(|t| t.tag_id == tag_id)
+    }
+
+    /// PDF 原文的大小
+    pub fn pdf_size(&self) -> Option<u64> {
+        self.assets.iter().find(|a| a.flag == "source" && a.format == "pdf").and_then(|a| a.size)
+    }
+}
+
+pub(crate) async fn fetch(client: &SmartEduClient) -> Result<Vec<CatalogEntry>, AppError> {
+    let version: DataVersion = client.fetch_json(&client.endpoint_url(&client.endpoints().catalog_version, "")).await?;
+    let mut entries = Vec::new();
+    for url in version.urls.split(',').map(str::trim).filter(|u| !u.is_empty()) {
+        let part: Vec<CatalogEntry> = client.fetch_json(url).await?;
+        entries.extend(part);
+    }
+    log::debug!("{}", tr!("catalog.loaded", entries.len()));
+    Ok(entries)
+}
+
+/// 获取分类标签树, 返回第一层 (学段) 节点
+pub(crate) async fn fetch_tags(client: &SmartEduClient) -> Result<Vec<TagNode>, AppError> {
+    let root: RawTagNode = client.fetch_json(&client.endpoint_url(&client.endpoints().catalog_tags, "")).await?;
+    Ok(TagNode::from(root).children)
+}
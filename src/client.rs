@@ -0,0 +1,761 @@
+// src/client.rs
+
+use crate::catalog::{self, CatalogEntry, TagNode};
+use crate::endpoints::Endpoints;
+use crate::events::{Event, EventSink};
+use crate::har::{HarEntry, HarRecorder};
+use crate::http_cache::HttpCache;
+use crate::preflight::{self, TokenState};
+use crate::retry::{self, RetryConfig, StatusClass};
+use crate::token_pool::{ParkReason, PoolStrategy, TokenPool};
+use crate::token_store::DEFAULT_TOKEN_NAME;
+use crate::{mirror_of, tr, AppError, DownloadStatus};
+use futures::stream::StreamExt;
+use log::debug;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use reqwest::{Client, RequestBuilder, Response, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::future::Future;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::fs::{self, File};
+use tokio::io::{AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio_util::sync::CancellationToken;
+
+static PDF_MAGIC: &[u8] = b"%PDF-";
+
+static FILENAME_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r#"[<>:"/\\|?*]"#).unwrap());
+static UUID_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}$").unwrap());
+
+/// 从教材页面 URL 或 Content ID 中解析出 Content ID
+pub fn get_content_id(input: &str) -> Option<String> {
+    if UUID_REGEX.is_match(input) {
+        Some(input.to_string())
+    } else if let Ok(url) = reqwest::Url::parse(input) {
+        url.query_pairs().find_map(|(key, value)| {
+            (key == "contentId" && UUID_REGEX.is_match(&value)).then(|| value.into_owned())
+        })
+    } else {
+        None
+    }
+}
+
+pub fn sanitize_filename(filename: &str) -> String {
+    FILENAME_REGEX.replace_all(filename, "_").to_string()
+}
+
+/// 教材详情中与下载相关的信息
+#[derive(Debug, Clone)]
+pub struct TextbookInfo {
+    pub content_id: String,
+    pub title: String,
+    /// 不含 accessToken 的资源地址, 下载时再拼接所用账号的 Token
+    pub source_url: String,
+    pub filename: String,
+    pub expected_md5: Option<String>,
+    pub expected_size: Option<u64>,
+}
+
+impl TextbookInfo {
+    pub fn download_url(&self, access_token: &str) -> String {
+        format!("{}?accessToken={}", self.source_url, access_token)
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct TextbookDetailsResponse {
+    ti_items: Vec<TechInfoItem>,
+    title: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct TechInfoItem {
+    ti_file_flag: String,
+    ti_format: String,
+    ti_storages: Vec<String>,
+    ti_md5: Option<String>,
+    ti_size: Option<u64>,
+}
+
+/// 一次下载的结果, 以及实际发起下载的账号 (未借到账号时为空)
+#[derive(Debug)]
+pub struct DownloadOutcome {
+    pub result: Result<DownloadStatus, AppError>,
+    pub account: Option<String>,
+}
+
+/// [`SmartEduClient`] 的构建器
+#[derive(Default)]
+pub struct SmartEduClientBuilder {
+    http: Option<Client>,
+    timeout: Option<Duration>,
+    user_agent: Option<String>,
+    retry: RetryConfig,
+    token: Option<String>,
+    pool: Option<TokenPool>,
+    events: Option<Arc<dyn EventSink>>,
+    cancel: Option<CancellationToken>,
+    har: Option<Arc<HarRecorder>>,
+    endpoints: Option<Endpoints>,
+    cache: Option<HttpCache>,
+    offline: bool,
+}
+
+impl SmartEduClientBuilder {
+    /// 使用自定义的 HTTP 客户端, 此时 `timeout` 和 `user_agent` 不生效
+    pub fn http_client(mut self, client: Client) -> Self {
+        self.http = Some(client);
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    pub fn retry(mut self, retry: RetryConfig) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// 使用单个 Access Token 下载
+    pub fn token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// 使用多账号 Token 池下载, 优先于 [`token`](Self::token)
+    pub fn token_pool(mut self, pool: TokenPool) -> Self {
+        self.pool = Some(pool);
+        self
+    }
+
+    /// 订阅下载事件 (进度、重试、校验结果等)
+    pub fn event_sink(mut self, sink: Arc<dyn EventSink>) -> Self {
+        self.events = Some(sink);
+        self
+    }
+
+    /// 取消令牌被触发后, 进行中的请求和下载立即以 [`AppError::Cancelled`] 结束
+    pub fn cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancel = Some(token);
+        self
+    }
+
+    /// 使用自定义的接口地址表, 如更换主机或指向本地的测试服务器
+    pub fn endpoints(mut self, endpoints: Endpoints) -> Self {
+        self.endpoints = Some(endpoints);
+        self
+    }
+
+    /// 把教材详情和目录缓存到磁盘
+    pub fn cache(mut self, cache: HttpCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// 离线模式: 只使用缓存中的详情和目录, 不发出任何请求, 也不下载。需要同时启用缓存。
+    pub fn offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    /// 把详情、目录和下载请求记录到 HAR 抓包中
+    pub fn har(mut self, recorder: Arc<HarRecorder>) -> Self {
+        self.har = Some(recorder);
+        self
+    }
+
+    pub fn build(self) -> Result<SmartEduClient, AppError> {
+        let endpoints = self.endpoints.unwrap_or_default();
+        endpoints.validate()?;
+        if self.offline && self.cache.is_none() {
+            return Err(AppError::Config(tr!("client.offline_needs_cache")));
+        }
+        let http = match self.http {
+            Some(client) => client,
+            None => {
+                let mut builder = Client::builder();
+                if let Some(timeout) = self.timeout { builder = builder.timeout(timeout); }
+                if let Some(user_agent) = self.user_agent { builder = builder.user_agent(user_agent); }
+                builder.build()?
+            }
+        };
+        let pool = self.pool.unwrap_or_else(|| {
+            let accounts = self.token.map(|t| (DEFAULT_TOKEN_NAME.to_string(), t)).into_iter().collect();
+            TokenPool::new(accounts, PoolStrategy::default(), Duration::ZERO)
+        });
+        Ok(SmartEduClient {
+            http,
+            retry: Arc::new(self.retry),
+            pool: Arc::new(pool),
+            events: self.events,
+            cancel: self.cancel.unwrap_or_default(),
+            har: self.har,
+            endpoints: Arc::new(endpoints),
+            preferred_host: Arc::new(AtomicUsize::new(0)),
+            cache: self.cache.map(Arc::new),
+            offline: self.offline,
+        })
+    }
+}
+
+/// 智慧教育平台客户端: 查询教材详情和目录、下载并校验教材。克隆的开销很小, 可在任务间共享。
+#[derive(Clone)]
+pub struct SmartEduClient {
+    http: Client,
+    retry: Arc<RetryConfig>,
+    pool: Arc<TokenPool>,
+    events: Option<Arc<dyn EventSink>>,
+    cancel: CancellationToken,
+    har: Option<Arc<HarRecorder>>,
+    endpoints: Arc<Endpoints>,
+    /// 最近一次请求成功的主机, 之后的请求优先使用
+    preferred_host: Arc<AtomicUsize>,
+    cache: Option<Arc<HttpCache>>,
+    offline: bool,
+}
+
+impl SmartEduClient {
+    pub fn builder() -> SmartEduClientBuilder {
+        SmartEduClientBuilder::default()
+    }
+
+    pub fn http(&self) -> &Client {
+        &self.http
+    }
+
+    pub fn retry_config(&self) -> &RetryConfig {
+        &self.retry
+    }
+
+    pub fn pool(&self) -> &TokenPool {
+        &self.pool
+    }
+
+    pub fn endpoints(&self) -> &Endpoints {
+        &self.endpoints
+    }
+
+    /// 用当前优先的主机生成接口地址
+    pub(crate) fn endpoint_url(&self, template: &str, id: &str) -> String {
+        self.endpoints.render(template, self.preferred_host.load(Ordering::Relaxed), id)
+    }
+
+    pub fn is_offline(&self) -> bool {
+        self.offline
+    }
+
+    pub fn cancellation_token(&self) -> &CancellationToken {
+        &self.cancel
+    }
+
+    /// 共享连接和账号池, 但可单独取消的客户端。取消原客户端时它也会被取消。
+    pub fn child(&self) -> SmartEduClient {
+        SmartEduClient { cancel: self.cancel.child_token(), ..self.clone() }
+    }
+
+    /// 执行可被取消的操作
+    async fn cancellable<F: Future>(&self, future: F) -> Result<F::Output, AppError> {
+        tokio::select! {
+            output = future => Ok(output),
+            _ = self.cancel.cancelled() => Err(AppError::Cancelled),
+        }
+    }
+
+    /// 发出可被取消的请求。开启抓包时 `entry` 会得到这次请求的记录, 在丢弃时写入抓包。
+    async fn send(&self, request: RequestBuilder, entry: &mut Option<HarEntry>) -> Result<reqwest::Result<Response>, AppError> {
+        let request = match request.build() {
+            Ok(request) => request,
+            Err(e) => return Ok(Err(e)),
+        };
+        if self.offline {
+            return Err(AppError::Offline(tr!("client.offline_request", mirror_of(request.url().as_str()))));
+        }
+        *entry = self.har.as_ref().map(|har| har.begin(&request));
+        let result = self.cancellable(self.http.execute(request)).await?;
+        if let Some(entry) = entry {
+            match &result {
+                Ok(response) => entry.response(response),
+                Err(e) => entry.error(e),
+            }
+        }
+        Ok(result)
+    }
+
+    /// 开启抓包时读取错误响应的内容, 以便排查
+    async fn record_body(&self, response: Response, entry: &mut Option<HarEntry>) -> Result<(), AppError> {
+        if let Some(entry) = entry {
+            match self.cancellable(response.bytes()).await? {
+                Ok(body) => entry.body(&body),
+                Err(e) => entry.error(&e),
+            }
+        }
+        Ok(())
+    }
+
+    /// 向订阅者发出事件
+    pub fn emit(&self, event: Event) {
+        if let Some(sink) = &self.events {
+            sink.on_event(&event);
+        }
+    }
+
+    /// 按详情接口的重试策略获取 JSON, 暂时性错误自动重试。
+    /// 地址位于接口地址表的主机上时, 每次重试换用下一个主机。
+    /// 启用缓存时新鲜的缓存直接使用, 过期的缓存带条件请求头向服务器确认。
+    pub(crate) async fn fetch_json<T: DeserializeOwned>(&self, url: &str) -> Result<T, AppError> {
+        // 同一路径在各个主机上共用一条缓存
+        let key = self.endpoints.split_host(url).map_or(url, |(_, path)| path);
+        let mut cached = match &self.cache {
+            Some(cache) => cache.load(key).await.filter(|entry| serde_json::from_str::<T>(&entry.body).is_ok()),
+            None => None,
+        };
+        if let (Some(cache), Some(entry)) = (&self.cache, &cached)
+            && (self.offline || cache.is_fresh(entry))
+        {
+            debug!("{}", tr!("cache.hit", key));
+            return Ok(serde_json::from_str(&entry.body)?);
+        }
+        if self.offline {
+            return Err(AppError::Offline(tr!("client.cache_miss", key)));
+        }
+
+        let policy = &self.retry.details;
+        let mirrors = self.endpoints.mirrors(url);
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let (host, url) = &mirrors[(attempt as usize - 1) % mirrors.len()];
+            let url = url.as_str();
+            let mut request = self.http.get(url);
+            if let Some(entry) = &cached { request = entry.conditional(request); }
+            let mut har = None;
+            let (err, retry_after) = match self.send(request, &mut har).await? {
+                Ok(response) if response.status() == StatusCode::NOT_MODIFIED && let Some(entry) = cached.take() => {
+                    debug!("{}", tr!("cache.not_modified", key));
+                    self.use_host(*host, url);
+                    let data = serde_json::from_str(&entry.body)?;
+                    if let Some(cache) = &self.cache { cache.revalidated(entry, response.headers()).await; }
+                    return Ok(data);
+                }
+                Ok(response) if response.status().is_success() => {
+                    let headers = response.headers().clone();
+                    match self.cancellable(response.bytes()).await? {
+                        Ok(body) => {
+                            if let Some(entry) = &mut har { entry.body(&body); }
+                            match serde_json::from_slice::<T>(&body) {
+                                Ok(data) => {
+                                    self.use_host(*host, url);
+                                    if let Some(cache) = &self.cache { cache.store(key, &headers, &body).await; }
+                                    return Ok(data);
+                                }
+                                // 响应体不完整时也会表现为解析失败, 按暂时性错误处理
+                                Err(e) => (AppError::Json(e), None),
+                            }
+                        }
+                        Err(e) => {
+                            if let Some(entry) = &mut har { entry.error(&e); }
+                            (AppError::transfer(url, e), None)
+                        }
+                    }
+                }
+                Ok(response) => {
+                    let status = response.status();
+                    let err = AppError::HttpStatus { status, mirror: mirror_of(url) };
+                    let retry_after = retry::retry_after(status, response.headers());
+                    self.record_body(response, &mut har).await?;
+                    if retry::classify_status(status) != StatusClass::Transient {
+                        return Err(err);
+                    }
+                    (err, retry_after)
+                }
+                Err(e) => (AppError::transfer(url, e), None),
+            };
+            if attempt >= policy.max_attempts { return Err(err); }
+            let wait_time = policy.delay_for(attempt, retry_after, self.retry.max_retry_after);
+            debug!("{}", tr!("client.json_retry", err, format!("{:.1?}", wait_time), url));
+            self.cancellable(tokio::time::sleep(wait_time)).await?;
+        }
+    }
+
+    /// 请求成功后, 之后的请求优先使用这个主机
+    fn use_host(&self, host: Option<usize>, url: &str) {
+        if let Some(host) = host
+            && self.preferred_host.swap(host, Ordering::Relaxed) != host
+        {
+            debug!("{}", tr!("client.host_switched", mirror_of(url)));
+        }
+    }
+
+    /// 查询教材详情 (不需要 Token)
+    pub async fn details(&self, content_id: &str) -> Result<TextbookInfo, AppError> {
+        let url = self.endpoint_url(&self.endpoints.details, content_id);
+        let data: TextbookDetailsResponse = self.fetch_json(&url).await?;
+        let source_item = data.ti_items.iter()
+            .find(|item| item.ti_file_flag == "source" && item.ti_format == "pdf")
+            .ok_or_else(|| AppError::DetailFetch(tr!("client.no_source_pdf", content_id)))?;
+        let pdf_url_base = source_item.ti_storages.first()
+            .ok_or_else(|| AppError::DetailFetch(tr!("client.no_pdf_url", content_id)))?;
+        let is_pdf_pdf = pdf_url_base.to_lowercase().ends_with("pdf.pdf");
+        let mut final_filename = if is_pdf_pdf {
+            data.title.clone()
+        } else {
+            Path::new(pdf_url_base)
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_else(|| content_id.to_string())
+        };
+        if !final_filename.to_lowercase().ends_with(".pdf") {
+            final_filename.push_str(".pdf");
+        }
+        let info = TextbookInfo {
+            content_id: content_id.to_string(),
+            title: data.title,
+            source_url: pdf_url_base.clone(),
+            filename: sanitize_filename(&final_filename),
+            expected_md5: if is_pdf_pdf { None } else { source_item.ti_md5.clone() },
+            expected_size: source_item.ti_size,
+        };
+        self.emit(Event::DetailsResolved {
+            content_id: info.content_id.clone(),
+            filename: info.filename.clone(),
+            total_bytes: info.expected_size,
+        });
+        Ok(info)
+    }
+
+    /// 获取平台的电子教材目录
+    pub async fn catalog(&self) -> Result<Vec<CatalogEntry>, AppError> {
+        catalog::fetch(self).await
+    }
+
+    /// 获取电子教材的分类标签树 (学段 → 学科 → 版本 → 年级)
+    pub async fn catalog_tags(&self) -> Result<Vec<TagNode>, AppError> {
+        catalog::fetch_tags(self).await
+    }
+
+    /// 用指定 Token 请求教材资源的第一个字节, 判断 Token 状态
+    pub async fn probe_token(&self, info: &TextbookInfo, token: &str) -> Result<TokenState, AppError> {
+        let url = info.download_url(token);
+        let mut har = None;
+        let response = self.send(preflight::probe_request(&self.http, &url), &mut har).await?
+            .map_err(|e| AppError::transfer(&url, e))?;
+        Ok(preflight::token_state(response.status(), &url))
+    }
+
+    /// 下载到文件并校验。进度通过事件发出。
+    /// 内容先写入同目录下的 `.part` 文件, 完整下载并通过校验后才替换目标文件, 中断或校验失败时不会破坏已有文件。
+    pub async fn download_to_path(&self, info: &TextbookInfo, path: &Path) -> DownloadOutcome {
+        let mut sink = PathSink { path, part_path: part_path(path), file: None };
+        let outcome = self.transfer(info, &mut sink).await;
+        if outcome.result.is_err() && sink.file.take().is_some() {
+            let _ = fs::remove_file(&sink.part_path).await;
+        }
+        outcome
+    }
+
+    /// 下载到任意异步写入目标, 边下载边校验。
+    /// 写入目标无法回退, 因此开始写入后的传输中断不再重试。
+    pub async fn download_to_writer<W: AsyncWrite + Unpin + Send>(&self, info: &TextbookInfo, writer: &mut W) -> DownloadOutcome {
+        let mut sink = WriterSink { writer, dirty: false };
+        self.transfer(info, &mut sink).await
+    }
+
+    async fn transfer<S: Sink>(&self, info: &TextbookInfo, sink: &mut S) -> DownloadOutcome {
+        if self.offline {
+            return DownloadOutcome { result: Err(AppError::Offline(tr!("client.offline_download", info.filename))), account: None };
+        }
+        let content_id = || info.content_id.clone();
+        let pool = &self.pool;
+        let mut account: Option<String> = None;
+        let result: Result<DownloadStatus, AppError> = async {
+            let policy = &self.retry.download;
+            let mut last_error: Option<AppError> = None;
+            let mut retry_after: Option<Duration> = None;
+            let mut attempt = 0;
+            // 账号级别的错误换一个账号立即重试, 不计入重试次数
+            let mut account_switches = 0;
+            let mut switch_account = false;
+            // 403 换一个账号再试一次, 仍然 403 时视为该条目无权访问
+            let mut forbidden_retried = false;
+            while attempt < policy.max_attempts || switch_account {
+                if switch_account {
+                    switch_account = false;
+                } else {
+                    attempt += 1;
+                    if attempt > 1 {
+                        let wait_time = policy.delay_for(attempt - 1, retry_after.take(), self.retry.max_retry_after);
+                        let reason = last_error.as_ref().map(|e| e.to_string()).unwrap_or_default();
+                        debug!("{}", tr!("client.download_retry", info.filename, attempt - 1, reason, format!("{:.1?}", wait_time)));
+                        self.emit(Event::Retry { content_id: content_id(), attempt: attempt - 1, delay_ms: wait_time.as_millis() as u64, reason });
+                        self.cancellable(tokio::time::sleep(wait_time)).await?;
+                    }
+                }
+
+                let lease = loop {
+                    if let Some(lease) = pool.acquire() {
+                        break lease;
+                    }
+                    // 账号都在冷却中时等待最早恢复的一个, 全部失效时才放弃
+                    let Some(wait) = pool.next_available_in() else {
+                        return Err(AppError::NoAccountAvailable { all_unauthorized: pool.all_unauthorized() });
+                    };
+                    debug!("{}", tr!("client.waiting_for_account", info.filename, format!("{:.1?}", wait)));
+                    self.cancellable(tokio::time::sleep(wait)).await?;
+                };
+                account = Some(lease.name.clone());
+                self.emit(Event::DownloadStarted {
+                    content_id: content_id(),
+                    filename: info.filename.clone(),
+                    total_bytes: info.expected_size,
+                    account: lease.name.clone(),
+                    mirror: mirror_of(&info.source_url),
+                    attempt,
+                });
+                let download_url = info.download_url(&lease.token);
+
+                let mut har = None;
+                let response = match self.send(self.http.get(&download_url), &mut har).await? {
+                    Ok(response) => response,
+                    Err(e) => {
+                        // 网络层错误 (e.g., DNS, TCP)
+                        last_error = Some(AppError::transfer(&download_url, e));
+                        continue;
+                    }
+                };
+
+                let status = response.status();
+                if !status.is_success() {
+                    let err = AppError::HttpStatus { status, mirror: mirror_of(&download_url) };
+                    let headers = response.headers().clone();
+                    self.record_body(response, &mut har).await?;
+                    let retry_forbidden = pool.len() > 1 && !forbidden_retried;
+                    if let Some(reason) = park_reason(status, &headers, retry_forbidden, self.retry.max_retry_after) {
+                        pool.park(lease.index, reason);
+                        forbidden_retried |= matches!(reason, ParkReason::Forbidden);
+                        if account_switches + 1 < pool.len() {
+                            account_switches += 1;
+                            debug!("{}", tr!("client.account_switched", info.filename, lease.name, status));
+                            self.emit(Event::AccountSwitched { content_id: content_id(), account: lease.name.clone(), http_status: status.as_u16() });
+                            last_error = Some(err);
+                            switch_account = true;
+                            continue;
+                        }
+                    }
+                    match retry::classify_status(status) {
+                        // 认证失败、资源已下架等永久性错误，重试无意义，直接返回
+                        StatusClass::Unauthorized | StatusClass::Permanent => return Err(err),
+                        StatusClass::Transient => {
+                            // 429 已暂停账号, 下次借出账号时会等到 Retry-After 结束
+                            if status != StatusCode::TOO_MANY_REQUESTS {
+                                retry_after = retry::retry_after(status, &headers);
+                            }
+                            last_error = Some(err);
+                            continue;
+                        }
+                    }
+                }
+
+                if !sink.begin().await? {
+                    // 写入目标无法回退, 只能放弃
+                    return Err(last_error.unwrap_or(AppError::DetailFetch(tr!("client.unknown_download_error"))));
+                }
+                let mut check = StreamCheck::default();
+                let mut stream = response.bytes_stream();
+                let mut interrupted = None;
+                while let Some(chunk_result) = self.cancellable(stream.next()).await? {
+                    match chunk_result {
+                        Ok(chunk) => {
+                            sink.write(&chunk).await?;
+                            check.update(&chunk);
+                            self.emit(Event::Progress { content_id: content_id(), bytes: check.size, total_bytes: info.expected_size });
+                        }
+                        Err(e) => {
+                            // 传输中断, 可以重试
+                            interrupted = Some(e);
+                            break;
+                        }
+                    }
+                }
+                if let Some(entry) = &mut har {
+                    entry.body_size(check.size);
+                    if let Some(e) = &interrupted { entry.error(e); }
+                }
+                if let Some(e) = interrupted {
+                    last_error = Some(AppError::transfer(&download_url, e));
+                    continue;
+                }
+                // 先校验再替换目标文件, 校验失败时保留原有文件
+                let status = check.judge(info);
+                sink.finish(status.is_success()).await?;
+                self.emit(Event::Validated { content_id: content_id(), status });
+                return Ok(status);
+            }
+            // 如果循环结束仍然失败，返回最后一次的错误
+            Err(last_error.unwrap_or(AppError::DetailFetch(tr!("client.unknown_download_error"))))
+        }.await;
+        DownloadOutcome { result, account }
+    }
+}
+
+/// 账号级别的错误对应的暂停原因。401 暂停到替换 Token 为止; 429 按 Retry-After (不超过 `max_wait`)
+/// 或冷却周期暂停, 单账号时同样暂停, 之后的请求等待账号恢复; 403 只在 `retry_forbidden` 时短暂暂停,
+/// 由其他账号再试一次。
+fn park_reason(status: StatusCode, headers: &reqwest::header::HeaderMap, retry_forbidden: bool, max_wait: Duration) -> Option<ParkReason> {
+    match status.as_u16() {
+        401 => Some(ParkReason::Unauthorized),
+        403 if retry_forbidden => Some(ParkReason::Forbidden),
+        429 => Some(ParkReason::RateLimited(retry::retry_after(status, headers).map(|after| after.min(max_wait)))),
+        _ => None,
+    }
+}
+
+/// 下载内容的写入目标
+trait Sink {
+    /// 收到成功响应、开始写入前调用。写入目标无法回到起点时返回 false。
+    async fn begin(&mut self) -> io::Result<bool>;
+    async fn write(&mut self, chunk: &[u8]) -> io::Result<()>;
+    /// 完整接收并校验后调用, `keep` 为 false 表示校验失败、应丢弃已写入的内容
+    async fn finish(&mut self, keep: bool) -> io::Result<()>;
+}
+
+fn part_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".part");
+    path.with_file_name(name)
+}
+
+struct PathSink<'a> {
+    path: &'a Path,
+    part_path: PathBuf,
+    file: Option<File>,
+}
+
+impl Sink for PathSink<'_> {
+    async fn begin(&mut self) -> io::Result<bool> {
+        // 每次尝试都重新创建临时文件, 失败的响应不会覆盖已有文件
+        self.file = Some(File::create(&self.part_path).await?);
+        Ok(true)
+    }
+
+    async fn write(&mut self, chunk: &[u8]) -> io::Result<()> {
+        match &mut self.file {
+            Some(file) => file.write_all(chunk).await,
+            None => Ok(()),
+        }
+    }
+
+    async fn finish(&mut self, keep: bool) -> io::Result<()> {
+        if let Some(mut file) = self.file.take() {
+            file.flush().await?;
+            drop(file);
+            if keep {
+                fs::rename(&self.part_path, self.path).await?;
+            } else {
+                fs::remove_file(&self.part_path).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+struct WriterSink<'a, W> {
+    writer: &'a mut W,
+    dirty: bool,
+}
+
+impl<W: AsyncWrite + Unpin + Send> Sink for WriterSink<'_, W> {
+    async fn begin(&mut self) -> io::Result<bool> {
+        Ok(!self.dirty)
+    }
+
+    async fn write(&mut self, chunk: &[u8]) -> io::Result<()> {
+        self.dirty = true;
+        self.writer.write_all(chunk).await
+    }
+
+    async fn finish(&mut self, _keep: bool) -> io::Result<()> {
+        self.writer.flush().await
+    }
+}
+
+/// 边下载边计算校验所需的文件头、MD5 和大小
+struct StreamCheck {
+    header: Vec<u8>,
+    md5: md5::Context,
+    size: u64,
+}
+
+impl Default for StreamCheck {
+    fn default() -> Self {
+        StreamCheck { header: Vec::with_capacity(PDF_MAGIC.len()), md5: md5::Context::new(), size: 0 }
+    }
+}
+
+impl StreamCheck {
+    fn update(&mut self, chunk: &[u8]) {
+        let missing = PDF_MAGIC.len().saturating_sub(self.header.len());
+        self.header.extend_from_slice(&chunk[..missing.min(chunk.len())]);
+        self.md5.consume(chunk);
+        self.size += chunk.len() as u64;
+    }
+
+    fn judge(self, info: &TextbookInfo) -> DownloadStatus {
+        let md5 = format!("{:x}", self.md5.compute());
+        judge(info, self.header == PDF_MAGIC, Some(&md5), Some(self.size))
+    }
+}
+
+fn judge(info: &TextbookInfo, has_pdf_header: bool, actual_md5: Option<&str>, actual_size: Option<u64>) -> DownloadStatus {
+    // 服务器偶尔会以 200 返回 HTML 错误页, 先检查文件头
+    if !has_pdf_header { return DownloadStatus::InvalidPdf; }
+    if info.expected_md5.is_some() && info.expected_md5.as_deref() == actual_md5 { return DownloadStatus::Success; }
+    if info.expected_size.is_some() && info.expected_size == actual_size { return DownloadStatus::Success; }
+    if info.expected_md5.is_none() && info.expected_size.is_none() { return DownloadStatus::SuccessNoValidation; }
+    if info.expected_md5.is_some() { DownloadStatus::Md5ValidationFailed } else { DownloadStatus::SizeValidationFailed }
+}
+
+async fn calculate_file_md5(path: &Path) -> Result<String, io::Error> {
+    let mut file = File::open(path).await?;
+    let mut context = md5::Context::new();
+    let mut buffer = [0; 8192];
+    loop {
+        let n = file.read(&mut buffer).await?;
+        if n == 0 { break; }
+        context.consume(&buffer[..n]);
+    }
+    Ok(format!("{:x}", context.compute()))
+}
+
+async fn has_pdf_header(path: &Path) -> Result<bool, io::Error> {
+    let mut file = File::open(path).await?;
+    let mut header = [0u8; 5];
+    match file.read_exact(&mut header).await {
+        Ok(_) => Ok(header == PDF_MAGIC),
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// 按教材详情中的 MD5 / 大小校验本地文件
+pub async fn verify_file(path: &Path, info: &TextbookInfo) -> Result<DownloadStatus, AppError> {
+    if !path.exists() { return Ok(DownloadStatus::SizeValidationFailed); }
+    let header = has_pdf_header(path).await?;
+    let md5 = match info.expected_md5 {
+        Some(_) if header => calculate_file_md5(path).await.ok(),
+        _ => None,
+    };
+    let size = fs::metadata(path).await.ok().map(|m| m.len());
+    Ok(judge(info, header, md5.as_deref(), size))
+}
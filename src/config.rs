@@ -0,0 +1,99 @@
+// src/config.rs
+
+use crate::logfile::{LogFormat, Rotation};
+use crate::{tr, AppError};
+use sed_dl::endpoints::Endpoints;
+use sed_dl::token_pool::PoolStrategy;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// 配置文件 (TOML) 内容。所有字段均可省略, 命令行参数优先于配置文件。
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub retry: RetrySection,
+    pub token: TokenSection,
+    pub accounts: AccountsSection,
+    pub log: LogSection,
+    /// 接口主机和地址模板
+    pub endpoints: Endpoints,
+    pub cache: CacheSection,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct CacheSection {
+    /// 设为 false 时不缓存教材详情和目录
+    pub enabled: Option<bool>,
+    pub dir: Option<PathBuf>,
+    /// 缓存在此秒数内直接使用, 不向服务器确认
+    pub ttl_secs: Option<u64>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogSection {
+    /// 日志文件路径, 设置后即启用文件日志
+    pub file: Option<PathBuf>,
+    pub format: Option<LogFormat>,
+    /// 写入文件的最低级别, 如 `debug`
+    pub level: Option<String>,
+    /// `never`、`daily` 或大小, 如 `10MB`
+    pub rotate: Option<Rotation>,
+    /// 保留的轮转文件数
+    pub keep: Option<usize>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct AccountsSection {
+    /// 参与分摊下载的已保存 Token 名称, `["all"]` 表示全部
+    pub names: Vec<String>,
+    pub strategy: Option<PoolStrategy>,
+    pub cooldown_secs: Option<u64>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct TokenSection {
+    /// `token check` 默认使用的探测教材 (URL 或 Content ID)
+    pub probe: Option<String>,
+    /// 存放 Token 的文件
+    pub file: Option<PathBuf>,
+    /// git-credential 风格的凭据助手命令
+    pub helper: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct RetrySection {
+    pub details_attempts: Option<u32>,
+    pub download_attempts: Option<u32>,
+    pub base_delay_ms: Option<u64>,
+    pub max_delay_ms: Option<u64>,
+    pub jitter: Option<bool>,
+    pub max_retry_after_secs: Option<u64>,
+    /// 批量结束后重试临时失败项之前的等待秒数
+    pub pass_delay_secs: Option<u64>,
+}
+
+/// 默认配置文件位置, 如 Linux 下的 `~/.config/sed-dl/config.toml`。
+pub fn default_config_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("sed-dl").join("config.toml"))
+}
+
+impl Config {
+    /// 加载配置: 显式指定的路径必须存在; 默认路径不存在时使用空配置。
+    pub fn load(explicit: Option<&Path>) -> Result<Config, AppError> {
+        let path = match explicit {
+            Some(p) => p.to_path_buf(),
+            None => match default_config_path() {
+                Some(p) if p.exists() => p,
+                _ => return Ok(Config::default()),
+            },
+        };
+        let content = std::fs::read_to_string(&path)
+            .map_err(|e| AppError::Config(tr!("file.read_failed", path.display(), e)))?;
+        toml::from_str(&content).map_err(|e| AppError::Config(tr!("file.bad_format", path.display(), e)))
+    }
+}
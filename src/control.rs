@@ -0,0 +1,453 @@
+// src/control.rs
+
+//! 批量下载运行中的控制: 暂停/继续、调整并发数、插队、取消单项, 通过 Unix 域套接字接收 `sed-dl ctl` 的命令
+
+use crate::{DownloadItem, TaskResult};
+use sed_dl::{tr, AppError, CancellationToken, DownloadStatus};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::sync::{watch, OwnedSemaphorePermit, Semaphore};
+
+/// 默认的控制套接字位置
+pub fn default_socket_path() -> PathBuf {
+    dirs::runtime_dir().unwrap_or_else(std::env::temp_dir).join("sed-dl.sock")
+}
+
+/// `sed-dl ctl` 发送的命令, 每个连接一行 JSON
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+pub enum Request {
+    /// 暂停派发新任务; 指定项目时暂停该项 (下载中的会中止, 继续后重新下载)
+    Pause { item: Option<String> },
+    Resume { item: Option<String> },
+    Concurrency { value: usize },
+    /// 移到队首
+    Bump { item: String },
+    Cancel { item: String },
+    /// 重新排队本次运行中失败或已取消的项
+    Retry { item: String },
+    State,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Response {
+    pub ok: bool,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state: Option<StateDump>,
+}
+
+impl Response {
+    fn ok(message: impl Into<String>) -> Response {
+        Response { ok: true, message: message.into(), state: None }
+    }
+
+    fn error(message: impl Into<String>) -> Response {
+        Response { ok: false, message: message.into(), state: None }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct StateDump {
+    pub paused: bool,
+    pub max_concurrent: usize,
+    pub running: Vec<ItemState>,
+    pub queued: Vec<ItemState>,
+    /// 状态码 -> 已结束的项数
+    pub finished: BTreeMap<String, usize>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ItemState {
+    pub content_id: String,
+    pub input: String,
+    pub paused: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub elapsed_secs: Option<u64>,
+}
+
+struct RunningItem {
+    input: String,
+    started: Instant,
+    cancel: CancellationToken,
+}
+
+#[derive(Default)]
+struct ControlState {
+    paused: bool,
+    limit: usize,
+    /// 调低并发数时尚未收回的许可, 由结束的下载归还时抵扣
+    owed: usize,
+    /// 调度循环正在派发; 结束后不再接受插队和重试
+    round_open: bool,
+    /// 被单独暂停的项
+    held: HashSet<String>,
+    /// 待移到队首的项, 按命令顺序
+    bumps: Vec<String>,
+    /// 待取消的排队项
+    cancels: HashSet<String>,
+    /// 待重新排队的项
+    retries: Vec<DownloadItem>,
+    /// 失败或已取消的项: Content ID -> (下载项, 状态码)
+    failed: HashMap<String, (DownloadItem, &'static str)>,
+    /// 队列清空后仍保持运行, 供仪表盘重试失败项
+    keep_open: bool,
+    running: HashMap<String, RunningItem>,
+    /// 最近一次同步的排队项
+    queued: Vec<DownloadItem>,
+    finished: BTreeMap<&'static str, usize>,
+}
+
+/// 一次批量下载的控制状态, 由调度循环和控制套接字共享
+pub struct Controller {
+    semaphore: Arc<Semaphore>,
+    state: Mutex<ControlState>,
+    changed: watch::Sender<()>,
+}
+
+impl Controller {
+    pub fn new(max_concurrent: usize) -> Controller {
+        let limit = max_concurrent.max(1);
+        Controller {
+            semaphore: Arc::new(Semaphore::new(limit)),
+            state: Mutex::new(ControlState { limit, ..Default::default() }),
+            changed: watch::Sender::new(()),
+        }
+    }
+
+    /// 限制同时下载数的信号量, 调整并发数时增减其中的许可
+    pub fn semaphore(&self) -> Arc<Semaphore> {
+        self.semaphore.clone()
+    }
+
+    /// 控制状态变化的通知
+    pub fn subscribe(&self) -> watch::Receiver<()> {
+        self.changed.subscribe()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.state.lock().unwrap().paused
+    }
+
+    pub fn is_held(&self, content_id: &str) -> bool {
+        self.state.lock().unwrap().held.contains(content_id)
+    }
+
+    pub fn keep_open(&self) -> bool {
+        self.state.lock().unwrap().keep_open
+    }
+
+    pub fn set_keep_open(&self, keep_open: bool) {
+        self.state.lock().unwrap().keep_open = keep_open;
+        self.changed.send_replace(());
+    }
+
+    /// 开始一轮派发
+    pub fn begin_round(&self) {
+        self.state.lock().unwrap().round_open = true;
+    }
+
+    /// 结束本轮派发, 先应用最后到达的命令, 之后的插队和重试命令返回错误。
+    /// 未停止且有新的重试项时不结束, 返回 false。
+    pub fn end_round(&self, queue: &mut VecDeque<DownloadItem>, results: &mut Vec<TaskResult>, stopped: bool) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if !state.retries.is_empty() && !stopped { return false; }
+        state.apply(queue, results);
+        state.round_open = false;
+        true
+    }
+
+    /// 归还下载结束的许可; 调低并发数后还有未收回的名额时直接收回
+    pub fn release(&self, permit: OwnedSemaphorePermit) {
+        let mut state = self.state.lock().unwrap();
+        if state.owed > 0 {
+            state.owed -= 1;
+            permit.forget();
+        }
+    }
+
+    /// 把插队、取消和重试命令应用到队列, 并记录当前排队项
+    pub fn apply(&self, queue: &mut VecDeque<DownloadItem>, results: &mut Vec<TaskResult>) {
+        self.state.lock().unwrap().apply(queue, results);
+    }
+
+    /// 队列中第一个未被暂停的项
+    pub fn next_index(&self, queue: &VecDeque<DownloadItem>) -> Option<usize> {
+        let state = self.state.lock().unwrap();
+        queue.iter().position(|(id, _)| !state.held.contains(id))
+    }
+
+    pub fn started(&self, item: &DownloadItem, cancel: CancellationToken) {
+        let running = RunningItem { input: item.1.clone(), started: Instant::now(), cancel };
+        self.state.lock().unwrap().running.insert(item.0.clone(), running);
+    }
+
+    pub fn finished(&self, result: &TaskResult) {
+        let mut state = self.state.lock().unwrap();
+        state.running.remove(&result.content_id);
+        // 被暂停而中止的项会重新排队, 不计入结果
+        if result.status == DownloadStatus::Cancelled && state.held.contains(&result.content_id) {
+            return;
+        }
+        *state.finished.entry(result.status.code()).or_default() += 1;
+        if !result.status.is_success() {
+            state.failed.insert(result.content_id.clone(), (result.item(), result.status.code()));
+        }
+    }
+
+    pub fn handle(&self, request: Request) -> Response {
+        let mut state = self.state.lock().unwrap();
+        let response = match request {
+            Request::Pause { item: None } => {
+                state.paused = true;
+                Response::ok(tr!("ctl.reply.paused_all"))
+            }
+            Request::Resume { item: None } => {
+                state.paused = false;
+                Response::ok(tr!("ctl.reply.resumed_all"))
+            }
+            Request::Pause { item: Some(id) } => {
+                if let Some(running) = state.running.get(&id) {
+                    running.cancel.cancel();
+                    state.held.insert(id.clone());
+                    Response::ok(tr!("ctl.reply.paused_running", id))
+                } else if state.queued.iter().any(|(queued, _)| *queued == id) {
+                    state.held.insert(id.clone());
+                    Response::ok(tr!("ctl.reply.paused", id))
+                } else {
+                    Response::error(tr!("ctl.reply.not_in_queue", id))
+                }
+            }
+            Request::Resume { item: Some(id) } => {
+                if state.held.remove(&id) {
+                    Response::ok(tr!("ctl.reply.resumed", id))
+                } else {
+                    Response::error(tr!("ctl.reply.not_paused", id))
+                }
+            }
+            Request::Concurrency { value: 0 } => Response::error(tr!("ctl.reply.concurrency_zero")),
+            Request::Concurrency { value } => {
+                let previous = state.limit;
+                if value > previous {
+                    // 先抵扣尚未收回的名额, 其余补回信号量
+                    let added = value - previous;
+                    let cancelled = added.min(state.owed);
+                    state.owed -= cancelled;
+                    self.semaphore.add_permits(added - cancelled);
+                } else if value < previous {
+                    // 空闲的许可立即收回, 其余等进行中的下载结束时收回
+                    let removed = previous - value;
+                    let idle = removed.min(self.semaphore.available_permits());
+                    let taken = match self.semaphore.try_acquire_many(idle as u32) {
+                        Ok(permits) => { permits.forget(); idle }
+                        Err(_) => 0,
+                    };
+                    state.owed += removed - taken;
+                }
+                state.limit = value;
+                Response::ok(tr!("ctl.reply.concurrency", previous, value))
+            }
+            Request::Bump { .. } | Request::Retry { .. } if !state.round_open => Response::error(tr!("ctl.reply.round_ended")),
+            Request::Bump { item } => {
+                if state.queued.iter().any(|(id, _)| *id == item) {
+                    state.bumps.push(item.clone());
+                    Response::ok(tr!("ctl.reply.bumped", item))
+                } else {
+                    Response::error(tr!("ctl.reply.not_queued", item))
+                }
+            }
+            Request::Cancel { item } => {
+                if let Some(running) = state.running.get(&item) {
+                    running.cancel.cancel();
+                    state.held.remove(&item);
+                    Response::ok(tr!("ctl.reply.cancelled", item))
+                } else if state.round_open && state.queued.iter().any(|(id, _)| *id == item) {
+                    state.cancels.insert(item.clone());
+                    Response::ok(tr!("ctl.reply.cancelled", item))
+                } else {
+                    Response::error(tr!("ctl.reply.not_in_queue", item))
+                }
+            }
+            Request::Retry { item } => match state.failed.remove(&item) {
+                Some((download_item, code)) => {
+                    if let Some(count) = state.finished.get_mut(code) {
+                        *count -= 1;
+                        if *count == 0 { state.finished.remove(code); }
+                    }
+                    state.retries.push(download_item);
+                    Response::ok(tr!("ctl.reply.requeued", item))
+                }
+                None => Response::error(tr!("ctl.reply.not_failed", item)),
+            },
+            Request::State => {
+                let mut response = Response::ok("");
+                response.state = Some(state.dump());
+                return response;
+            }
+        };
+        drop(state);
+        self.changed.send_replace(());
+        response
+    }
+}
+
+impl ControlState {
+    fn apply(&mut self, queue: &mut VecDeque<DownloadItem>, results: &mut Vec<TaskResult>) {
+        for item in std::mem::take(&mut self.retries) {
+            results.retain(|r| r.content_id != item.0);
+            queue.push_front(item);
+        }
+        for content_id in std::mem::take(&mut self.bumps) {
+            if let Some(pos) = queue.iter().position(|(id, _)| *id == content_id) {
+                let item = queue.remove(pos).expect("位置有效");
+                queue.push_front(item);
+            }
+        }
+        if !self.cancels.is_empty() {
+            let cancels = std::mem::take(&mut self.cancels);
+            queue.retain(|item| {
+                if !cancels.contains(&item.0) { return true; }
+                let mut result = TaskResult::new(item.clone(), String::new(), DownloadStatus::Cancelled);
+                result.failure = Some(crate::FailureInfo { message: Some(tr!("ctl.cancelled_by_command")), ..Default::default() });
+                results.push(result);
+                self.held.remove(&item.0);
+                self.failed.insert(item.0.clone(), (item.clone(), DownloadStatus::Cancelled.code()));
+                *self.finished.entry(DownloadStatus::Cancelled.code()).or_default() += 1;
+                false
+            });
+        }
+        self.queued = queue.iter().cloned().collect();
+    }
+
+    fn dump(&self) -> StateDump {
+        let mut running: Vec<ItemState> = self.running.iter().map(|(id, item)| ItemState {
+            content_id: id.clone(),
+            input: item.input.clone(),
+            paused: false,
+            elapsed_secs: Some(item.started.elapsed().as_secs()),
+        }).collect();
+        running.sort_by_key(|item| std::cmp::Reverse(item.elapsed_secs));
+        StateDump {
+            paused: self.paused,
+            max_concurrent: self.limit,
+            running,
+            queued: self.queued.iter().map(|(id, input)| ItemState {
+                content_id: id.clone(),
+                input: input.clone(),
+                paused: self.held.contains(id),
+                elapsed_secs: None,
+            }).collect(),
+            finished: self.finished.iter().map(|(code, n)| (code.to_string(), *n)).collect(),
+        }
+    }
+}
+
+/// 监听中的控制套接字, 释放时删除套接字文件
+#[cfg(unix)]
+pub struct ControlSocket {
+    path: PathBuf,
+}
+
+#[cfg(unix)]
+impl Drop for ControlSocket {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// 在 `path` 上监听控制命令。已有其他批量下载在使用该套接字时返回错误。
+#[cfg(unix)]
+pub async fn listen(controller: Arc<Controller>, path: PathBuf) -> Result<ControlSocket, AppError> {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::{UnixListener, UnixStream};
+
+    if path.exists() {
+        if UnixStream::connect(&path).await.is_ok() {
+            return Err(AppError::InvalidInput(tr!("ctl.socket_in_use", path.display())));
+        }
+        // 上次异常退出留下的套接字文件
+        std::fs::remove_file(&path)?;
+    }
+    let listener = UnixListener::bind(&path)?;
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let controller = controller.clone();
+            tokio::spawn(async move {
+                let (reader, mut writer) = stream.into_split();
+                let mut line = String::new();
+                if BufReader::new(reader).read_line(&mut line).await.is_err() { return; }
+                let response = match serde_json::from_str(&line) {
+                    Ok(request) => controller.handle(request),
+                    Err(e) => Response::error(tr!("ctl.reply.bad_request", e)),
+                };
+                if let Ok(mut json) = serde_json::to_string(&response) {
+                    json.push('\n');
+                    let _ = writer.write_all(json.as_bytes()).await;
+                }
+            });
+        }
+    });
+    Ok(ControlSocket { path })
+}
+
+/// 向运行中的批量下载发送一条命令 (`sed-dl ctl`)
+#[cfg(unix)]
+pub async fn send(path: &std::path::Path, request: &Request) -> Result<Response, AppError> {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    let stream = tokio::net::UnixStream::connect(path).await
+        .map_err(|e| AppError::InvalidInput(tr!("ctl.connect_failed", path.display(), e)))?;
+    let (reader, mut writer) = stream.into_split();
+    let mut json = serde_json::to_string(request)?;
+    json.push('\n');
+    writer.write_all(json.as_bytes()).await?;
+    let mut line = String::new();
+    BufReader::new(reader).read_line(&mut line).await?;
+    Ok(serde_json::from_str(&line)?)
+}
+
+#[cfg(not(unix))]
+pub async fn send(_path: &std::path::Path, _request: &Request) -> Result<Response, AppError> {
+    Err(AppError::InvalidInput(tr!("ctl.unsupported")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn concurrency_changes_keep_permits_in_step_with_limit() {
+        let controller = Controller::new(3);
+        let semaphore = controller.semaphore();
+        let busy: Vec<_> = (0..2).map(|_| semaphore.clone().try_acquire_owned().unwrap()).collect();
+
+        // 1 个空闲许可立即收回, 另 1 个等下载结束时收回
+        assert!(controller.handle(Request::Concurrency { value: 1 }).ok);
+        assert_eq!(semaphore.available_permits(), 0);
+        // 调高时先抵扣未收回的名额
+        assert!(controller.handle(Request::Concurrency { value: 2 }).ok);
+        for permit in busy { controller.release(permit); }
+        assert_eq!(semaphore.available_permits(), 2);
+    }
+
+    #[test]
+    fn retry_after_the_round_ended_is_rejected() {
+        let controller = Controller::new(1);
+        let item = ("id".to_string(), "input".to_string());
+        let mut result = TaskResult::new(item, String::new(), DownloadStatus::Cancelled);
+        controller.begin_round();
+        controller.finished(&result);
+        assert!(controller.end_round(&mut VecDeque::new(), &mut Vec::new(), false));
+        assert!(!controller.handle(Request::Retry { item: "id".into() }).ok);
+
+        result.content_id = "other".into();
+        controller.begin_round();
+        controller.finished(&result);
+        assert!(controller.handle(Request::Retry { item: "other".into() }).ok);
+        // 最后一次检查后到达的重试不会结束本轮
+        assert!(!controller.end_round(&mut VecDeque::new(), &mut Vec::new(), false));
+    }
+}
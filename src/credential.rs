@@ -0,0 +1,115 @@
+// src/credential.rs
+
+use crate::{tr, AppError};
+use std::fmt;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// 读取 Token 的环境变量名
+pub static TOKEN_ENV: &str = "SED_DL_TOKEN";
+/// 传给凭据助手的主机名
+static CREDENTIAL_HOST: &str = "smartedu.cn";
+
+/// 非交互的 Token 来源, 按优先级排列
+#[derive(Debug, Clone, Default)]
+pub struct CredentialOptions {
+    pub cli_token: Option<String>,
+    pub token_file: Option<PathBuf>,
+    pub helper: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CredentialSource {
+    CommandLine,
+    Env,
+    File(PathBuf),
+    Helper(String),
+}
+
+impl fmt::Display for CredentialSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CredentialSource::CommandLine => write!(f, "{}", tr!("credential.source.command_line")),
+            CredentialSource::Env => write!(f, "{}", tr!("credential.source.env", TOKEN_ENV)),
+            CredentialSource::File(path) => write!(f, "{}", tr!("credential.source.file", path.display())),
+            CredentialSource::Helper(cmd) => write!(f, "{}", tr!("credential.source.helper", cmd)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Credential {
+    pub token: String,
+    pub source: CredentialSource,
+}
+
+/// 依次尝试 命令行 -> 环境变量 -> Token 文件 -> 凭据助手。都没有时返回 `None`。
+/// 显式配置的文件或助手出错时直接报错, 不会悄悄跳过。
+pub fn resolve(opts: &CredentialOptions) -> Result<Option<Credential>, AppError> {
+    if let Some(token) = non_empty(opts.cli_token.as_deref()) {
+        return Ok(Some(Credential { token, source: CredentialSource::CommandLine }));
+    }
+    if let Some(token) = non_empty(std::env::var(TOKEN_ENV).ok().as_deref()) {
+        return Ok(Some(Credential { token, source: CredentialSource::Env }));
+    }
+    if let Some(path) = &opts.token_file {
+        let token = read_token_file(path)?;
+        return Ok(Some(Credential { token, source: CredentialSource::File(path.clone()) }));
+    }
+    if let Some(helper) = &opts.helper {
+        let token = run_helper(helper)?;
+        return Ok(Some(Credential { token, source: CredentialSource::Helper(helper.clone()) }));
+    }
+    Ok(None)
+}
+
+fn non_empty(value: Option<&str>) -> Option<String> {
+    value.map(str::trim).filter(|v| !v.is_empty()).map(str::to_string)
+}
+
+fn read_token_file(path: &Path) -> Result<String, AppError> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| AppError::Credential(tr!("credential.file_unreadable", path.display(), e)))?;
+    non_empty(Some(&content))
+        .ok_or_else(|| AppError::Credential(tr!("credential.file_empty", path.display())))
+}
+
+/// 以 git-credential 助手协议调用外部命令: 执行 `<helper> get`,
+/// 通过标准输入传入 `protocol`/`host`, 从输出的 `password=` 行读取 Token。
+fn run_helper(helper: &str) -> Result<String, AppError> {
+    let command_line = format!("{} get", helper);
+    let mut child = shell_command(&command_line)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .map_err(|e| AppError::Credential(tr!("credential.helper_spawn", helper, e)))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // 助手可能不读取标准输入, 写入失败不影响结果
+        let _ = write!(stdin, "protocol=https\nhost={}\n\n", CREDENTIAL_HOST);
+    }
+    let output = child.wait_with_output()
+        .map_err(|e| AppError::Credential(tr!("credential.helper_failed", helper, e)))?;
+    if !output.status.success() {
+        return Err(AppError::Credential(tr!("credential.helper_status", helper, output.status)));
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| line.strip_prefix("password=").and_then(|v| non_empty(Some(v))))
+        .ok_or_else(|| AppError::Credential(tr!("credential.helper_no_password", helper)))
+}
+
+#[cfg(windows)]
+fn shell_command(command_line: &str) -> Command {
+    let mut cmd = Command::new("cmd");
+    cmd.args(["/C", command_line]);
+    cmd
+}
+
+#[cfg(not(windows))]
+fn shell_command(command_line: &str) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.args(["-c", command_line]);
+    cmd
+}
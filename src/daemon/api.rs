@@ -0,0 +1,136 @@
+// src/daemon/api.rs
+
+use super::jobs::{Job, JobState};
+use super::Daemon;
+use axum::extract::{Path, Query, State};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use sed_dl::{get_content_id, i18n, tr, DownloadStatus};
+use serde::Deserialize;
+use serde_json::json;
+use std::fmt::Write;
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+
+/// API 错误, 以 `{"error": "..."}` 返回
+pub struct ApiError(StatusCode, String);
+
+impl ApiError {
+    pub fn not_found(id: u64) -> ApiError {
+        ApiError(StatusCode::NOT_FOUND, tr!("daemon.not_found", id))
+    }
+
+    pub fn conflict(message: String) -> ApiError {
+        ApiError(StatusCode::CONFLICT, message)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(json!({ "error": self.1 }))).into_response()
+    }
+}
+
+pub fn router(daemon: Arc<Daemon>) -> Router {
+    Router::new()
+        .route("/jobs", get(list_jobs).post(create_jobs))
+        .route("/jobs/{id}", get(get_job))
+        .route("/jobs/{id}/cancel", post(cancel_job))
+        .route("/jobs/{id}/retry", post(retry_job))
+        .route("/accounts/reload", post(reload_accounts))
+        .route("/metrics", get(metrics))
+        .with_state(daemon)
+}
+
+#[derive(Deserialize)]
+struct CreateJobs {
+    /// URL 或 Content ID
+    items: Vec<String>,
+}
+
+/// 添加任务。同一教材已在排队或下载中时返回已有的任务。
+async fn create_jobs(State(daemon): State<Arc<Daemon>>, Json(body): Json<CreateJobs>) -> Result<(StatusCode, Json<serde_json::Value>), ApiError> {
+    let invalid: Vec<&str> = body.items.iter().filter(|i| get_content_id(i).is_none()).map(String::as_str).collect();
+    if body.items.is_empty() || !invalid.is_empty() {
+        return Err(ApiError(StatusCode::BAD_REQUEST, tr!("daemon.invalid_items", format!("{:?}", invalid))));
+    }
+    let jobs: Vec<Job> = daemon.update(|store| {
+        body.items.iter().map(|input| {
+            let content_id = get_content_id(input).expect("已校验");
+            match store.find_active(&content_id) {
+                Some(job) => job.clone(),
+                None => store.add(content_id, input.clone()).clone(),
+            }
+        }).collect()
+    });
+    daemon.wake.notify_one();
+    Ok((StatusCode::CREATED, Json(json!({ "jobs": jobs }))))
+}
+
+#[derive(Deserialize)]
+struct ListQuery {
+    state: Option<JobState>,
+}
+
+async fn list_jobs(State(daemon): State<Arc<Daemon>>, Query(query): Query<ListQuery>) -> Json<serde_json::Value> {
+    let store = daemon.store.lock().unwrap();
+    let jobs: Vec<&Job> = store.jobs().filter(|j| query.state.is_none_or(|s| j.state == s)).collect();
+    Json(json!({ "jobs": jobs }))
+}
+
+async fn get_job(State(daemon): State<Arc<Daemon>>, Path(id): Path<u64>) -> Result<Json<Job>, ApiError> {
+    daemon.store.lock().unwrap().get(id).cloned().map(Json).ok_or(ApiError::not_found(id))
+}
+
+async fn cancel_job(State(daemon): State<Arc<Daemon>>, Path(id): Path<u64>) -> Result<Json<Job>, ApiError> {
+    daemon.cancel(id).map(Json)
+}
+
+async fn retry_job(State(daemon): State<Arc<Daemon>>, Path(id): Path<u64>) -> Result<Json<Job>, ApiError> {
+    daemon.retry(id).map(Json)
+}
+
+/// 重新读取 Token, 返回恢复使用的账号
+async fn reload_accounts(State(daemon): State<Arc<Daemon>>) -> Result<Json<serde_json::Value>, ApiError> {
+    let reloaded = daemon.reload_tokens().await.map_err(|e| ApiError(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(Json(json!({ "reloaded": reloaded })))
+}
+
+/// Prometheus 文本格式的指标
+async fn metrics(State(daemon): State<Arc<Daemon>>) -> impl IntoResponse {
+    let metrics = &daemon.metrics;
+    let finished = metrics.finished.lock().unwrap().clone();
+    let succeeded: u64 = finished.iter()
+        .filter(|(code, _)| DownloadStatus::from_code(code).is_some_and(DownloadStatus::is_success))
+        .map(|(_, n)| n)
+        .sum();
+    let failed: u64 = finished.values().sum::<u64>() - succeeded;
+    let mut states = [0u64; JobState::ALL.len()];
+    for job in daemon.store.lock().unwrap().jobs() {
+        states[job.state as usize] += 1;
+    }
+
+    let mut out = String::new();
+    let _ = writeln!(out, "# HELP sed_dl_downloaded_bytes_total {}", i18n::t("metrics.downloaded_bytes"));
+    let _ = writeln!(out, "# TYPE sed_dl_downloaded_bytes_total counter");
+    let _ = writeln!(out, "sed_dl_downloaded_bytes_total {}", metrics.downloaded_bytes.load(Ordering::Relaxed));
+    let _ = writeln!(out, "# HELP sed_dl_downloads_succeeded_total {}", i18n::t("metrics.succeeded"));
+    let _ = writeln!(out, "# TYPE sed_dl_downloads_succeeded_total counter");
+    let _ = writeln!(out, "sed_dl_downloads_succeeded_total {}", succeeded);
+    let _ = writeln!(out, "# HELP sed_dl_downloads_failed_total {}", i18n::t("metrics.failed"));
+    let _ = writeln!(out, "# TYPE sed_dl_downloads_failed_total counter");
+    let _ = writeln!(out, "sed_dl_downloads_failed_total {}", failed);
+    let _ = writeln!(out, "# HELP sed_dl_jobs_finished_total {}", i18n::t("metrics.finished"));
+    let _ = writeln!(out, "# TYPE sed_dl_jobs_finished_total counter");
+    for (code, count) in &finished {
+        let _ = writeln!(out, "sed_dl_jobs_finished_total{{status=\"{}\"}} {}", code, count);
+    }
+    let _ = writeln!(out, "# HELP sed_dl_jobs {}", i18n::t("metrics.jobs"));
+    let _ = writeln!(out, "# TYPE sed_dl_jobs gauge");
+    for state in JobState::ALL {
+        let _ = writeln!(out, "sed_dl_jobs{{state=\"{}\"}} {}", state.name(), states[state as usize]);
+    }
+    ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], out)
+}
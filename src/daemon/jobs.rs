@@ -0,0 +1,168 @@
+// src/daemon/jobs.rs
+
+use sed_dl::{tr, AppError, DownloadStatus};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum JobState {
+    Queued,
+    Running,
+    Done,
+    Failed,
+    Cancelled,
+}
+
+impl JobState {
+    pub const ALL: [JobState; 5] = [JobState::Queued, JobState::Running, JobState::Done, JobState::Failed, JobState::Cancelled];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            JobState::Queued => "queued",
+            JobState::Running => "running",
+            JobState::Done => "done",
+            JobState::Failed => "failed",
+            JobState::Cancelled => "cancelled",
+        }
+    }
+
+    /// 排队或下载中
+    pub fn is_active(self) -> bool {
+        matches!(self, JobState::Queued | JobState::Running)
+    }
+}
+
+/// 一个下载任务
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Job {
+    pub id: u64,
+    pub content_id: String,
+    pub input: String,
+    pub state: JobState,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<DownloadStatus>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// 当前这次下载尝试已传输的字节数
+    #[serde(default)]
+    pub bytes: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_bytes: Option<u64>,
+    pub created_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<String>,
+}
+
+impl Job {
+    /// 恢复为排队状态, 清除上次的结果
+    pub fn requeue(&mut self) {
+        self.state = JobState::Queued;
+        self.status = None;
+        self.error = None;
+        self.bytes = 0;
+        self.finished_at = None;
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct JobFile {
+    next_id: u64,
+    jobs: Vec<Job>,
+}
+
+/// 持久化的任务队列, 每次状态变化后整体写回 JSON 文件
+pub struct JobStore {
+    path: PathBuf,
+    next_id: u64,
+    jobs: BTreeMap<u64, Job>,
+}
+
+impl JobStore {
+    /// 加载任务文件。上次退出时仍在下载的任务重新排队。
+    pub fn load(path: &Path) -> Result<JobStore, AppError> {
+        let file: JobFile = if path.exists() {
+            let content = std::fs::read_to_string(path)?;
+            serde_json::from_str(&content)
+                .map_err(|e| AppError::Config(tr!("daemon.jobs_file_malformed", path.display(), e)))?
+        } else {
+            JobFile::default()
+        };
+        let mut jobs = BTreeMap::new();
+        for mut job in file.jobs {
+            if job.state == JobState::Running { job.requeue(); }
+            jobs.insert(job.id, job);
+        }
+        let next_id = file.next_id.max(jobs.keys().next_back().map_or(1, |id| id + 1));
+        Ok(JobStore { path: path.to_path_buf(), next_id, jobs })
+    }
+
+    /// 写回任务文件 (先写临时文件再重命名)
+    pub fn save(&self) -> Result<(), AppError> {
+        let file = JobFile { next_id: self.next_id, jobs: self.jobs.values().cloned().collect() };
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let tmp_path = self.path.with_extension("tmp");
+        std::fs::write(&tmp_path, serde_json::to_string_pretty(&file)?)?;
+        std::fs::rename(&tmp_path, &self.path)?;
+        Ok(())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn add(&mut self, content_id: String, input: String) -> &Job {
+        let id = self.next_id;
+        self.next_id += 1;
+        let job = Job {
+            id,
+            content_id,
+            input,
+            state: JobState::Queued,
+            status: None,
+            file: None,
+            account: None,
+            error: None,
+            bytes: 0,
+            total_bytes: None,
+            created_at: chrono::Local::now().to_rfc3339(),
+            finished_at: None,
+        };
+        self.jobs.entry(id).or_insert(job)
+    }
+
+    pub fn get(&self, id: u64) -> Option<&Job> {
+        self.jobs.get(&id)
+    }
+
+    pub fn get_mut(&mut self, id: u64) -> Option<&mut Job> {
+        self.jobs.get_mut(&id)
+    }
+
+    pub fn jobs(&self) -> impl Iterator<Item = &Job> {
+        self.jobs.values()
+    }
+
+    /// 同一教材尚未结束的任务
+    pub fn find_active(&self, content_id: &str) -> Option<&Job> {
+        self.jobs.values().find(|j| j.state.is_active() && j.content_id == content_id)
+    }
+
+    pub fn find_running_mut(&mut self, content_id: &str) -> Option<&mut Job> {
+        self.jobs.values_mut().find(|j| j.state == JobState::Running && j.content_id == content_id)
+    }
+
+    /// 取出最早排队的任务并标记为下载中
+    pub fn start_next(&mut self) -> Option<Job> {
+        let job = self.jobs.values_mut().find(|j| j.state == JobState::Queued)?;
+        job.state = JobState::Running;
+        Some(job.clone())
+    }
+}
@@ -0,0 +1,253 @@
+// src/daemon/mod.rs
+
+//! `sed-dl serve`: 常驻后台, 通过本地 HTTP/JSON API 接收下载任务
+
+mod api;
+mod jobs;
+
+use crate::{process_single_task, resolve_accounts, tr, AuthBreaker, Cli, TaskContext, SYMBOL_INFO, SYMBOL_WARNING};
+use jobs::{Job, JobState, JobStore};
+use log::{error, info, warn};
+use sed_dl::{AppError, CancellationToken, DownloadStatus, Event, SmartEduClient};
+use std::collections::{BTreeMap, HashMap};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{mpsc, Notify, Semaphore};
+use tokio_util::task::TaskTracker;
+
+/// 默认任务文件位置, 如 Linux 下的 `~/.local/share/sed-dl/jobs.json`
+pub fn default_jobs_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("sed-dl").join("jobs.json"))
+}
+
+/// 供 `/metrics` 输出的累计计数, 服务重启后清零
+#[derive(Default)]
+struct Metrics {
+    downloaded_bytes: AtomicU64,
+    /// 错误码 -> 结束的任务数
+    finished: Mutex<BTreeMap<&'static str, u64>>,
+}
+
+struct Daemon {
+    store: Mutex<JobStore>,
+    client: SmartEduClient,
+    args: Arc<Cli>,
+    /// 账号池使用的账号名称, 重新读取 Token 时使用
+    accounts: Vec<String>,
+    dest_folder: PathBuf,
+    max_concurrent: usize,
+    /// 有新任务排队时唤醒调度循环
+    wake: Notify,
+    /// 下载中的任务 -> 取消令牌
+    running: Mutex<HashMap<u64, CancellationToken>>,
+    metrics: Metrics,
+    shutdown: CancellationToken,
+}
+
+impl Daemon {
+    /// 修改任务并写回任务文件
+    fn update<R>(&self, f: impl FnOnce(&mut JobStore) -> R) -> R {
+        let mut store = self.store.lock().unwrap();
+        let result = f(&mut store);
+        if let Err(e) = store.save() {
+            error!("{}", tr!("daemon.save_failed", store.path().display(), e));
+        }
+        result
+    }
+
+    fn on_event(&self, event: &Event) {
+        let mut store = self.store.lock().unwrap();
+        match event {
+            Event::DownloadStarted { content_id, total_bytes, account, .. } => {
+                if let Some(job) = store.find_running_mut(content_id) {
+                    job.bytes = 0;
+                    job.total_bytes = *total_bytes;
+                    job.account = Some(account.clone());
+                }
+            }
+            Event::Progress { content_id, bytes, total_bytes } => {
+                if let Some(job) = store.find_running_mut(content_id) {
+                    self.metrics.downloaded_bytes.fetch_add(bytes.saturating_sub(job.bytes), Ordering::Relaxed);
+                    job.bytes = *bytes;
+                    job.total_bytes = *total_bytes;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn start_next(&self) -> Option<Job> {
+        let mut store = self.store.lock().unwrap();
+        let job = store.start_next()?;
+        if let Err(e) = store.save() {
+            error!("{}", tr!("daemon.save_failed", store.path().display(), e));
+        }
+        Some(job)
+    }
+
+    /// 取消任务: 排队中的直接标记为已取消, 下载中的中止下载
+    fn cancel(&self, id: u64) -> Result<Job, api::ApiError> {
+        if let Some(token) = self.running.lock().unwrap().get(&id) {
+            token.cancel();
+            return self.store.lock().unwrap().get(id).cloned().ok_or(api::ApiError::not_found(id));
+        }
+        self.update(|store| {
+            let job = store.get_mut(id).ok_or(api::ApiError::not_found(id))?;
+            if job.state != JobState::Queued {
+                return Err(api::ApiError::conflict(tr!("daemon.cancel_finished", id)));
+            }
+            job.state = JobState::Cancelled;
+            job.status = Some(DownloadStatus::Cancelled);
+            job.finished_at = Some(chrono::Local::now().to_rfc3339());
+            Ok(job.clone())
+        })
+    }
+
+    /// 重新排队失败或已取消的任务
+    fn retry(&self, id: u64) -> Result<Job, api::ApiError> {
+        let job = self.update(|store| {
+            let job = store.get_mut(id).ok_or(api::ApiError::not_found(id))?;
+            if !matches!(job.state, JobState::Failed | JobState::Cancelled) {
+                return Err(api::ApiError::conflict(tr!("daemon.retry_unfinished", id, job.state.name())));
+            }
+            job.requeue();
+            Ok(job.clone())
+        })?;
+        self.wake.notify_one();
+        Ok(job)
+    }
+
+    /// 通过 Token 来源 (环境变量、Token 文件、Token 存储等) 重新读取 Token, Token 有变化的账号恢复使用。
+    /// 返回恢复使用的账号名称。服务中不会提示输入。
+    async fn reload_tokens(&self) -> Result<Vec<String>, AppError> {
+        let mut args = (*self.args).clone();
+        args.no_input = true;
+        let accounts = resolve_accounts(&args, &self.accounts).await?;
+        let reloaded = self.client.pool().reload(&accounts);
+        if !reloaded.is_empty() {
+            info!("{}", tr!("daemon.tokens_reloaded", SYMBOL_INFO, reloaded.join(", ")));
+        }
+        Ok(reloaded)
+    }
+
+    async fn run_job(self: Arc<Self>, job: Job) {
+        // 所有账号都因 401 停用时, 先重新读取 Token, 以便替换 Token 文件或存储后无需重启服务
+        if self.client.pool().all_unauthorized() {
+            match self.reload_tokens().await {
+                Ok(reloaded) if reloaded.is_empty() => warn!("{}", tr!("daemon.tokens_unchanged", SYMBOL_WARNING)),
+                Ok(_) => {}
+                Err(e) => warn!("{}", tr!("daemon.tokens_reload_failed", SYMBOL_WARNING, e)),
+            }
+        }
+        let client = self.client.child();
+        self.running.lock().unwrap().insert(job.id, client.cancellation_token().clone());
+        let ctx = Arc::new(TaskContext {
+            client,
+            args: self.args.clone(),
+            dest_folder: self.dest_folder.clone(),
+            // 所有任务共用同一目录, 各自使用默认文件名
+            is_batch: true,
+            breaker: AuthBreaker::default(),
+            shutdown: self.shutdown.clone(),
+            journal: None,
+            control: None,
+        });
+        let result = process_single_task(ctx, (job.content_id.clone(), job.input.clone())).await;
+        self.running.lock().unwrap().remove(&job.id);
+
+        // 服务退出时中断的任务保持排队, 下次启动后继续
+        if self.shutdown.is_cancelled() && result.status == DownloadStatus::Cancelled {
+            self.update(|store| store.get_mut(job.id).map(Job::requeue));
+            return;
+        }
+        *self.metrics.finished.lock().unwrap().entry(result.status.code()).or_default() += 1;
+        self.update(|store| {
+            let Some(job) = store.get_mut(job.id) else { return };
+            job.state = match result.status {
+                status if status.is_success() => JobState::Done,
+                DownloadStatus::Cancelled => JobState::Cancelled,
+                _ => JobState::Failed,
+            };
+            job.status = Some(result.status);
+            job.file = result.path.clone();
+            job.account = result.account.clone().or(job.account.take());
+            job.error = result.failure.as_ref().and_then(|f| f.message.clone());
+            job.finished_at = Some(chrono::Local::now().to_rfc3339());
+        });
+    }
+
+    /// 调度循环: 有空闲名额时按排队顺序启动任务
+    async fn schedule(self: Arc<Self>, tracker: TaskTracker) {
+        let semaphore = Arc::new(Semaphore::new(self.max_concurrent));
+        loop {
+            let permit = tokio::select! {
+                permit = semaphore.clone().acquire_owned() => permit.expect("信号量不会被关闭"),
+                _ = self.shutdown.cancelled() => return,
+            };
+            let job = loop {
+                if let Some(job) = self.start_next() { break job; }
+                tokio::select! {
+                    _ = self.wake.notified() => {}
+                    _ = self.shutdown.cancelled() => return,
+                }
+            };
+            info!("{}", tr!("daemon.job_started", SYMBOL_INFO, job.id, job.input));
+            let daemon = self.clone();
+            tracker.spawn(async move {
+                daemon.run_job(job).await;
+                drop(permit);
+            });
+        }
+    }
+}
+
+/// 运行后台服务, 直到收到 Ctrl-C
+pub async fn serve(client_builder: sed_dl::SmartEduClientBuilder, args: Arc<Cli>, accounts: Vec<String>, dest_folder: PathBuf, listen: SocketAddr, jobs_path: &Path) -> Result<(), AppError> {
+    let store = JobStore::load(jobs_path)?;
+    let queued = store.jobs().filter(|j| j.state == JobState::Queued).count();
+    let (events_tx, mut events_rx) = mpsc::unbounded_channel();
+    let shutdown = CancellationToken::new();
+    let client = client_builder.event_sink(Arc::new(events_tx)).cancellation(shutdown.clone()).build()?;
+    let daemon = Arc::new(Daemon {
+        store: Mutex::new(store),
+        client,
+        args: args.clone(),
+        accounts,
+        dest_folder,
+        max_concurrent: args.max_concurrent_downloads.max(1),
+        wake: Notify::new(),
+        running: Mutex::new(HashMap::new()),
+        metrics: Metrics::default(),
+        shutdown: shutdown.clone(),
+    });
+
+    let listener = tokio::net::TcpListener::bind(listen).await?;
+    info!("{}", tr!("daemon.started", SYMBOL_INFO, listener.local_addr()?, jobs_path.display(), queued, daemon.dest_folder.display()));
+
+    let consumer = daemon.clone();
+    tokio::spawn(async move {
+        while let Some(event) = events_rx.recv().await {
+            consumer.on_event(&event);
+        }
+    });
+    let tracker = TaskTracker::new();
+    let scheduler = tokio::spawn(daemon.clone().schedule(tracker.clone()));
+
+    let signal = shutdown.clone();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            warn!("{}", tr!("daemon.stopping", SYMBOL_WARNING));
+            signal.cancel();
+        }
+    });
+
+    axum::serve(listener, api::router(daemon.clone()))
+        .with_graceful_shutdown(shutdown.cancelled_owned())
+        .await?;
+    let _ = scheduler.await;
+    tracker.close();
+    tracker.wait().await;
+    Ok(())
+}
@@ -0,0 +1,448 @@
+// src/dashboard.rs
+
+//! `--dashboard`: 全屏的下载仪表盘, 取代逐项的进度条
+
+use crate::control::{Controller, Request, StateDump};
+use crate::logfile::strip_ansi;
+use indicatif::{HumanBytes, HumanDuration};
+use once_cell::sync::Lazy;
+use ratatui::crossterm::event::{self, Event as TermEvent, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Cell, List, ListItem, Paragraph, Row, Table, TableState};
+use ratatui::Frame;
+use sed_dl::{i18n, tr, AppError, CancellationToken, DownloadStatus, Event, EventSink};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// 日志面板保留的行数
+const LOG_LINES: usize = 200;
+
+/// 仪表盘显示期间捕获的日志; 为 `None` 时日志照常写到标准错误
+static LOG_CAPTURE: Lazy<Mutex<Option<VecDeque<String>>>> = Lazy::new(|| Mutex::new(None));
+
+/// 日志输出目标: 仪表盘显示时写入日志面板, 否则写到标准错误
+pub struct LogWriter;
+
+impl Write for LogWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut capture = LOG_CAPTURE.lock().unwrap();
+        let Some(lines) = capture.as_mut() else {
+            return io::stderr().write(buf);
+        };
+        let text = String::from_utf8_lossy(buf);
+        for line in text.lines().map(strip_ansi).filter(|l| !l.trim().is_empty()) {
+            if lines.len() == LOG_LINES { lines.pop_front(); }
+            lines.push_back(line.into_owned());
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        io::stderr().flush()
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum TaskState {
+    Queued,
+    Downloading,
+    Retrying,
+    Done,
+    Failed,
+    Cancelled,
+}
+
+impl TaskState {
+    /// 按状态排序时的先后
+    fn rank(self) -> u8 {
+        match self {
+            TaskState::Downloading => 0,
+            TaskState::Retrying => 1,
+            TaskState::Queued => 2,
+            TaskState::Failed => 3,
+            TaskState::Cancelled => 4,
+            TaskState::Done => 5,
+        }
+    }
+}
+
+struct TaskRow {
+    content_id: String,
+    name: String,
+    state: TaskState,
+    status: Option<DownloadStatus>,
+    bytes: u64,
+    total_bytes: Option<u64>,
+    /// 平滑后的速度 (字节/秒)
+    speed: f64,
+    last_sample: Option<(Instant, u64)>,
+    retries: u32,
+    mirror: Option<String>,
+    account: Option<String>,
+}
+
+impl TaskRow {
+    fn eta(&self) -> Option<Duration> {
+        let remaining = self.total_bytes?.checked_sub(self.bytes)?;
+        (self.state == TaskState::Downloading && self.speed > 1.0).then(|| Duration::from_secs_f64(remaining as f64 / self.speed))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SortKey {
+    Order,
+    State,
+    Speed,
+    Progress,
+    Name,
+}
+
+impl SortKey {
+    fn next(self) -> SortKey {
+        match self {
+            SortKey::Order => SortKey::State,
+            SortKey::State => SortKey::Speed,
+            SortKey::Speed => SortKey::Progress,
+            SortKey::Progress => SortKey::Name,
+            SortKey::Name => SortKey::Order,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            SortKey::Order => i18n::t("dashboard.sort.order"),
+            SortKey::State => i18n::t("dashboard.column.state"),
+            SortKey::Speed => i18n::t("dashboard.column.speed"),
+            SortKey::Progress => i18n::t("dashboard.column.progress"),
+            SortKey::Name => i18n::t("dashboard.column.name"),
+        }
+    }
+}
+
+struct DashState {
+    rows: Vec<TaskRow>,
+    index: HashMap<String, usize>,
+    downloaded: u64,
+    sort: SortKey,
+    /// 选中行的 Content ID, 排序变化时保持选中同一项
+    selected: Option<String>,
+    message: Option<String>,
+    /// 按 q 的次数: 第一次停止派发, 第二次中止下载
+    quit_presses: u8,
+}
+
+/// 订阅下载事件并在全屏界面中显示, 通过 [`Controller`] 控制单个任务
+pub struct Dashboard {
+    state: Mutex<DashState>,
+    control: Arc<Controller>,
+    started: Instant,
+    shutdown: CancellationToken,
+    abort: CancellationToken,
+    closed: CancellationToken,
+}
+
+impl Dashboard {
+    pub fn new(control: Arc<Controller>, shutdown: CancellationToken, abort: CancellationToken) -> Dashboard {
+        Dashboard {
+            state: Mutex::new(DashState {
+                rows: Vec::new(),
+                index: HashMap::new(),
+                downloaded: 0,
+                sort: SortKey::Order,
+                selected: None,
+                message: None,
+                quit_presses: 0,
+            }),
+            control,
+            started: Instant::now(),
+            shutdown,
+            abort,
+            closed: CancellationToken::new(),
+        }
+    }
+
+    /// 关闭界面, 恢复终端
+    pub fn close(&self) {
+        self.closed.cancel();
+    }
+
+    /// 显示界面直到 [`close`](Self::close) 被调用
+    pub fn run(&self) -> Result<(), AppError> {
+        *LOG_CAPTURE.lock().unwrap() = Some(VecDeque::new());
+        let mut terminal = ratatui::init();
+        let result = (|| {
+            while !self.closed.is_cancelled() {
+                let dump = self.control.handle(Request::State).state.expect("状态查询总是返回状态");
+                terminal.draw(|frame| self.draw(frame, &dump))?;
+                if event::poll(Duration::from_millis(250))?
+                    && let TermEvent::Key(key) = event::read()?
+                    && key.kind == KeyEventKind::Press
+                {
+                    let ctrl_c = key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+                    self.on_key(if ctrl_c { KeyCode::Char('q') } else { key.code }, &dump);
+                }
+            }
+            Ok(())
+        })();
+        ratatui::restore();
+        *LOG_CAPTURE.lock().unwrap() = None;
+        result
+    }
+
+    fn send(&self, state: &mut DashState, request: Request) -> bool {
+        let response = self.control.handle(request);
+        state.message = Some(response.message);
+        response.ok
+    }
+
+    fn on_key(&self, key: KeyCode, dump: &StateDump) {
+        let mut state = self.state.lock().unwrap();
+        state.message = None;
+        let order = sorted_rows(&state);
+        let position = state.selected.as_ref().and_then(|id| order.iter().position(|&i| state.rows[i].content_id == *id));
+        let selected = position.map(|p| state.rows[order[p]].content_id.clone());
+        match key {
+            KeyCode::Up | KeyCode::Down | KeyCode::Char('k') | KeyCode::Char('j') if !order.is_empty() => {
+                let up = matches!(key, KeyCode::Up | KeyCode::Char('k'));
+                let next = match position {
+                    Some(p) if up => p.saturating_sub(1),
+                    Some(p) => (p + 1).min(order.len() - 1),
+                    None => 0,
+                };
+                state.selected = Some(state.rows[order[next]].content_id.clone());
+            }
+            KeyCode::Char('s') => state.sort = state.sort.next(),
+            KeyCode::Char(' ') => {
+                self.send(&mut state, if dump.paused { Request::Resume { item: None } } else { Request::Pause { item: None } });
+            }
+            KeyCode::Char('+') => {
+                self.send(&mut state, Request::Concurrency { value: dump.max_concurrent + 1 });
+            }
+            KeyCode::Char('-') => {
+                self.send(&mut state, Request::Concurrency { value: dump.max_concurrent.saturating_sub(1) });
+            }
+            KeyCode::Char('p') if let Some(item) = selected => {
+                let held = dump.queued.iter().any(|q| q.paused && q.content_id == item);
+                self.send(&mut state, if held { Request::Resume { item: Some(item) } } else { Request::Pause { item: Some(item) } });
+            }
+            KeyCode::Char('c') if let Some(item) = selected => {
+                self.send(&mut state, Request::Cancel { item });
+            }
+            KeyCode::Char('r') if let Some(item) = selected && self.send(&mut state, Request::Retry { item: item.clone() }) => {
+                let index = state.index[&item];
+                let row = &mut state.rows[index];
+                row.state = TaskState::Queued;
+                row.status = None;
+                row.bytes = 0;
+                row.speed = 0.0;
+            }
+            KeyCode::Char('q') | KeyCode::Esc => {
+                if dump.running.is_empty() && dump.queued.is_empty() {
+                    self.control.set_keep_open(false);
+                    return;
+                }
+                state.quit_presses += 1;
+                if state.quit_presses == 1 {
+                    self.shutdown.cancel();
+                    self.control.set_keep_open(false);
+                    state.message = Some(tr!("dashboard.stopping"));
+                } else {
+                    self.abort.cancel();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn draw(&self, frame: &mut Frame, dump: &StateDump) {
+        let state = self.state.lock().unwrap();
+        let [header, table_area, log_area, footer] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(5),
+            Constraint::Length(8),
+            Constraint::Length(1),
+        ]).areas(frame.area());
+
+        let count = |s: TaskState| state.rows.iter().filter(|r| r.state == s).count();
+        let speed: f64 = state.rows.iter().filter(|r| r.state == TaskState::Downloading).map(|r| r.speed).sum();
+        let elapsed = self.started.elapsed();
+        let average = state.downloaded as f64 / elapsed.as_secs_f64().max(1.0);
+        let run_state = if dump.paused { i18n::t("ctl.paused").yellow() } else { i18n::t("ctl.running").green() };
+        frame.render_widget(Line::from(vec![
+            Span::raw("sed-dl ").bold(),
+            run_state,
+            Span::raw(tr!(
+                "dashboard.header",
+                dump.max_concurrent,
+                count(TaskState::Downloading) + count(TaskState::Retrying),
+                count(TaskState::Queued),
+                count(TaskState::Done),
+                count(TaskState::Failed) + count(TaskState::Cancelled),
+                HumanBytes(speed as u64),
+                HumanBytes(average as u64),
+                HumanBytes(state.downloaded),
+                HumanDuration(Duration::from_secs(elapsed.as_secs())),
+            )),
+        ]), header);
+
+        let held: HashSet<&str> = dump.queued.iter().filter(|q| q.paused).map(|q| q.content_id.as_str()).collect();
+        let order = sorted_rows(&state);
+        let rows: Vec<Row> = order.iter().map(|&i| {
+            let row = &state.rows[i];
+            let (label, color) = match row.state {
+                TaskState::Queued if held.contains(row.content_id.as_str()) => (i18n::t("ctl.paused"), Color::Yellow),
+                TaskState::Queued => (i18n::t("ctl.queued"), Color::Gray),
+                TaskState::Downloading => (i18n::t("ctl.downloading"), Color::Cyan),
+                TaskState::Retrying => (i18n::t("dashboard.state.retrying"), Color::Yellow),
+                TaskState::Done => (i18n::t("dashboard.state.done"), Color::Green),
+                TaskState::Failed => (row.status.map_or(i18n::t("dashboard.state.failed"), DownloadStatus::code), Color::Red),
+                TaskState::Cancelled => (i18n::t("dashboard.state.cancelled"), Color::DarkGray),
+            };
+            let progress = match (row.total_bytes, row.state) {
+                (_, TaskState::Done) => "100%".to_string(),
+                (Some(total), _) if total > 0 => format!("{:>3}% {}", row.bytes * 100 / total, HumanBytes(row.bytes)),
+                _ if row.bytes > 0 => HumanBytes(row.bytes).to_string(),
+                _ => String::new(),
+            };
+            let speed = if row.state == TaskState::Downloading { format!("{}/s", HumanBytes(row.speed as u64)) } else { String::new() };
+            let eta = row.eta().map(|d| HumanDuration(d).to_string()).unwrap_or_default();
+            Row::new(vec![
+                Cell::from(row.name.clone()),
+                Cell::from(label).fg(color),
+                Cell::from(progress),
+                Cell::from(speed),
+                Cell::from(eta),
+                Cell::from(if row.retries > 0 { row.retries.to_string() } else { String::new() }),
+                Cell::from(row.mirror.clone().unwrap_or_default()),
+                Cell::from(row.account.clone().unwrap_or_default()),
+            ])
+        }).collect();
+        let widths = [
+            Constraint::Fill(1),
+            Constraint::Length(14),
+            Constraint::Length(16),
+            Constraint::Length(12),
+            Constraint::Length(10),
+            Constraint::Length(4),
+            Constraint::Length(26),
+            Constraint::Length(10),
+        ];
+        let table = Table::new(rows, widths)
+            .header(Row::new([
+                "dashboard.column.name", "dashboard.column.state", "dashboard.column.progress", "dashboard.column.speed",
+                "dashboard.column.eta", "dashboard.column.retries", "dashboard.column.mirror", "dashboard.column.account",
+            ].map(i18n::t)).bold())
+            .block(Block::default().borders(Borders::ALL).title(tr!("dashboard.tasks_title", state.rows.len(), state.sort.label())))
+            .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        let mut table_state = TableState::default()
+            .with_selected(state.selected.as_ref().and_then(|id| order.iter().position(|&i| state.rows[i].content_id == *id)));
+        frame.render_stateful_widget(table, table_area, &mut table_state);
+
+        let capture = LOG_CAPTURE.lock().unwrap();
+        let visible = log_area.height.saturating_sub(2) as usize;
+        let logs: Vec<ListItem> = capture.iter().flatten()
+            .skip(capture.as_ref().map_or(0, |l| l.len().saturating_sub(visible)))
+            .map(|l| ListItem::new(l.clone()))
+            .collect();
+        frame.render_widget(List::new(logs).block(Block::default().borders(Borders::ALL).title(tr!("dashboard.logs_title"))), log_area);
+
+        let footer_text = match &state.message {
+            Some(message) => Line::from(message.clone().yellow()),
+            None => Line::from(i18n::t("dashboard.help")).dim(),
+        };
+        frame.render_widget(Paragraph::new(footer_text), footer);
+    }
+}
+
+/// 按当前排序方式排列的行下标
+fn sorted_rows(state: &DashState) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..state.rows.len()).collect();
+    let rows = &state.rows;
+    let progress = |r: &TaskRow| r.total_bytes.filter(|&t| t > 0).map_or(0, |t| r.bytes * 1000 / t);
+    match state.sort {
+        SortKey::Order => {}
+        SortKey::State => order.sort_by_key(|&i| rows[i].state.rank()),
+        SortKey::Speed => order.sort_by(|&a, &b| rows[b].speed.total_cmp(&rows[a].speed)),
+        SortKey::Progress => order.sort_by_key(|&i| std::cmp::Reverse(progress(&rows[i]))),
+        SortKey::Name => order.sort_by(|&a, &b| rows[a].name.cmp(&rows[b].name)),
+    }
+    order
+}
+
+impl EventSink for Dashboard {
+    fn on_event(&self, event: &Event) {
+        let mut state = self.state.lock().unwrap();
+        let state = &mut *state;
+        if let Event::Queued { content_id } = event {
+            if !state.index.contains_key(content_id) {
+                state.index.insert(content_id.clone(), state.rows.len());
+                state.rows.push(TaskRow {
+                    content_id: content_id.clone(),
+                    name: content_id.clone(),
+                    state: TaskState::Queued,
+                    status: None,
+                    bytes: 0,
+                    total_bytes: None,
+                    speed: 0.0,
+                    last_sample: None,
+                    retries: 0,
+                    mirror: None,
+                    account: None,
+                });
+                if state.selected.is_none() { state.selected = Some(content_id.clone()); }
+            }
+            return;
+        }
+        let Some(&index) = state.index.get(event.content_id()) else { return };
+        let row = &mut state.rows[index];
+        match event {
+            Event::DetailsResolved { filename, total_bytes, .. } => {
+                row.name = filename.clone();
+                row.total_bytes = *total_bytes;
+            }
+            Event::DownloadStarted { total_bytes, account, mirror, .. } => {
+                row.state = TaskState::Downloading;
+                row.total_bytes = *total_bytes;
+                row.bytes = 0;
+                row.speed = 0.0;
+                row.last_sample = Some((Instant::now(), 0));
+                row.account = Some(account.clone());
+                row.mirror = Some(mirror.clone());
+            }
+            Event::Progress { bytes, total_bytes, .. } => {
+                state.downloaded += bytes.saturating_sub(row.bytes);
+                let now = Instant::now();
+                if let Some((at, sampled)) = row.last_sample {
+                    let elapsed = now.duration_since(at).as_secs_f64();
+                    if elapsed >= 0.5 {
+                        let instant = bytes.saturating_sub(sampled) as f64 / elapsed;
+                        row.speed = if row.speed == 0.0 { instant } else { row.speed * 0.7 + instant * 0.3 };
+                        row.last_sample = Some((now, *bytes));
+                    }
+                }
+                row.bytes = *bytes;
+                row.total_bytes = *total_bytes;
+            }
+            Event::Retry { .. } => {
+                row.state = TaskState::Retrying;
+                row.retries += 1;
+            }
+            Event::Finished { status, .. } => {
+                row.status = Some(*status);
+                row.speed = 0.0;
+                row.state = match status {
+                    s if s.is_success() => TaskState::Done,
+                    // 被单独暂停的项会重新排队
+                    DownloadStatus::Cancelled if self.control.is_held(&row.content_id) => TaskState::Queued,
+                    DownloadStatus::Cancelled => TaskState::Cancelled,
+                    _ => TaskState::Failed,
+                };
+            }
+            _ => {}
+        }
+    }
+}
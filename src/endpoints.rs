@@ -0,0 +1,83 @@
+// src/endpoints.rs
+
+//! 平台接口地址表。地址模板中的 `{host}` 替换为候选主机, `{id}` 替换为 Content ID。
+//! 某个主机连接失败或返回暂时性错误时, 重试会换用下一个主机。
+
+use crate::{tr, AppError};
+use serde::Deserialize;
+
+/// 平台接口地址表, 可在配置文件的 `[endpoints]` 中覆盖
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Endpoints {
+    /// 候选主机 (含协议, 如 `https://s-file-1.ykt.cbern.com.cn`), 按顺序尝试
+    pub hosts: Vec<String>,
+    /// 教材详情
+    pub details: String,
+    /// 电子教材目录的版本文件, 其中列出了各个分片的地址
+    pub catalog_version: String,
+    /// 电子教材的分类标签树
+    pub catalog_tags: String,
+}
+
+impl Default for Endpoints {
+    fn default() -> Self {
+        Endpoints {
+            hosts: ["s-file-2", "s-file-1", "s-file-3"].iter()
+                .map(|name| format!("https://{}.ykt.cbern.com.cn", name))
+                .collect(),
+            details: "{host}/zxx/ndrv2/resources/tch_material/details/{id}.json".into(),
+            catalog_version: "{host}/zxx/ndrs/resources/tch_material/version/data_version.json".into(),
+            catalog_tags: "{host}/zxx/ndrs/tags/tch_material_tag.json".into(),
+        }
+    }
+}
+
+impl Endpoints {
+    /// 使用指定的主机, 其余沿用默认地址模板 (如指向本地的测试服务器)
+    pub fn with_hosts(hosts: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Endpoints { hosts: hosts.into_iter().map(Into::into).collect(), ..Endpoints::default() }
+    }
+
+    pub fn validate(&self) -> Result<(), AppError> {
+        if self.hosts.is_empty() {
+            return Err(AppError::Config(tr!("endpoints.no_hosts")));
+        }
+        for host in &self.hosts {
+            match reqwest::Url::parse(host) {
+                Ok(url) if matches!(url.scheme(), "http" | "https") => {}
+                _ => return Err(AppError::Config(tr!("endpoints.invalid_host", host))),
+            }
+        }
+        if !self.details.contains("{id}") {
+            return Err(AppError::Config(tr!("endpoints.details_missing_id")));
+        }
+        Ok(())
+    }
+
+    /// 用第 `host` 个主机生成地址
+    pub fn render(&self, template: &str, host: usize, id: &str) -> String {
+        let host = self.hosts[host % self.hosts.len()].trim_end_matches('/');
+        template.replace("{host}", host).replace("{id}", id)
+    }
+
+    /// 地址所在的候选主机, 以及去掉主机后的路径部分
+    pub fn split_host<'a>(&self, url: &'a str) -> Option<(usize, &'a str)> {
+        self.hosts.iter().enumerate().find_map(|(index, host)| {
+            let rest = url.strip_prefix(host.trim_end_matches('/'))?;
+            (rest.is_empty() || rest.starts_with('/')).then_some((index, rest))
+        })
+    }
+
+    /// 同一地址在各个主机上的副本, 从地址本身所在的主机开始依次排列。
+    /// 地址不在候选主机上时只返回它本身。
+    pub fn mirrors(&self, url: &str) -> Vec<(Option<usize>, String)> {
+        match self.split_host(url) {
+            Some((index, rest)) => (0..self.hosts.len())
+                .map(|k| (index + k) % self.hosts.len())
+                .map(|i| (Some(i), format!("{}{}", self.hosts[i].trim_end_matches('/'), rest)))
+                .collect(),
+            None => vec![(None, url.to_string())],
+        }
+    }
+}
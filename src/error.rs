@@ -0,0 +1,115 @@
+// src/error.rs
+
+use crate::{tr, DownloadStatus};
+use std::io;
+
+#[derive(thiserror::Error, Debug)]
+pub enum AppError {
+    #[error("{}", tr!("error.reqwest", .0))]
+    Reqwest(#[from] reqwest::Error),
+    #[error("{}", tr!("error.io", .0))]
+    Io(#[from] std::io::Error),
+    #[error("{}", tr!("error.json", .0))]
+    Json(#[from] serde_json::Error),
+    #[error("{}", tr!("error.detail_fetch", .0))]
+    DetailFetch(String),
+    #[error("{}", tr!("error.invalid_input", .0))]
+    InvalidInput(String),
+    #[error("{}", tr!("error.dir_creation", .0))]
+    DirCreation(String),
+    #[error("{}", tr!("error.http_status", .status, .mirror))]
+    HttpStatus { status: reqwest::StatusCode, mirror: String },
+    #[error("{}", tr!("error.transfer", .mirror, .source))]
+    Transfer { mirror: String, #[source] source: reqwest::Error },
+    #[error("{}", tr!("error.config", .0))]
+    Config(String),
+    #[error("{}", tr!("error.token_expired"))]
+    TokenExpired,
+    #[error("{}", tr!("error.credential", .0))]
+    Credential(String),
+    #[error("{}", tr!("error.token_store", .0))]
+    TokenStore(String),
+    #[error("{}", tr!("error.browser_import", .0))]
+    BrowserImport(String),
+    #[error("{}", tr!("error.offline", .0))]
+    Offline(String),
+    #[error("{}", tr!("error.cancelled"))]
+    Cancelled,
+    #[error("{}", tr!("error.no_account"))]
+    NoAccountAvailable { all_unauthorized: bool },
+}
+
+impl AppError {
+    /// 构造传输错误。去掉错误中的 URL, 避免把带 accessToken 的地址打印到日志里。
+    pub fn transfer(url: &str, source: reqwest::Error) -> Self {
+        AppError::Transfer { mirror: mirror_of(url), source: source.without_url() }
+    }
+
+    /// 将错误归类为最终的下载状态
+    pub fn status(&self) -> DownloadStatus {
+        match self {
+            AppError::HttpStatus { status, .. } => match status.as_u16() {
+                401 => DownloadStatus::TokenError,
+                403 => DownloadStatus::Forbidden,
+                404 | 410 => DownloadStatus::ContentRemoved,
+                429 => DownloadStatus::RateLimited,
+                _ => DownloadStatus::HttpError,
+            },
+            AppError::Transfer { source, .. } | AppError::Reqwest(source) => classify_reqwest_error(source),
+            AppError::Io(e) => classify_io_error(e),
+            AppError::Json(_) | AppError::DetailFetch(_) => DownloadStatus::FailGetDetails,
+            AppError::Offline(_) => DownloadStatus::NetworkError,
+            AppError::Cancelled => DownloadStatus::Cancelled,
+            AppError::NoAccountAvailable { all_unauthorized: true } => DownloadStatus::TokenError,
+            AppError::NoAccountAvailable { all_unauthorized: false } => DownloadStatus::RateLimited,
+            _ => DownloadStatus::UnexpectedError,
+        }
+    }
+
+    pub fn http_code(&self) -> Option<u16> {
+        match self {
+            AppError::HttpStatus { status, .. } => Some(status.as_u16()),
+            AppError::Transfer { source, .. } | AppError::Reqwest(source) => source.status().map(|s| s.as_u16()),
+            _ => None,
+        }
+    }
+
+    pub fn mirror(&self) -> Option<&str> {
+        match self {
+            AppError::HttpStatus { mirror, .. } | AppError::Transfer { mirror, .. } => Some(mirror),
+            _ => None,
+        }
+    }
+}
+
+fn classify_reqwest_error(e: &reqwest::Error) -> DownloadStatus {
+    if e.is_timeout() { return DownloadStatus::Timeout; }
+    if e.is_decode() { return DownloadStatus::FailGetDetails; }
+    // rustls 的错误被包在 io::Error 里, 需要沿着错误链逐层查找
+    let mut current: Option<&(dyn std::error::Error + 'static)> = Some(e);
+    while let Some(err) = current {
+        if err.is::<rustls::Error>() { return DownloadStatus::TlsError; }
+        if let Some(io_err) = err.downcast_ref::<io::Error>() {
+            if io_err.kind() == io::ErrorKind::TimedOut { return DownloadStatus::Timeout; }
+            if io_err.get_ref().is_some_and(|inner| inner.is::<rustls::Error>()) { return DownloadStatus::TlsError; }
+        }
+        current = err.source();
+    }
+    DownloadStatus::NetworkError
+}
+
+fn classify_io_error(e: &io::Error) -> DownloadStatus {
+    match e.kind() {
+        io::ErrorKind::StorageFull => DownloadStatus::DiskFull,
+        io::ErrorKind::PermissionDenied | io::ErrorKind::ReadOnlyFilesystem => DownloadStatus::PermissionDenied,
+        _ => DownloadStatus::UnexpectedError,
+    }
+}
+
+/// 从下载地址中提取主机名, 用于在日志和汇总中标明来源镜像
+pub fn mirror_of(url: &str) -> String {
+    reqwest::Url::parse(url).ok()
+        .and_then(|u| u.host_str().map(str::to_string))
+        .unwrap_or_else(|| tr!("error.unknown_mirror"))
+}
+
@@ -0,0 +1,77 @@
+// src/events.rs
+
+use crate::DownloadStatus;
+use serde::Serialize;
+
+/// 下载过程中的事件。客户端在查询详情和下载时发出除 `Queued`/`Finished` 以外的事件,
+/// 后两者由调度下载的一方 (如命令行的批量下载) 通过 [`SmartEduClient::emit`](crate::SmartEduClient::emit) 发出。
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum Event {
+    /// 加入下载队列
+    Queued { content_id: String },
+    /// 已获取教材详情
+    DetailsResolved { content_id: String, filename: String, total_bytes: Option<u64> },
+    /// 开始一次下载尝试
+    DownloadStarted { content_id: String, filename: String, total_bytes: Option<u64>, account: String, mirror: String, attempt: u32 },
+    /// 本次尝试已传输的字节数
+    Progress { content_id: String, bytes: u64, total_bytes: Option<u64> },
+    /// 下载失败, 等待后重试
+    Retry { content_id: String, attempt: u32, delay_ms: u64, reason: String },
+    /// 账号返回 401/403/429, 换用其他账号
+    AccountSwitched { content_id: String, account: String, http_status: u16 },
+    /// 下载完成后的校验结果
+    Validated { content_id: String, status: DownloadStatus },
+    /// 该项处理结束 (含跳过和失败)
+    Finished { content_id: String, status: DownloadStatus, account: Option<String>, error: Option<String> },
+}
+
+impl Event {
+    pub fn content_id(&self) -> &str {
+        match self {
+            Event::Queued { content_id }
+            | Event::DetailsResolved { content_id, .. }
+            | Event::DownloadStarted { content_id, .. }
+            | Event::Progress { content_id, .. }
+            | Event::Retry { content_id, .. }
+            | Event::AccountSwitched { content_id, .. }
+            | Event::Validated { content_id, .. }
+            | Event::Finished { content_id, .. } => content_id,
+        }
+    }
+}
+
+/// 事件的订阅者。回调在下载任务中同步执行, 应尽快返回。
+pub trait EventSink: Send + Sync {
+    fn on_event(&self, event: &Event);
+}
+
+impl<F: Fn(&Event) + Send + Sync> EventSink for F {
+    fn on_event(&self, event: &Event) {
+        self(event)
+    }
+}
+
+/// 把事件依次转发给多个订阅者
+impl EventSink for Vec<std::sync::Arc<dyn EventSink>> {
+    fn on_event(&self, event: &Event) {
+        for sink in self {
+            sink.on_event(event);
+        }
+    }
+}
+
+/// 把事件转发到通道, 由接收端在其他任务或线程中处理
+impl EventSink for tokio::sync::mpsc::UnboundedSender<Event> {
+    fn on_event(&self, event: &Event) {
+        // 接收端已关闭时丢弃事件
+        let _ = self.send(event.clone());
+    }
+}
+
+impl EventSink for std::sync::mpsc::Sender<Event> {
+    fn on_event(&self, event: &Event) {
+        let _ = self.send(event.clone());
+    }
+}
@@ -0,0 +1,280 @@
+// src/har.rs
+
+//! 把客户端发出的请求记录为 HAR (HTTP Archive 1.2) 文件, 用于排查平台接口的变化。
+//! 请求地址和请求头中的 Token 会被替换为 `REDACTED`, PDF 内容不记录。
+
+use crate::{tr, AppError};
+use chrono::{Local, SecondsFormat};
+use reqwest::header::{HeaderMap, CONTENT_TYPE};
+use reqwest::{Request, Response, Url};
+use serde::Serialize;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+const REDACTED: &str = "REDACTED";
+
+/// 收集请求记录, 结束时用 [`save`](HarRecorder::save) 写入文件
+#[derive(Default)]
+pub struct HarRecorder {
+    entries: Mutex<Vec<Entry>>,
+}
+
+impl HarRecorder {
+    pub fn new() -> HarRecorder {
+        HarRecorder::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 按开始时间排序后写入 HAR 文件
+    pub fn save(&self, path: &Path) -> Result<(), AppError> {
+        let mut entries = self.entries.lock().unwrap().clone();
+        entries.sort_by(|a, b| a.started_date_time.cmp(&b.started_date_time));
+        let har = Har {
+            log: Log {
+                version: "1.2",
+                creator: Creator { name: "sed-dl", version: env!("CARGO_PKG_VERSION") },
+                entries,
+            },
+        };
+        let file = std::fs::File::create(path)?;
+        serde_json::to_writer_pretty(std::io::BufWriter::new(file), &har)?;
+        Ok(())
+    }
+
+    /// 开始记录一个请求。返回的记录在丢弃时写入, 因此被取消或出错的请求也会留下记录。
+    pub(crate) fn begin(self: &Arc<Self>, request: &Request) -> HarEntry {
+        let url = redact_url(request.url());
+        let query_string = Url::parse(&url).map(|u| u.query_pairs()
+            .map(|(name, value)| NameValue { name: name.into_owned(), value: value.into_owned() })
+            .collect()).unwrap_or_default();
+        HarEntry {
+            recorder: self.clone(),
+            started: Instant::now(),
+            wait_ms: None,
+            entry: Some(Entry {
+                started_date_time: Local::now().to_rfc3339_opts(SecondsFormat::Millis, false),
+                time: 0.0,
+                request: HarRequest {
+                    method: request.method().to_string(),
+                    url,
+                    http_version: format!("{:?}", request.version()),
+                    headers: redact_headers(request.headers()),
+                    query_string,
+                    cookies: Vec::new(),
+                    headers_size: -1,
+                    body_size: 0,
+                },
+                response: HarResponse {
+                    status: 0,
+                    status_text: String::new(),
+                    http_version: String::new(),
+                    headers: Vec::new(),
+                    cookies: Vec::new(),
+                    content: Content { size: 0, mime_type: String::new(), text: None, comment: None },
+                    redirect_url: String::new(),
+                    headers_size: -1,
+                    body_size: -1,
+                    error: None,
+                },
+                cache: Cache {},
+                timings: Timings { send: 0.0, wait: 0.0, receive: 0.0 },
+            }),
+        }
+    }
+}
+
+/// 进行中的一条记录
+pub(crate) struct HarEntry {
+    recorder: Arc<HarRecorder>,
+    started: Instant,
+    wait_ms: Option<f64>,
+    entry: Option<Entry>,
+}
+
+impl HarEntry {
+    /// 记录响应的状态和响应头
+    pub(crate) fn response(&mut self, response: &Response) {
+        self.wait_ms = Some(self.started.elapsed().as_secs_f64() * 1000.0);
+        let Some(entry) = &mut self.entry else { return };
+        let r = &mut entry.response;
+        r.status = response.status().as_u16();
+        r.status_text = response.status().canonical_reason().unwrap_or_default().to_string();
+        r.http_version = format!("{:?}", response.version());
+        r.headers = redact_headers(response.headers());
+        r.content.mime_type = response.headers().get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok()).unwrap_or_default().to_string();
+    }
+
+    /// 记录响应内容; PDF 等二进制内容只记录大小
+    pub(crate) fn body(&mut self, body: &[u8]) {
+        let Some(entry) = &mut self.entry else { return };
+        let content = &mut entry.response.content;
+        content.size = body.len() as u64;
+        entry.response.body_size = body.len() as i64;
+        if is_binary(&content.mime_type) || body.starts_with(b"%PDF") {
+            content.comment = Some(tr!("har.binary_omitted"));
+        } else {
+            content.text = Some(String::from_utf8_lossy(body).into_owned());
+        }
+    }
+
+    /// 只记录响应内容的大小, 用于流式下载的文件
+    pub(crate) fn body_size(&mut self, size: u64) {
+        let Some(entry) = &mut self.entry else { return };
+        entry.response.content.size = size;
+        entry.response.body_size = size as i64;
+        entry.response.content.comment = Some(tr!("har.file_omitted"));
+    }
+
+    /// 记录请求失败的原因, 错误信息中的地址同样打码
+    pub(crate) fn error(&mut self, error: &reqwest::Error) {
+        let Some(entry) = &mut self.entry else { return };
+        let mut message = error.to_string();
+        if let Some(url) = error.url() {
+            message = message.replace(url.as_str(), &redact_url(url));
+        }
+        entry.response.error = Some(message);
+    }
+}
+
+impl Drop for HarEntry {
+    fn drop(&mut self) {
+        let Some(mut entry) = self.entry.take() else { return };
+        let total = self.started.elapsed().as_secs_f64() * 1000.0;
+        let wait = self.wait_ms.unwrap_or(total);
+        entry.time = total;
+        entry.timings = Timings { send: 0.0, wait, receive: total - wait };
+        if entry.response.status == 0 && entry.response.error.is_none() {
+            entry.response.error = Some(tr!("har.no_response"));
+        }
+        self.recorder.entries.lock().unwrap().push(entry);
+    }
+}
+
+fn is_binary(mime_type: &str) -> bool {
+    let mime_type = mime_type.to_ascii_lowercase();
+    ["application/pdf", "application/octet-stream", "image/", "video/", "audio/"].iter().any(|m| mime_type.starts_with(m))
+}
+
+fn is_secret(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    name.contains("token") || name.contains("auth") || name.contains("cookie")
+}
+
+/// 把查询参数中的 Token 替换为 `REDACTED`
+pub fn redact_url(url: &Url) -> String {
+    if !url.query_pairs().any(|(name, _)| is_secret(&name)) {
+        return url.to_string();
+    }
+    let mut redacted = url.clone();
+    let pairs: Vec<(String, String)> = url.query_pairs()
+        .map(|(name, value)| {
+            let value = if is_secret(&name) { REDACTED.to_string() } else { value.into_owned() };
+            (name.into_owned(), value)
+        })
+        .collect();
+    redacted.query_pairs_mut().clear().extend_pairs(pairs);
+    redacted.to_string()
+}
+
+fn redact_headers(headers: &HeaderMap) -> Vec<NameValue> {
+    headers.iter().map(|(name, value)| NameValue {
+        name: name.to_string(),
+        value: if is_secret(name.as_str()) { REDACTED.to_string() } else { String::from_utf8_lossy(value.as_bytes()).into_owned() },
+    }).collect()
+}
+
+#[derive(Serialize)]
+struct Har {
+    log: Log,
+}
+
+#[derive(Serialize)]
+struct Log {
+    version: &'static str,
+    creator: Creator,
+    entries: Vec<Entry>,
+}
+
+#[derive(Serialize)]
+struct Creator {
+    name: &'static str,
+    version: &'static str,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct Entry {
+    started_date_time: String,
+    time: f64,
+    request: HarRequest,
+    response: HarResponse,
+    cache: Cache,
+    timings: Timings,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct HarRequest {
+    method: String,
+    url: String,
+    http_version: String,
+    headers: Vec<NameValue>,
+    query_string: Vec<NameValue>,
+    cookies: Vec<NameValue>,
+    headers_size: i64,
+    body_size: i64,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct HarResponse {
+    status: u16,
+    status_text: String,
+    http_version: String,
+    headers: Vec<NameValue>,
+    cookies: Vec<NameValue>,
+    content: Content,
+    #[serde(rename = "redirectURL")]
+    redirect_url: String,
+    headers_size: i64,
+    body_size: i64,
+    /// 请求失败时的错误信息 (HAR 的自定义字段以下划线开头)
+    #[serde(rename = "_error", skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct Content {
+    size: u64,
+    mime_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    comment: Option<String>,
+}
+
+#[derive(Serialize, Clone)]
+struct Cache {}
+
+#[derive(Serialize, Clone)]
+struct Timings {
+    send: f64,
+    wait: f64,
+    receive: f64,
+}
+
+#[derive(Serialize, Clone)]
+struct NameValue {
+    name: String,
+    value: String,
+}
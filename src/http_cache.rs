@@ -0,0 +1,167 @@
+// src/http_cache.rs
+
+//! 教材详情和目录 JSON 的磁盘缓存。遵循 Cache-Control 的 max-age / no-cache / no-store,
+//! 过期后带 If-None-Match / If-Modified-Since 向服务器确认, 收到 304 时沿用缓存内容。
+
+use crate::tr;
+use log::debug;
+use reqwest::header::{HeaderMap, CACHE_CONTROL, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::RequestBuilder;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::fs;
+
+/// 磁盘上的 HTTP 缓存
+#[derive(Debug, Clone)]
+pub struct HttpCache {
+    dir: PathBuf,
+    ttl: Option<Duration>,
+}
+
+impl HttpCache {
+    pub fn new(dir: impl Into<PathBuf>) -> HttpCache {
+        HttpCache { dir: dir.into(), ttl: None }
+    }
+
+    /// 默认缓存目录, 如 Linux 下的 `~/.cache/sed-dl/http`
+    pub fn default_dir() -> Option<PathBuf> {
+        dirs::cache_dir().map(|dir| dir.join("sed-dl").join("http"))
+    }
+
+    /// 缓存在指定时长内视为新鲜, 不再向服务器确认 (覆盖响应中的 Cache-Control)。
+    /// 为零时每次都向服务器确认。
+    pub fn ttl(mut self, ttl: Duration) -> HttpCache {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{:x}.json", md5::compute(key)))
+    }
+
+    /// 读取缓存, 文件不存在或已损坏时返回 `None`
+    pub(crate) async fn load(&self, key: &str) -> Option<CachedResponse> {
+        let content = fs::read(self.entry_path(key)).await.ok()?;
+        match serde_json::from_slice::<CachedResponse>(&content) {
+            Ok(entry) if entry.key == key => Some(entry),
+            Ok(_) => None,
+            Err(e) => {
+                debug!("{}", tr!("cache.corrupted", key, e));
+                None
+            }
+        }
+    }
+
+    pub(crate) fn is_fresh(&self, entry: &CachedResponse) -> bool {
+        let age = now_secs().saturating_sub(entry.stored_at);
+        match self.ttl {
+            Some(ttl) => age < ttl.as_secs(),
+            None => !entry.no_cache && entry.max_age.is_some_and(|max_age| age < max_age),
+        }
+    }
+
+    /// 保存响应。响应要求 no-store 时删除已有的缓存。
+    pub(crate) async fn store(&self, key: &str, headers: &HeaderMap, body: &[u8]) {
+        let directives = CacheControl::parse(headers);
+        if directives.no_store {
+            let _ = fs::remove_file(self.entry_path(key)).await;
+            return;
+        }
+        let entry = CachedResponse {
+            key: key.to_string(),
+            etag: header_value(headers, ETAG),
+            last_modified: header_value(headers, LAST_MODIFIED),
+            stored_at: now_secs(),
+            max_age: directives.max_age,
+            no_cache: directives.no_cache,
+            body: String::from_utf8_lossy(body).into_owned(),
+        };
+        self.write(&entry).await;
+    }
+
+    /// 收到 304 后更新缓存的时间和校验信息
+    pub(crate) async fn revalidated(&self, mut entry: CachedResponse, headers: &HeaderMap) {
+        let directives = CacheControl::parse(headers);
+        if let Some(etag) = header_value(headers, ETAG) { entry.etag = Some(etag); }
+        if let Some(last_modified) = header_value(headers, LAST_MODIFIED) { entry.last_modified = Some(last_modified); }
+        if headers.contains_key(CACHE_CONTROL) {
+            entry.max_age = directives.max_age;
+            entry.no_cache = directives.no_cache;
+        }
+        entry.stored_at = now_secs();
+        self.write(&entry).await;
+    }
+
+    /// 先写临时文件再替换, 中断时不会留下不完整的缓存
+    async fn write(&self, entry: &CachedResponse) {
+        let path = self.entry_path(&entry.key);
+        let result = async {
+            fs::create_dir_all(&self.dir).await?;
+            let tmp = path.with_extension("tmp");
+            fs::write(&tmp, serde_json::to_vec(entry)?).await?;
+            fs::rename(&tmp, &path).await
+        }.await;
+        if let Err(e) = result {
+            debug!("{}", tr!("cache.write_failed", path.display(), e));
+        }
+    }
+}
+
+/// 一条缓存的响应
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct CachedResponse {
+    key: String,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    /// 保存或最近一次确认的时间 (Unix 秒)
+    stored_at: u64,
+    max_age: Option<u64>,
+    no_cache: bool,
+    pub(crate) body: String,
+}
+
+impl CachedResponse {
+    /// 附加条件请求头, 内容未变时服务器返回 304
+    pub(crate) fn conditional(&self, mut request: RequestBuilder) -> RequestBuilder {
+        if let Some(etag) = &self.etag { request = request.header(IF_NONE_MATCH, etag); }
+        if let Some(last_modified) = &self.last_modified { request = request.header(IF_MODIFIED_SINCE, last_modified); }
+        request
+    }
+}
+
+#[derive(Default)]
+struct CacheControl {
+    max_age: Option<u64>,
+    no_cache: bool,
+    no_store: bool,
+}
+
+impl CacheControl {
+    fn parse(headers: &HeaderMap) -> CacheControl {
+        let mut directives = CacheControl::default();
+        for value in headers.get_all(CACHE_CONTROL).iter().filter_map(|v| v.to_str().ok()) {
+            for directive in value.split(',').map(|d| d.trim().to_ascii_lowercase()) {
+                match directive.split_once('=') {
+                    Some(("max-age", secs)) => directives.max_age = secs.trim_matches('"').parse().ok(),
+                    _ if directive == "no-cache" => directives.no_cache = true,
+                    _ if directive == "no-store" => directives.no_store = true,
+                    _ => {}
+                }
+            }
+        }
+        directives
+    }
+}
+
+fn header_value(headers: &HeaderMap, name: reqwest::header::HeaderName) -> Option<String> {
+    headers.get(name).and_then(|v| v.to_str().ok()).map(str::to_string)
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+}
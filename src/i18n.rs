@@ -0,0 +1,1078 @@
+// src/i18n.rs
+
+//! 界面语言和消息目录。消息按键查找, 其中的 `{}` 按顺序替换为参数, 见 [`tr!`](crate::tr)。
+
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// 界面语言
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Lang {
+    /// 中文
+    #[default]
+    Zh,
+    /// English
+    En,
+}
+
+impl Lang {
+    pub const ALL: [Lang; 2] = [Lang::Zh, Lang::En];
+
+    /// 按 `LC_ALL`、`LC_MESSAGES`、`LANG` 的顺序推断; 未设置或为 C/POSIX 时使用中文, 其他非中文地区使用英文
+    pub fn detect() -> Lang {
+        ["LC_ALL", "LC_MESSAGES", "LANG"].iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|value| !value.is_empty())
+            .map_or(Lang::Zh, |value| Lang::from_locale(&value))
+    }
+
+    /// 从 `zh_CN.UTF-8`、`en_US` 之类的地区设置得出语言
+    pub fn from_locale(locale: &str) -> Lang {
+        let language = locale.split(['_', '.', '-', '@']).next().unwrap_or_default().to_ascii_lowercase();
+        match language.as_str() {
+            "" | "c" | "posix" | "zh" => Lang::Zh,
+            _ => Lang::En,
+        }
+    }
+
+    fn catalog(self) -> &'static HashMap<&'static str, &'static str> {
+        &CATALOGS[self as usize]
+    }
+}
+
+static CURRENT: AtomicU8 = AtomicU8::new(Lang::Zh as u8);
+
+/// 设置全局的界面语言, 应在程序启动时调用
+pub fn set_lang(lang: Lang) {
+    CURRENT.store(lang as u8, Ordering::Relaxed);
+}
+
+pub fn lang() -> Lang {
+    if CURRENT.load(Ordering::Relaxed) == Lang::En as u8 { Lang::En } else { Lang::Zh }
+}
+
+static CATALOGS: Lazy<[HashMap<&'static str, &'static str>; 2]> = Lazy::new(|| [
+    ZH.iter().copied().collect(),
+    EN.iter().copied().collect(),
+]);
+
+/// 按指定语言查找消息
+pub fn lookup(lang: Lang, key: &str) -> Option<&'static str> {
+    lang.catalog().get(key).copied()
+}
+
+/// 按当前语言查找消息, 缺失时退回中文
+pub fn message(key: &str) -> Option<&'static str> {
+    lookup(lang(), key).or_else(|| lookup(Lang::Zh, key))
+}
+
+/// 同 [`message`], 都缺失时返回键本身
+pub fn t(key: &str) -> &str {
+    message(key).unwrap_or(key)
+}
+
+/// 把模板中的 `{}` 按顺序替换为参数, 多余的占位符保留原样
+pub fn format(template: &str, args: &[&dyn Display]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut args = args.iter();
+    let mut rest = template;
+    while let Some(pos) = rest.find("{}") {
+        out.push_str(&rest[..pos]);
+        match args.next() {
+            Some(arg) => out.push_str(&arg.to_string()),
+            None => out.push_str("{}"),
+        }
+        rest = &rest[pos + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// 按当前语言格式化消息: `tr!("summary.totals", total, ok, failed, skipped)`
+#[macro_export]
+macro_rules! tr {
+    ($key:expr) => {
+        $crate::i18n::t($key).to_string()
+    };
+    ($key:expr, $($arg:expr),+ $(,)?) => {
+        $crate::i18n::format($crate::i18n::t($key), &[$(&$arg as &dyn ::std::fmt::Display),+])
+    };
+}
+
+static ZH: &[(&str, &str)] = &[
+    // AppError
+    ("error.reqwest", "网络请求错误: {}"),
+    ("error.io", "文件IO错误: {}"),
+    ("error.json", "JSON解析错误: {}"),
+    ("error.detail_fetch", "获取教材详情失败: {}"),
+    ("error.invalid_input", "无效的输入: {}"),
+    ("error.dir_creation", "目录创建失败: {}"),
+    ("error.http_status", "服务器返回错误状态 {} (来源: {})"),
+    ("error.transfer", "请求 {} 失败: {}"),
+    ("error.config", "配置文件错误: {}"),
+    ("error.token_expired", "Token 已失效或错误"),
+    ("error.credential", "凭据读取失败: {}"),
+    ("error.token_store", "Token 存储错误: {}"),
+    ("error.browser_import", "从浏览器导入失败: {}"),
+    ("error.offline", "离线模式: {}"),
+    ("error.cancelled", "操作已取消"),
+    ("error.no_account", "没有可用的账号 (全部失效或处于冷却中)"),
+    ("error.unknown_mirror", "未知来源"),
+    // DownloadStatus
+    ("status.label.OK", "校验通过"),
+    ("status.label.OK_UNVERIFIED", "无校验信息"),
+    ("status.label.SKIPPED", "已存在, 跳过"),
+    ("status.label.E_TOKEN", "Token错误或过期"),
+    ("status.label.E_FORBIDDEN", "无权访问"),
+    ("status.label.E_REMOVED", "资源已下架"),
+    ("status.label.E_RATE_LIMITED", "请求过于频繁"),
+    ("status.label.E_HTTP", "服务器错误"),
+    ("status.label.E_TLS", "TLS握手失败"),
+    ("status.label.E_TIMEOUT", "请求超时"),
+    ("status.label.E_NETWORK", "网络错误"),
+    ("status.label.E_DISK_FULL", "磁盘空间不足"),
+    ("status.label.E_PERMISSION", "没有写入权限"),
+    ("status.label.E_INVALID_PDF", "不是有效的PDF"),
+    ("status.label.E_MD5", "MD5校验失败"),
+    ("status.label.E_SIZE", "文件大小校验失败"),
+    ("status.label.E_DETAILS", "获取详情失败"),
+    ("status.label.E_ABORTED", "已中止, 未处理"),
+    ("status.label.E_CANCELLED", "已取消"),
+    ("status.label.E_UNEXPECTED", "意外错误"),
+    ("status.hint.E_TOKEN", "Token 已失效, 请重新登录平台获取新的 Access Token。"),
+    ("status.hint.E_FORBIDDEN", "当前账号无权下载该资源 (可能未购买或需要更高权限), 请确认账号权限。"),
+    ("status.hint.E_REMOVED", "资源已被平台下架或ID有误, 请在平台上确认该教材是否仍然可用。"),
+    ("status.hint.E_RATE_LIMITED", "平台限流, 请降低 --max-concurrent-downloads 或稍后再试。"),
+    ("status.hint.E_HTTP", "平台服务暂时异常, 请稍后重试。"),
+    ("status.hint.E_TLS", "TLS 连接失败, 请检查系统时间、代理或防火墙是否拦截了 HTTPS。"),
+    ("status.hint.E_TIMEOUT", "连接超时, 请检查网络状况或适当增加重试次数。"),
+    ("status.hint.E_NETWORK", "网络连接失败, 请检查网络后重试。"),
+    ("status.hint.E_DISK_FULL", "磁盘空间不足, 请清理空间或使用 -o 指定其他目录。"),
+    ("status.hint.E_PERMISSION", "没有输出目录的写入权限, 请检查目录权限或使用 -o 指定其他目录。"),
+    ("status.hint.E_INVALID_PDF", "下载内容不是 PDF (可能是错误页面), 请稍后重试或检查 Token。"),
+    ("status.hint.E_MD5", "文件可能不完整, 重新运行即可重新下载。"),
+    ("status.hint.E_SIZE", "文件可能不完整, 重新运行即可重新下载。"),
+    ("status.hint.E_DETAILS", "无法解析教材信息, 请确认 URL/ID 是否正确。"),
+    ("status.hint.E_ABORTED", "批量任务被提前中止, 这些项目尚未处理, 解决问题后重新运行即可。"),
+    ("status.hint.E_CANCELLED", "下载被手动中断 (Ctrl-C 或 'sed-dl ctl cancel'), 重新运行即可继续。"),
+    ("status.hint.E_UNEXPECTED", "发生意外错误, 请使用 --debug 重新运行并反馈问题。"),
+    // 结果汇总
+    ("summary.done_batch", "{} 批量下载任务完成！"),
+    ("summary.done_single", "{} 单次下载任务完成！"),
+    ("summary.totals", "{} 总计: {} | 成功: {} | 失败: {} | 跳过: {}"),
+    ("summary.skipped", "{} 跳过的文件 (已存在):"),
+    ("summary.accounts", "{} 各账号下载情况:"),
+    ("summary.account_line", "{} 成功: {} | 失败: {}"),
+    ("summary.failed", "{} 失败的详情:"),
+    ("summary.hints", "{} 处理建议:"),
+    ("summary.source", "来源 {}"),
+    ("summary.account", "账号 {}"),
+    ("summary.manifest_written", "{} 结果清单已写入 '{}'"),
+    ("summary.manifest_failed", "{} 写入结果清单失败: {}"),
+    ("summary.har_written", "{} 已把 {} 个请求写入 HAR 抓包 '{}'"),
+    ("summary.har_failed", "{} 写入 HAR 抓包失败: {}"),
+    ("summary.failed_list_written", "{} {} 个未成功的项已写入 '{}', 可用 -i 重新下载"),
+    ("summary.failed_list_failed", "{} 写入失败列表失败: {}"),
+    ("summary.resume_hint", "{} 下载进度已记录在 '{}', 运行 'sed-dl resume {}' 即可继续"),
+    // 下载进度
+    ("progress.totals", "完成 {} | 失败 {} | 剩余 {}"),
+    ("progress.speed", " | 平均 {}/s"),
+    ("progress.eta", " | 预计还需 {}"),
+    ("progress.total", "总进度"),
+    ("progress.retry", "{} '{}' 第{}次下载失败 ({}), {}后重试..."),
+    ("progress.account_switched", "{} '{}' 账号 '{}' 返回 {}, 换用其他账号"),
+    ("progress.by_account", " (账号 {})"),
+    ("task.details_failed", "{} 获取'{}' (ID: {}) 详情失败: {}"),
+    ("task.skip_existing", "已存在且校验一致, 跳过"),
+    ("task.redownload", "校验不一致, 重新下载"),
+    ("task.token_tripped", "{} 检测到 Token 失效, 停止派发新的下载任务"),
+    // 输入和输出位置
+    ("input.output_is_file", "批量下载时, '-o' 参数必须是目录, 不能是文件。"),
+    ("input.output_dir", "文件将保存到目录: '{}'"),
+    ("input.added", "{} 已添加: ID {}... (来源: {})"),
+    ("input.duplicate", "{} 检测到重复项，已跳过: '{}'"),
+    ("input.invalid", "{} 无效输入，已跳过: '{}' (来源: {})"),
+    ("input.source_url", "命令行URL"),
+    ("input.source_id", "命令行ID"),
+    ("input.source_line", "文件第 {} 行"),
+    ("input.source_manifest", "结果清单"),
+    ("input.no_items", "未找到任何有效的下载项。请检查输入。"),
+    ("input.no_content_id", "无法从 '{}' 中识别 Content ID"),
+    ("input.no_source", "必须至少提供一个输入源 (-u, -c, -i 或 --retry-failed)"),
+    // Token 预检和账号
+    ("preflight.skipped", "{} Token 预检失败, 跳过: {}"),
+    ("preflight.account", "账号 '{}' 的 "),
+    ("preflight.valid", "{} {}Token 预检通过"),
+    ("preflight.forbidden", "{} {}Token 有效, 但无权访问探测的教材, 部分教材可能无法下载"),
+    ("preflight.inconclusive", "{} {}探测资源返回 {}, 无法确认 Token 状态, 继续下载"),
+    ("preflight.expired", "{} {}Token 已失效"),
+    ("preflight.failed", "{} {}Token 预检失败, 跳过: {}"),
+    ("preflight.reenter", "{} Token 已失效, 请重新输入。"),
+    ("accounts.none_saved", "尚未保存任何 Token, 请先使用 'sed-dl token set <名称>' 保存"),
+    ("accounts.using", "{} 使用 {} 个账号 ({}), 调度策略: {}"),
+    // Token 子命令
+    ("token.legacy_file", "{} 当前目录下的 '{}' 已弃用, 建议运行 'sed-dl token set' 保存到用户配置目录后删除该文件"),
+    ("token.save_failed", "{} 无法保存Token: {}"),
+    ("token.saved", "{} 已保存 Token '{}' 到 '{}'"),
+    ("token.probe_missing", "请通过 --probe 或配置文件中的 token.probe 指定用于探测的教材"),
+    ("token.valid", "Token 有效"),
+    ("token.invalid", "Token 已失效或错误"),
+    ("token.forbidden", "Token 有效, 但无权访问探测的教材"),
+    ("token.nothing_to_save", "未提供要保存的 Token"),
+    ("token.empty", "访问令牌不能为空"),
+    ("token.imported", "{} 已从浏览器读取到 Token"),
+    ("token.list_empty", "{} 尚未保存任何 Token, 可使用 'sed-dl token set' 保存"),
+    ("token.encrypted", "加密"),
+    ("token.plain", "明文"),
+    ("token.not_found", "不存在名为 '{}' 的 Token"),
+    ("token.removed", "{} 已删除 Token '{}'"),
+    ("token.default_set", "{} 默认 Token 已设为 '{}'"),
+    // 客户端设置、目录浏览和控制命令
+    ("settings.no_cache_dir", "无法确定缓存目录, 请使用 --cache-dir 指定"),
+    ("settings.offline_cache_disabled", "离线模式需要启用缓存 (配置文件中 cache.enabled 为 false)"),
+    ("browse.fetching", "{} 正在获取教材目录..."),
+    ("browse.nothing_selected", "{} 未选择任何教材"),
+    ("ctl.state", "{} {} | 最大并发: {} | 下载中: {} | 排队: {} | 已结束: {}"),
+    ("ctl.paused", "已暂停"),
+    ("ctl.running", "运行中"),
+    ("ctl.downloading", "下载中"),
+    ("ctl.queued", "排队"),
+    ("ctl.finished", "已结束"),
+    // 运行过程
+    ("signal.stopping", "{} 收到中断信号, 停止派发新任务, 等待进行中的下载完成 (再按一次 Ctrl-C 立即中止)"),
+    ("signal.aborting", "{} 正在中止进行中的下载..."),
+    ("run.task_panicked", "任务执行时发生Panic: {}"),
+    ("run.retry_pass", "{} {} 项因临时错误失败, {} 秒后重试"),
+    ("run.no_jobs_file", "无法确定任务文件位置, 请使用 --jobs-file 指定"),
+    ("run.not_a_terminal", "{} 标准输出不是终端, 不显示仪表盘"),
+    ("run.resuming", "{} 续接上次的下载: 已完成 {} 项, 上次失败的 {} 项优先重试"),
+    ("run.journal_unavailable", "{} 无法使用下载日志, 本次不记录进度: {}"),
+    ("run.control_socket", "{} 可通过 'sed-dl ctl' 控制本次下载 (套接字 '{}')"),
+    ("run.control_unavailable", "{} 控制套接字不可用: {}"),
+    ("run.token_expired_before_start", "{} Token 已失效, 未开始下载。请更新 Token 后重新运行。"),
+    ("run.token_expired_stopped", "{} Token 已失效, 已停止派发, 剩余 {} 项未处理。请更新 Token 后重新运行。"),
+    ("run.token_expired_paused", "{} Token 已失效, 下载已暂停 (待处理 {} 项)。请输入新的 Token 以继续。"),
+    ("run.dashboard_error", "{} 仪表盘出错: {}"),
+    ("run.dashboard_panicked", "{} 仪表盘异常退出: {}"),
+    // 客户端错误信息
+    ("client.offline_needs_cache", "离线模式需要启用缓存"),
+    ("client.offline_request", "不向 {} 发出请求"),
+    ("client.cache_miss", "缓存中没有 '{}'"),
+    ("client.offline_download", "未下载 '{}'"),
+    ("client.no_source_pdf", "在内容ID '{}' 中未找到源PDF文件信息"),
+    ("client.no_pdf_url", "在内容ID '{}' 中未找到PDF下载地址"),
+    ("client.unknown_download_error", "未知下载错误"),
+    // 交互提示
+    ("prompt.yes_no", "(y/n)"),
+    ("prompt.passphrase", "请输入口令: "),
+    ("prompt.passphrase_again", "请再次输入口令: "),
+    ("prompt.passphrase_required", "需要口令, 请设置环境变量 {}"),
+    ("prompt.passphrase_empty", "口令不能为空"),
+    ("prompt.passphrase_mismatch", "两次输入的口令不一致"),
+    ("prompt.use_saved_named", "检测到已保存的 Token '{}'，是否使用？"),
+    ("prompt.use_saved", "检测到已保存的 Token，是否使用？"),
+    ("prompt.token", "请输入您的访问令牌 (Access Token): "),
+    ("prompt.token_empty", "访问令牌不能为空，请重新输入。"),
+    ("prompt.token_stdin_closed", "未提供访问令牌 (标准输入已关闭)"),
+    ("prompt.token_missing", "未找到访问令牌。请通过 -t、环境变量 {}、--token-file、--token-helper 或 'sed-dl token set' 提供"),
+    ("prompt.token_to_save", "请输入要保存的访问令牌: "),
+    ("prompt.renew_account", "{} 请为账号 '{}' 输入新的 Token"),
+    ("prompt.token_saved", "{} Token 已保存为 '{}' ({})"),
+    // 命令行帮助
+    ("daemon.tokens_reloaded", "{} 已重新读取 Token, 恢复使用账号: {}"),
+    ("daemon.tokens_unchanged", "{} 所有账号的 Token 均已失效, 重新读取后 Token 没有变化"),
+    ("daemon.tokens_reload_failed", "{} 重新读取 Token 失败: {}"),
+    ("output.size_unknown", "大小未知"),
+    ("output.started", "{} 开始下载 '{}' ({})"),
+    ("output.attempt_started", "{} 第{}次下载 '{}' ({})"),
+    ("output.progress", "{} '{}' 已下载 {}% ({}/{})"),
+    ("dashboard.sort.order", "加入顺序"),
+    ("dashboard.column.name", "名称"),
+    ("dashboard.column.state", "状态"),
+    ("dashboard.column.progress", "进度"),
+    ("dashboard.column.speed", "速度"),
+    ("dashboard.column.eta", "剩余"),
+    ("dashboard.column.retries", "重试"),
+    ("dashboard.column.mirror", "镜像"),
+    ("dashboard.column.account", "账号"),
+    ("dashboard.state.retrying", "重试中"),
+    ("dashboard.state.done", "完成"),
+    ("dashboard.state.failed", "失败"),
+    ("dashboard.state.cancelled", "已取消"),
+    ("dashboard.stopping", "已停止派发新任务, 等待进行中的下载完成; 再按 q 立即中止"),
+    ("dashboard.header", " | 并发 {} | 下载中 {} 排队 {} 完成 {} 失败 {} | {}/s (平均 {}/s) | 已下载 {} | 用时 {}"),
+    ("dashboard.tasks_title", " 任务 ({}) · 排序: {} "),
+    ("dashboard.logs_title", " 日志 "),
+    ("dashboard.help", "↑↓ 选择  s 排序  p 暂停/继续所选  c 取消所选  r 重试所选  空格 暂停/继续全部  +/- 并发  q 退出"),
+    ("browse.select_first", "尚未选择教材, 在右侧列表中按空格选择"),
+    ("browse.root", "电子教材"),
+    ("browse.search", "   搜索: {}"),
+    ("browse.tags_title", " 分类 "),
+    ("browse.books_title", " 教材 ({}) "),
+    ("browse.detail.tags", "标签: "),
+    ("browse.detail.files", "文件: "),
+    ("browse.no_match", "没有符合条件的教材"),
+    ("browse.help", "↑↓ 移动  Enter/→ 进入分类  ←/Backspace 返回  Tab 切换  空格 选择  a 全选  / 搜索  d 开始下载 (已选 {})  q 退出"),
+    ("ctl.reply.paused_all", "已暂停派发新任务, 进行中的下载会继续完成"),
+    ("ctl.reply.resumed_all", "已继续"),
+    ("ctl.reply.paused_running", "已暂停 {} (下载已中止, 继续后重新下载)"),
+    ("ctl.reply.paused", "已暂停 {}"),
+    ("ctl.reply.not_in_queue", "{} 不在队列中"),
+    ("ctl.reply.resumed", "已继续 {}"),
+    ("ctl.reply.not_paused", "{} 未被暂停"),
+    ("ctl.reply.concurrency_zero", "并发数至少为 1"),
+    ("ctl.reply.concurrency", "最大并发数: {} -> {}"),
+    ("ctl.reply.round_ended", "当前没有进行中的下载, 无法插队或重试"),
+    ("ctl.reply.bumped", "已将 {} 移到队首"),
+    ("ctl.reply.not_queued", "{} 不在排队中"),
+    ("ctl.reply.cancelled", "已取消 {}"),
+    ("ctl.reply.requeued", "已将 {} 重新排队"),
+    ("ctl.reply.not_failed", "{} 不是本次运行中失败或已取消的项"),
+    ("ctl.reply.bad_request", "无法解析命令: {}"),
+    ("ctl.cancelled_by_command", "已通过控制命令取消"),
+    ("ctl.socket_in_use", "控制套接字 '{}' 正被其他下载使用"),
+    ("ctl.connect_failed", "无法连接控制套接字 '{}' (没有正在运行的批量下载?): {}"),
+    ("ctl.unsupported", "当前平台不支持控制套接字"),
+    ("daemon.save_failed", "保存任务文件 '{}' 失败: {}"),
+    ("daemon.cancel_finished", "任务 {} 已结束, 无法取消"),
+    ("daemon.retry_unfinished", "只能重试失败或已取消的任务, 任务 {} 当前为 {}"),
+    ("daemon.job_started", "{} 开始任务 {}: {}"),
+    ("daemon.started", "{} 服务已启动: http://{} (任务文件 '{}', 待处理 {} 项, 保存到 '{}')"),
+    ("daemon.stopping", "{} 正在停止服务, 进行中的任务将在下次启动后继续"),
+    ("daemon.not_found", "任务 {} 不存在"),
+    ("daemon.invalid_items", "无法识别的 URL 或 Content ID: {}"),
+    ("metrics.downloaded_bytes", "已下载的字节数"),
+    ("metrics.succeeded", "成功 (含跳过) 的任务数"),
+    ("metrics.failed", "失败的任务数"),
+    ("metrics.finished", "按状态码统计的已结束任务数"),
+    ("metrics.jobs", "各状态的任务数"),
+    ("log.bad_rotation", "无法识别的轮转方式 '{}' (可用 never、daily 或大小, 如 10MB)"),
+    ("log.bad_level", "无法识别的日志级别 '{}'"),
+    ("log.write_failed", "写入日志文件 '{}' 失败: {}"),
+    ("log.event.queued", "加入下载队列"),
+    ("log.event.details_resolved", "已获取教材详情"),
+    ("log.event.download_started", "开始下载"),
+    ("log.event.retry", "下载失败 ({}), {} 毫秒后重试"),
+    ("log.event.account_switched", "账号 '{}' 返回 {}, 换用其他账号"),
+    ("log.event.validated", "校验结果: {}"),
+    ("log.event.finished_error", "处理结束: {} - {}"),
+    ("log.event.finished", "处理结束: {}"),
+    ("file.read_failed", "无法读取 '{}': {}"),
+    ("file.write_failed", "无法写入 '{}': {}"),
+    ("file.bad_format", "'{}' 格式错误: {}"),
+    ("token_store.no_config_dir", "无法确定用户配置目录"),
+    ("token_store.key_derivation", "口令派生密钥失败: {}"),
+    ("token_store.encrypt_failed", "加密失败"),
+    ("token_store.corrupted", "加密数据已损坏"),
+    ("token_store.wrong_passphrase", "口令错误或数据已损坏"),
+    ("credential.source.command_line", "命令行参数"),
+    ("credential.source.env", "环境变量 {}"),
+    ("credential.source.file", "文件 '{}'"),
+    ("credential.source.helper", "凭据助手 '{}'"),
+    ("credential.file_unreadable", "无法读取 Token 文件 '{}': {}"),
+    ("credential.file_empty", "Token 文件 '{}' 为空"),
+    ("credential.helper_spawn", "无法启动凭据助手 '{}': {}"),
+    ("credential.helper_failed", "凭据助手 '{}' 执行失败: {}"),
+    ("credential.helper_status", "凭据助手 '{}' 退出码异常: {}"),
+    ("credential.helper_no_password", "凭据助手 '{}' 未返回 password 字段"),
+    ("endpoints.no_hosts", "接口地址表中至少需要一个主机"),
+    ("endpoints.invalid_host", "无效的接口主机 '{}', 需要形如 https://example.com"),
+    ("endpoints.details_missing_id", "教材详情的地址模板中缺少 {id}"),
+    ("journal.missing", "'{}' 中没有可续接的下载日志"),
+    ("journal.corrupted", "下载日志 '{}' 已损坏"),
+    ("journal.write_failed", "写入下载日志失败: {}"),
+    ("journal.skipped_line", "跳过无法解析的日志行: {}"),
+    ("manifest.invalid", "'{}' 不是有效的结果清单: {}"),
+    ("daemon.jobs_file_malformed", "任务文件 '{}' 格式错误: {}"),
+    ("status.unknown_code", "未知的状态码 '{}'"),
+    ("browser_import.no_profile", "未找到浏览器的用户配置目录, 请使用 --profile 指定"),
+    ("browser_import.found", "从 '{}' 中读取到 Token"),
+    ("browser_import.profile_failed", "读取 '{}' 失败: {}"),
+    ("browser_import.not_logged_in", "未在浏览器中找到智慧教育平台的登录信息, 请先用该浏览器登录平台"),
+    ("browser_import.sqlite", "读取浏览器数据库失败: {}"),
+    ("leveldb.partial", "LevelDB 文件解析不完整, 已跳过剩余部分: {}"),
+    ("preflight.debug_state", "Token 预检 ({}): {}"),
+    ("catalog.loaded", "目录共 {} 本教材"),
+    ("accounts.parked", "账号 '{}' 已暂停使用 ({})"),
+    ("cache.corrupted", "忽略损坏的缓存 '{}': {}"),
+    ("cache.write_failed", "写入缓存 '{}' 失败: {}"),
+    ("cache.hit", "使用缓存: {}"),
+    ("cache.not_modified", "缓存未变化: {}"),
+    ("client.json_retry", "获取 JSON 失败 ({}), {}后重试: {}"),
+    ("client.host_switched", "接口改用主机 {}"),
+    ("client.download_retry", "'{}' 第{}次下载失败 ({}), {}后重试"),
+    ("client.waiting_for_account", "'{}' 暂无可用账号, {}后重试"),
+    ("client.account_switched", "'{}' 账号 '{}' 返回 {}, 换用其他账号"),
+    ("task.download_failed", "下载'{}' (ID: {}) 失败: {}"),
+    ("token.using_saved", "使用已保存的 Token '{}' ({})"),
+    ("token.using_source", "使用来自{}的 Token"),
+    ("settings.retry_config", "重试配置: {}"),
+    ("har.binary_omitted", "二进制内容未记录"),
+    ("har.file_omitted", "文件内容未记录"),
+    ("har.no_response", "未收到响应 (请求被取消)"),
+    ("cli.about", "国家中小学智慧教育平台教材下载命令行工具 (Rust版)"),
+    ("cli.after_help", "示例:\n  # 下载单个URL\n  sed-dl -u \"<教材URL>\" -t \"<你的TOKEN>\"\n\n  # 从文件批量下载并指定输出目录\n  sed-dl -i urls.txt -o ./教材下载 -t \"<你的TOKEN>\""),
+    ("cli.arg.url", "一个或多个教材页面URL"),
+    ("cli.arg.content_id", "一个或多个教材Content ID"),
+    ("cli.arg.input_file", "包含URL/ID的文本文件路径"),
+    ("cli.arg.token", "访问令牌(Access Token)"),
+    ("cli.arg.token_file", "从文件读取访问令牌"),
+    ("cli.arg.token_helper", "通过外部凭据助手获取访问令牌 (git-credential 协议, 执行 '<CMD> get')"),
+    ("cli.arg.token_name", "使用已保存的指定名称的 Token (见 'sed-dl token list')"),
+    ("cli.arg.no_input", "从不提示输入 (适用于 CI/cron), 缺少 Token 时直接报错"),
+    ("cli.arg.output", "输出文件路径或目录"),
+    ("cli.arg.debug", "启用详细调试日志"),
+    ("cli.arg.max_concurrent_downloads", "最大并发下载数"),
+    ("cli.arg.config", "配置文件路径 (默认: 用户配置目录下的 sed-dl/config.toml)"),
+    ("cli.arg.details_attempts", "获取详情的最大尝试次数 [默认: 3]"),
+    ("cli.arg.download_attempts", "下载文件的最大尝试次数 [默认: 3]"),
+    ("cli.arg.retry_base_delay", "重试退避的基础间隔(毫秒) [默认: 500]"),
+    ("cli.arg.retry_max_delay", "重试退避的最长间隔(毫秒) [默认: 30000]"),
+    ("cli.arg.max_retry_after", "服务器 Retry-After 的最长等待秒数 [默认: 120]"),
+    ("cli.arg.no_retry_jitter", "关闭重试间隔的随机抖动"),
+    ("cli.arg.skip_token_check", "跳过下载前的 Token 预检"),
+    ("cli.arg.manifest", "下载结束 (包括被中断) 时把每一项的结果写入 JSON 清单"),
+    ("cli.arg.failed_out", "把未成功的项写入列表文件, 可直接用 -i 重新下载"),
+    ("cli.arg.retry_failed", "重新下载之前的结果清单 (--manifest) 中未成功的项"),
+    ("cli.arg.retry_pass_delay", "批量结束后等待多少秒, 再重试一次因临时错误失败的项 [默认: 30]"),
+    ("cli.arg.no_retry_pass", "批量结束后不再重试因临时错误失败的项"),
+    ("cli.arg.control_socket", "批量下载的控制套接字路径, 供 'sed-dl ctl' 连接 (默认: 运行时目录下的 sed-dl.sock)"),
+    ("cli.arg.progress", "进度显示方式 (jsonl 在标准输出逐行输出 JSON 事件)"),
+    ("cli.arg.log_file", "同时把日志和下载事件写入文件"),
+    ("cli.arg.log_format", "日志文件格式 [默认: text]"),
+    ("cli.arg.log_level", "写入日志文件的最低级别, 与控制台 (--debug/RUST_LOG) 分开 [默认: debug]"),
+    ("cli.arg.log_rotate", "日志文件轮转: never、daily 或大小, 如 10MB [默认: never]"),
+    ("cli.arg.log_keep", "保留的轮转日志文件数 [默认: 5]"),
+    ("cli.arg.api_host", "接口主机, 可多次指定, 按顺序失败切换 (覆盖配置文件; 如指向本地测试服务器 http://127.0.0.1:8080)"),
+    ("cli.arg.cache_dir", "教材详情和目录的缓存目录 (默认: 用户缓存目录下的 sed-dl/http)"),
+    ("cli.arg.cache_ttl", "缓存在此时长内直接使用, 不向服务器确认 (覆盖 Cache-Control; 0 表示每次确认)"),
+    ("cli.arg.no_cache", "不读写教材详情和目录的缓存"),
+    ("cli.arg.offline", "离线模式: 只用缓存中的详情校验已下载的文件, 不发出任何请求, 也不需要 Token"),
+    ("cli.arg.har", "把教材详情、目录和下载请求记录为 HAR 文件以便排查 (Token 已打码, 不含 PDF 内容)"),
+    ("cli.arg.lang", "界面语言 (默认根据 LC_ALL/LANG 环境变量)"),
+    ("cli.arg.color", "是否输出彩色文字 (auto 时遵循 NO_COLOR 并在非终端下关闭)"),
+    ("cli.arg.compact", "批量下载时只显示总进度条, 不显示每一项的进度条"),
+    ("cli.arg.dashboard", "以全屏仪表盘显示下载进度, 可暂停、取消或重试单个任务 (需要终端)"),
+    ("cli.arg.no_journal", "批量下载时不在输出目录中记录下载日志 (不可用 'sed-dl resume' 续接)"),
+    ("cli.arg.accounts", "使用多个已保存的 Token 分摊下载 (逗号分隔的名称, 或 'all' 表示全部)"),
+    ("cli.arg.account_strategy", "多账号调度策略 [默认: round-robin]"),
+    ("cli.arg.account_cooldown", "账号遇到 429 后暂停使用的秒数 (响应中有 Retry-After 时以其为准); 403 最多暂停 30 秒 [默认: 300]"),
+    ("cli.value.progress.auto", "标准错误是终端时显示进度条, 否则同 plain"),
+    ("cli.value.progress.bars", "进度条"),
+    ("cli.value.progress.plain", "逐行输出开始、进度 (每 25%)、重试和结果, 适合写入日志"),
+    ("cli.value.progress.none", "不输出进度, 只保留日志和汇总"),
+    ("cli.value.progress.jsonl", "在标准输出逐行输出 JSON 格式的事件, 供脚本解析"),
+    ("cli.value.color.auto", "标准错误是终端且未设置 NO_COLOR 时使用颜色"),
+    ("cli.value.log_format.text", "与控制台相同的文本格式"),
+    ("cli.value.log_format.json", "每行一个 JSON 对象"),
+    ("cli.value.lang.zh", "中文"),
+    ("cli.value.lang.en", "English"),
+    ("cli.value.account_strategy.round-robin", "轮流使用各个账号"),
+    ("cli.value.account_strategy.lru", "优先使用最久未使用的账号"),
+    ("cli.value.browser.chromium", "Chrome / Edge / Brave 等基于 Chromium 的浏览器"),
+    ("cli.resume.about", "按输出目录中的下载日志续接上次中断的批量下载"),
+    ("cli.resume.arg.dir", "上次批量下载的输出目录"),
+    ("cli.browse.about", "全屏浏览教材目录, 多选后下载 (文件保存到 -o 指定的目录)"),
+    ("cli.ctl.about", "控制正在运行的批量下载"),
+    ("cli.serve.about", "作为后台服务运行, 通过本地 HTTP/JSON API 接收下载任务 (文件保存到 -o 指定的目录)"),
+    ("cli.serve.arg.listen", "监听地址"),
+    ("cli.serve.arg.jobs_file", "任务文件路径 (默认: 用户数据目录下的 sed-dl/jobs.json)"),
+    ("cli.token.about", "Access Token 相关操作"),
+    ("cli.token.check.about", "检查 Token 是否有效 (退出码: 0 有效, 3 失效, 4 无权访问)"),
+    ("cli.token.check.arg.probe", "用于探测的教材 URL 或 Content ID (默认读取配置文件 token.probe)"),
+    ("cli.token.set.about", "保存 Token (值取自 -t、环境变量、--token-file、--token-helper 或交互输入)"),
+    ("cli.token.set.arg.name", "Token 名称"),
+    ("cli.token.set.arg.encrypt", "使用口令加密保存 (口令可通过环境变量 SED_DL_PASSPHRASE 提供)"),
+    ("cli.token.set.arg.default", "同时设为默认 Token"),
+    ("cli.token.import.about", "从本地浏览器的登录信息中导入 Token (无需打开开发者工具)"),
+    ("cli.token.import.arg.browser", "浏览器类型"),
+    ("cli.token.import.arg.profile", "浏览器用户配置目录 (默认自动查找)"),
+    ("cli.token.import.arg.name", "保存的 Token 名称"),
+    ("cli.token.import.arg.encrypt", "使用口令加密保存"),
+    ("cli.token.import.arg.default", "同时设为默认 Token"),
+    ("cli.token.list.about", "列出已保存的 Token"),
+    ("cli.token.remove.about", "删除已保存的 Token"),
+    ("cli.token.default.about", "设置默认使用的 Token"),
+    ("cli.ctl.pause.about", "暂停派发新任务; 指定项目时只暂停该项 (下载中的会中止, 继续后重新下载)"),
+    ("cli.ctl.resume.about", "继续整个批量下载或单个项目"),
+    ("cli.ctl.concurrency.about", "修改最大并发数"),
+    ("cli.ctl.bump.about", "把排队中的项目移到队首"),
+    ("cli.ctl.cancel.about", "取消排队中或下载中的项目"),
+    ("cli.ctl.retry.about", "重新下载本次运行中失败或已取消的项目"),
+    ("cli.ctl.state.about", "查看当前状态"),
+    ("cli.ctl.state.arg.json", "以 JSON 格式输出"),
+];
+
+static EN: &[(&str, &str)] = &[
+    // AppError
+    ("error.reqwest", "network request error: {}"),
+    ("error.io", "file I/O error: {}"),
+    ("error.json", "JSON parse error: {}"),
+    ("error.detail_fetch", "failed to fetch textbook details: {}"),
+    ("error.invalid_input", "invalid input: {}"),
+    ("error.dir_creation", "failed to create directory: {}"),
+    ("error.http_status", "server returned error status {} (source: {})"),
+    ("error.transfer", "request to {} failed: {}"),
+    ("error.config", "config file error: {}"),
+    ("error.token_expired", "token is expired or invalid"),
+    ("error.credential", "failed to read credentials: {}"),
+    ("error.token_store", "token store error: {}"),
+    ("error.browser_import", "browser import failed: {}"),
+    ("error.offline", "offline mode: {}"),
+    ("error.cancelled", "operation cancelled"),
+    ("error.no_account", "no account available (all invalid or cooling down)"),
+    ("error.unknown_mirror", "unknown source"),
+    // DownloadStatus
+    ("status.label.OK", "verified"),
+    ("status.label.OK_UNVERIFIED", "no checksum available"),
+    ("status.label.SKIPPED", "already exists, skipped"),
+    ("status.label.E_TOKEN", "token invalid or expired"),
+    ("status.label.E_FORBIDDEN", "access denied"),
+    ("status.label.E_REMOVED", "resource removed"),
+    ("status.label.E_RATE_LIMITED", "rate limited"),
+    ("status.label.E_HTTP", "server error"),
+    ("status.label.E_TLS", "TLS handshake failed"),
+    ("status.label.E_TIMEOUT", "request timed out"),
+    ("status.label.E_NETWORK", "network error"),
+    ("status.label.E_DISK_FULL", "disk full"),
+    ("status.label.E_PERMISSION", "permission denied"),
+    ("status.label.E_INVALID_PDF", "not a valid PDF"),
+    ("status.label.E_MD5", "MD5 mismatch"),
+    ("status.label.E_SIZE", "file size mismatch"),
+    ("status.label.E_DETAILS", "failed to fetch details"),
+    ("status.label.E_ABORTED", "aborted, not processed"),
+    ("status.label.E_CANCELLED", "cancelled"),
+    ("status.label.E_UNEXPECTED", "unexpected error"),
+    ("status.hint.E_TOKEN", "The token has expired. Log in to the platform again and get a new Access Token."),
+    ("status.hint.E_FORBIDDEN", "This account may not download the resource (not purchased or insufficient permissions). Check the account's permissions."),
+    ("status.hint.E_REMOVED", "The resource was removed from the platform or the ID is wrong. Check whether the textbook is still available."),
+    ("status.hint.E_RATE_LIMITED", "The platform is rate limiting. Lower --max-concurrent-downloads or try again later."),
+    ("status.hint.E_HTTP", "The platform is temporarily unavailable. Try again later."),
+    ("status.hint.E_TLS", "TLS connection failed. Check the system clock and whether a proxy or firewall intercepts HTTPS."),
+    ("status.hint.E_TIMEOUT", "The connection timed out. Check the network or increase the number of retries."),
+    ("status.hint.E_NETWORK", "Network connection failed. Check the network and try again."),
+    ("status.hint.E_DISK_FULL", "The disk is full. Free some space or choose another directory with -o."),
+    ("status.hint.E_PERMISSION", "The output directory is not writable. Check its permissions or choose another directory with -o."),
+    ("status.hint.E_INVALID_PDF", "The download is not a PDF (possibly an error page). Try again later or check the token."),
+    ("status.hint.E_MD5", "The file may be incomplete. Run again to download it again."),
+    ("status.hint.E_SIZE", "The file may be incomplete. Run again to download it again."),
+    ("status.hint.E_DETAILS", "Could not parse the textbook information. Check that the URL/ID is correct."),
+    ("status.hint.E_ABORTED", "The batch stopped early and these items were not processed. Fix the problem and run again."),
+    ("status.hint.E_CANCELLED", "The download was interrupted (Ctrl-C or 'sed-dl ctl cancel'). Run again to continue."),
+    ("status.hint.E_UNEXPECTED", "An unexpected error occurred. Run again with --debug and report the problem."),
+    // 结果汇总
+    ("summary.done_batch", "{} Batch download finished!"),
+    ("summary.done_single", "{} Download finished!"),
+    ("summary.totals", "{} Total: {} | Succeeded: {} | Failed: {} | Skipped: {}"),
+    ("summary.skipped", "{} Skipped files (already present):"),
+    ("summary.accounts", "{} Per-account results:"),
+    ("summary.account_line", "{} succeeded: {} | failed: {}"),
+    ("summary.failed", "{} Failures:"),
+    ("summary.hints", "{} Suggestions:"),
+    ("summary.source", "source {}"),
+    ("summary.account", "account {}"),
+    ("summary.manifest_written", "{} Result manifest written to '{}'"),
+    ("summary.manifest_failed", "{} Failed to write the result manifest: {}"),
+    ("summary.har_written", "{} Wrote {} requests to the HAR capture '{}'"),
+    ("summary.har_failed", "{} Failed to write the HAR capture: {}"),
+    ("summary.failed_list_written", "{} {} unsuccessful item(s) written to '{}'; download them again with -i"),
+    ("summary.failed_list_failed", "{} Failed to write the failed list: {}"),
+    ("summary.resume_hint", "{} Progress is recorded in '{}'; run 'sed-dl resume {}' to continue"),
+    // 下载进度
+    ("progress.totals", "done {} | failed {} | left {}"),
+    ("progress.speed", " | avg {}/s"),
+    ("progress.eta", " | ETA {}"),
+    ("progress.total", "Total"),
+    ("progress.retry", "{} '{}' attempt {} failed ({}), retrying in {}..."),
+    ("progress.account_switched", "{} '{}' account '{}' got {}, switching to another account"),
+    ("progress.by_account", " (account {})"),
+    ("task.details_failed", "{} Failed to fetch details for '{}' (ID: {}): {}"),
+    ("task.skip_existing", "already present and verified, skipped"),
+    ("task.redownload", "verification failed, downloading again"),
+    ("task.token_tripped", "{} Token is invalid; no new downloads will be started"),
+    // 输入和输出位置
+    ("input.output_is_file", "'-o' must be a directory, not a file, for batch downloads"),
+    ("input.output_dir", "Files will be saved to '{}'"),
+    ("input.added", "{} Added: ID {}... (from {})"),
+    ("input.duplicate", "{} Duplicate skipped: '{}'"),
+    ("input.invalid", "{} Invalid input skipped: '{}' (from {})"),
+    ("input.source_url", "command-line URL"),
+    ("input.source_id", "command-line ID"),
+    ("input.source_line", "line {} of the file"),
+    ("input.source_manifest", "result manifest"),
+    ("input.no_items", "no valid download items found; check the input"),
+    ("input.no_content_id", "cannot find a Content ID in '{}'"),
+    ("input.no_source", "at least one input source is required (-u, -c, -i or --retry-failed)"),
+    // Token 预检和账号
+    ("preflight.skipped", "{} Token preflight failed, skipping: {}"),
+    ("preflight.account", "account '{}': "),
+    ("preflight.valid", "{} {}token preflight passed"),
+    ("preflight.forbidden", "{} {}token is valid but cannot access the probe textbook; some textbooks may not download"),
+    ("preflight.inconclusive", "{} {}probe returned {}; cannot confirm the token state, continuing"),
+    ("preflight.expired", "{} {}token is expired"),
+    ("preflight.failed", "{} {}token preflight failed, skipping: {}"),
+    ("preflight.reenter", "{} Token is expired, please enter a new one."),
+    ("accounts.none_saved", "no tokens saved yet; save one with 'sed-dl token set <name>' first"),
+    ("accounts.using", "{} Using {} accounts ({}), strategy: {}"),
+    // Token 子命令
+    ("token.legacy_file", "{} '{}' in the current directory is deprecated; run 'sed-dl token set' to save the token to the user config directory, then delete the file"),
+    ("token.save_failed", "{} Could not save the token: {}"),
+    ("token.saved", "{} Saved token '{}' to '{}'"),
+    ("token.probe_missing", "specify the textbook to probe with --probe or token.probe in the config file"),
+    ("token.valid", "Token is valid"),
+    ("token.invalid", "Token is expired or invalid"),
+    ("token.forbidden", "Token is valid but cannot access the probe textbook"),
+    ("token.nothing_to_save", "no token provided to save"),
+    ("token.empty", "the access token must not be empty"),
+    ("token.imported", "{} Read the token from the browser"),
+    ("token.list_empty", "{} No tokens saved yet; save one with 'sed-dl token set'"),
+    ("token.encrypted", "encrypted"),
+    ("token.plain", "plain"),
+    ("token.not_found", "no token named '{}'"),
+    ("token.removed", "{} Removed token '{}'"),
+    ("token.default_set", "{} Default token set to '{}'"),
+    // 客户端设置、目录浏览和控制命令
+    ("settings.no_cache_dir", "cannot determine the cache directory; specify one with --cache-dir"),
+    ("settings.offline_cache_disabled", "offline mode needs the cache (cache.enabled is false in the config file)"),
+    ("browse.fetching", "{} Fetching the textbook catalog..."),
+    ("browse.nothing_selected", "{} No textbooks selected"),
+    ("ctl.state", "{} {} | max concurrency: {} | downloading: {} | queued: {} | finished: {}"),
+    ("ctl.paused", "paused"),
+    ("ctl.running", "running"),
+    ("ctl.downloading", "downloading"),
+    ("ctl.queued", "queued"),
+    ("ctl.finished", "finished"),
+    // 运行过程
+    ("signal.stopping", "{} Interrupted: no new downloads will start, waiting for running ones to finish (press Ctrl-C again to abort them)"),
+    ("signal.aborting", "{} Aborting running downloads..."),
+    ("run.task_panicked", "task panicked: {}"),
+    ("run.retry_pass", "{} {} item(s) failed with temporary errors, retrying in {} s"),
+    ("run.no_jobs_file", "cannot determine the jobs file location; specify it with --jobs-file"),
+    ("run.not_a_terminal", "{} Standard output is not a terminal; not showing the dashboard"),
+    ("run.resuming", "{} Resuming: {} item(s) already done, {} previously failed item(s) retried first"),
+    ("run.journal_unavailable", "{} Download journal unavailable, progress will not be recorded: {}"),
+    ("run.control_socket", "{} Control this download with 'sed-dl ctl' (socket '{}')"),
+    ("run.control_unavailable", "{} Control socket unavailable: {}"),
+    ("run.token_expired_before_start", "{} Token is expired; nothing was downloaded. Update the token and run again."),
+    ("run.token_expired_stopped", "{} Token is expired; stopped with {} item(s) unprocessed. Update the token and run again."),
+    ("run.token_expired_paused", "{} Token is expired; downloads paused ({} item(s) pending). Enter a new token to continue."),
+    ("run.dashboard_error", "{} Dashboard error: {}"),
+    ("run.dashboard_panicked", "{} Dashboard exited abnormally: {}"),
+    // 客户端错误信息
+    ("client.offline_needs_cache", "offline mode needs the cache"),
+    ("client.offline_request", "not sending a request to {}"),
+    ("client.cache_miss", "'{}' is not in the cache"),
+    ("client.offline_download", "'{}' was not downloaded"),
+    ("client.no_source_pdf", "no source PDF found for content ID '{}'"),
+    ("client.no_pdf_url", "no PDF download URL found for content ID '{}'"),
+    ("client.unknown_download_error", "unknown download error"),
+    // 交互提示
+    ("prompt.yes_no", "(y/n)"),
+    ("prompt.passphrase", "Enter passphrase: "),
+    ("prompt.passphrase_again", "Enter passphrase again: "),
+    ("prompt.passphrase_required", "a passphrase is required; set the environment variable {}"),
+    ("prompt.passphrase_empty", "the passphrase must not be empty"),
+    ("prompt.passphrase_mismatch", "the passphrases do not match"),
+    ("prompt.use_saved_named", "Found saved token '{}'. Use it?"),
+    ("prompt.use_saved", "Found a saved token. Use it?"),
+    ("prompt.token", "Enter your Access Token: "),
+    ("prompt.token_empty", "The access token must not be empty, please try again."),
+    ("prompt.token_stdin_closed", "no access token provided (standard input is closed)"),
+    ("prompt.token_missing", "No access token found. Provide one with -t, the {} environment variable, --token-file, --token-helper or 'sed-dl token set'"),
+    ("prompt.token_to_save", "Enter the access token to save: "),
+    ("prompt.renew_account", "{} Enter a new token for account '{}'"),
+    ("prompt.token_saved", "{} Token saved as '{}' ({})"),
+    // 命令行帮助
+    ("daemon.tokens_reloaded", "{} Tokens reloaded; accounts back in use: {}"),
+    ("daemon.tokens_unchanged", "{} The tokens of all accounts are invalid and reloading did not change them"),
+    ("daemon.tokens_reload_failed", "{} Failed to reload tokens: {}"),
+    ("output.size_unknown", "size unknown"),
+    ("output.started", "{} Downloading '{}' ({})"),
+    ("output.attempt_started", "{} Attempt {} at downloading '{}' ({})"),
+    ("output.progress", "{} '{}' {}% downloaded ({}/{})"),
+    ("dashboard.sort.order", "order added"),
+    ("dashboard.column.name", "Name"),
+    ("dashboard.column.state", "State"),
+    ("dashboard.column.progress", "Progress"),
+    ("dashboard.column.speed", "Speed"),
+    ("dashboard.column.eta", "ETA"),
+    ("dashboard.column.retries", "Retries"),
+    ("dashboard.column.mirror", "Mirror"),
+    ("dashboard.column.account", "Account"),
+    ("dashboard.state.retrying", "retrying"),
+    ("dashboard.state.done", "done"),
+    ("dashboard.state.failed", "failed"),
+    ("dashboard.state.cancelled", "cancelled"),
+    ("dashboard.stopping", "No new tasks are started; waiting for running downloads to finish. Press q again to abort now"),
+    ("dashboard.header", " | concurrency {} | downloading {} queued {} done {} failed {} | {}/s (avg {}/s) | downloaded {} | elapsed {}"),
+    ("dashboard.tasks_title", " Tasks ({}) · sorted by: {} "),
+    ("dashboard.logs_title", " Log "),
+    ("dashboard.help", "↑↓ select  s sort  p pause/resume selected  c cancel selected  r retry selected  space pause/resume all  +/- concurrency  q quit"),
+    ("browse.select_first", "Nothing selected yet; press space in the list on the right to select"),
+    ("browse.root", "Textbooks"),
+    ("browse.search", "   Search: {}"),
+    ("browse.tags_title", " Categories "),
+    ("browse.books_title", " Textbooks ({}) "),
+    ("browse.detail.tags", "Tags: "),
+    ("browse.detail.files", "Files: "),
+    ("browse.no_match", "No matching textbooks"),
+    ("browse.help", "↑↓ move  Enter/→ open category  ←/Backspace back  Tab switch  space select  a select all  / search  d download ({} selected)  q quit"),
+    ("ctl.reply.paused_all", "No new tasks will be started; running downloads continue"),
+    ("ctl.reply.resumed_all", "Resumed"),
+    ("ctl.reply.paused_running", "Paused {} (the download was stopped and restarts when resumed)"),
+    ("ctl.reply.paused", "Paused {}"),
+    ("ctl.reply.not_in_queue", "{} is not in the queue"),
+    ("ctl.reply.resumed", "Resumed {}"),
+    ("ctl.reply.not_paused", "{} is not paused"),
+    ("ctl.reply.concurrency_zero", "Concurrency must be at least 1"),
+    ("ctl.reply.concurrency", "Maximum concurrency: {} -> {}"),
+    ("ctl.reply.round_ended", "No downloads are being dispatched right now; cannot bump or retry"),
+    ("ctl.reply.bumped", "Moved {} to the front of the queue"),
+    ("ctl.reply.not_queued", "{} is not queued"),
+    ("ctl.reply.cancelled", "Cancelled {}"),
+    ("ctl.reply.requeued", "Queued {} again"),
+    ("ctl.reply.not_failed", "{} did not fail and was not cancelled in this run"),
+    ("ctl.reply.bad_request", "Cannot parse the command: {}"),
+    ("ctl.cancelled_by_command", "cancelled by a control command"),
+    ("ctl.socket_in_use", "control socket '{}' is in use by another download"),
+    ("ctl.connect_failed", "cannot connect to control socket '{}' (is a batch download running?): {}"),
+    ("ctl.unsupported", "control sockets are not supported on this platform"),
+    ("daemon.save_failed", "Failed to save jobs file '{}': {}"),
+    ("daemon.cancel_finished", "Job {} has finished and cannot be cancelled"),
+    ("daemon.retry_unfinished", "Only failed or cancelled jobs can be retried; job {} is {}"),
+    ("daemon.job_started", "{} Starting job {}: {}"),
+    ("daemon.started", "{} Service started: http://{} (jobs file '{}', {} pending, saving to '{}')"),
+    ("daemon.stopping", "{} Stopping the service; running jobs continue at the next start"),
+    ("daemon.not_found", "Job {} does not exist"),
+    ("daemon.invalid_items", "Unrecognized URL or Content ID: {}"),
+    ("metrics.downloaded_bytes", "Bytes downloaded"),
+    ("metrics.succeeded", "Jobs that succeeded (including skipped)"),
+    ("metrics.failed", "Jobs that failed"),
+    ("metrics.finished", "Finished jobs by status code"),
+    ("metrics.jobs", "Jobs in each state"),
+    ("log.bad_rotation", "unrecognized rotation '{}' (use never, daily or a size such as 10MB)"),
+    ("log.bad_level", "unrecognized log level '{}'"),
+    ("log.write_failed", "Failed to write log file '{}': {}"),
+    ("log.event.queued", "queued"),
+    ("log.event.details_resolved", "textbook details fetched"),
+    ("log.event.download_started", "download started"),
+    ("log.event.retry", "download failed ({}), retrying in {} ms"),
+    ("log.event.account_switched", "account '{}' got {}, switching to another account"),
+    ("log.event.validated", "validation result: {}"),
+    ("log.event.finished_error", "finished: {} - {}"),
+    ("log.event.finished", "finished: {}"),
+    ("file.read_failed", "cannot read '{}': {}"),
+    ("file.write_failed", "cannot write '{}': {}"),
+    ("file.bad_format", "'{}' is malformed: {}"),
+    ("token_store.no_config_dir", "cannot determine the user config directory"),
+    ("token_store.key_derivation", "failed to derive a key from the passphrase: {}"),
+    ("token_store.encrypt_failed", "encryption failed"),
+    ("token_store.corrupted", "the encrypted data is corrupted"),
+    ("token_store.wrong_passphrase", "wrong passphrase or corrupted data"),
+    ("credential.source.command_line", "the command line"),
+    ("credential.source.env", "environment variable {}"),
+    ("credential.source.file", "file '{}'"),
+    ("credential.source.helper", "credential helper '{}'"),
+    ("credential.file_unreadable", "cannot read token file '{}': {}"),
+    ("credential.file_empty", "token file '{}' is empty"),
+    ("credential.helper_spawn", "cannot start credential helper '{}': {}"),
+    ("credential.helper_failed", "credential helper '{}' failed: {}"),
+    ("credential.helper_status", "credential helper '{}' exited with {}"),
+    ("credential.helper_no_password", "credential helper '{}' returned no password field"),
+    ("endpoints.no_hosts", "the endpoint table needs at least one host"),
+    ("endpoints.invalid_host", "invalid API host '{}'; expected something like https://example.com"),
+    ("endpoints.details_missing_id", "the textbook details URL template lacks {id}"),
+    ("journal.missing", "'{}' has no download journal to resume"),
+    ("journal.corrupted", "download journal '{}' is corrupted"),
+    ("journal.write_failed", "Failed to write the download journal: {}"),
+    ("journal.skipped_line", "Skipping an unparsable journal line: {}"),
+    ("manifest.invalid", "'{}' is not a valid result manifest: {}"),
+    ("daemon.jobs_file_malformed", "jobs file '{}' is malformed: {}"),
+    ("status.unknown_code", "unknown status code '{}'"),
+    ("browser_import.no_profile", "no browser profile directory found; pass one with --profile"),
+    ("browser_import.found", "Found a token in '{}'"),
+    ("browser_import.profile_failed", "Failed to read '{}': {}"),
+    ("browser_import.not_logged_in", "no SmartEdu login found in the browser; sign in to the platform with it first"),
+    ("browser_import.sqlite", "failed to read the browser database: {}"),
+    ("leveldb.partial", "LevelDB file only partly parsed, skipped the rest: {}"),
+    ("preflight.debug_state", "Token preflight ({}): {}"),
+    ("catalog.loaded", "The catalog lists {} textbooks"),
+    ("accounts.parked", "Account '{}' paused ({})"),
+    ("cache.corrupted", "Ignoring corrupted cache entry '{}': {}"),
+    ("cache.write_failed", "Failed to write cache '{}': {}"),
+    ("cache.hit", "Using cache: {}"),
+    ("cache.not_modified", "Cache not modified: {}"),
+    ("client.json_retry", "Fetching JSON failed ({}), retrying in {}: {}"),
+    ("client.host_switched", "Switching API host to {}"),
+    ("client.download_retry", "'{}' attempt {} failed ({}), retrying in {}"),
+    ("client.waiting_for_account", "'{}' has no account available, retrying in {}"),
+    ("client.account_switched", "'{}' account '{}' got {}, switching to another account"),
+    ("task.download_failed", "Downloading '{}' (ID: {}) failed: {}"),
+    ("token.using_saved", "Using saved token '{}' ({})"),
+    ("token.using_source", "Using the token from {}"),
+    ("settings.retry_config", "Retry settings: {}"),
+    ("har.binary_omitted", "binary content not recorded"),
+    ("har.file_omitted", "file content not recorded"),
+    ("har.no_response", "no response received (request cancelled)"),
+    ("cli.about", "Command-line downloader for textbooks on the National Smart Education Platform for Primary and Secondary Schools (Rust edition)"),
+    ("cli.after_help", "Examples:\n  # Download a single URL\n  sed-dl -u \"<textbook URL>\" -t \"<your TOKEN>\"\n\n  # Batch download from a file into a directory\n  sed-dl -i urls.txt -o ./textbooks -t \"<your TOKEN>\""),
+    ("cli.arg.url", "One or more textbook page URLs"),
+    ("cli.arg.content_id", "One or more textbook Content IDs"),
+    ("cli.arg.input_file", "Text file containing URLs/IDs"),
+    ("cli.arg.token", "Access Token"),
+    ("cli.arg.token_file", "Read the access token from a file"),
+    ("cli.arg.token_helper", "Get the access token from an external credential helper (git-credential protocol, runs '<CMD> get')"),
+    ("cli.arg.token_name", "Use the saved token with this name (see 'sed-dl token list')"),
+    ("cli.arg.no_input", "Never prompt (for CI/cron); fail if no token is available"),
+    ("cli.arg.output", "Output file path or directory"),
+    ("cli.arg.debug", "Enable verbose debug logging"),
+    ("cli.arg.max_concurrent_downloads", "Maximum number of concurrent downloads"),
+    ("cli.arg.config", "Config file path (default: sed-dl/config.toml in the user config directory)"),
+    ("cli.arg.details_attempts", "Maximum attempts to fetch details [default: 3]"),
+    ("cli.arg.download_attempts", "Maximum attempts to download a file [default: 3]"),
+    ("cli.arg.retry_base_delay", "Base retry backoff interval in milliseconds [default: 500]"),
+    ("cli.arg.retry_max_delay", "Maximum retry backoff interval in milliseconds [default: 30000]"),
+    ("cli.arg.max_retry_after", "Longest server Retry-After to wait, in seconds [default: 120]"),
+    ("cli.arg.no_retry_jitter", "Disable random jitter in retry intervals"),
+    ("cli.arg.skip_token_check", "Skip the token preflight check before downloading"),
+    ("cli.arg.manifest", "Write the result of every item to a JSON manifest when the run ends (including when interrupted)"),
+    ("cli.arg.failed_out", "Write unsuccessful items to a list file that can be passed to -i"),
+    ("cli.arg.retry_failed", "Download again the unsuccessful items of an earlier result manifest (--manifest)"),
+    ("cli.arg.retry_pass_delay", "Seconds to wait after a batch before retrying items that failed with temporary errors [default: 30]"),
+    ("cli.arg.no_retry_pass", "Do not retry items that failed with temporary errors after a batch"),
+    ("cli.arg.control_socket", "Control socket of a batch download, used by 'sed-dl ctl' (default: sed-dl.sock in the runtime directory)"),
+    ("cli.arg.progress", "How to show progress (jsonl prints one JSON event per line on standard output)"),
+    ("cli.arg.log_file", "Also write logs and download events to a file"),
+    ("cli.arg.log_format", "Log file format [default: text]"),
+    ("cli.arg.log_level", "Lowest level written to the log file, separate from the console (--debug/RUST_LOG) [default: debug]"),
+    ("cli.arg.log_rotate", "Log file rotation: never, daily or a size such as 10MB [default: never]"),
+    ("cli.arg.log_keep", "Number of rotated log files to keep [default: 5]"),
+    ("cli.arg.api_host", "API host; may be given several times and is tried in order (overrides the config file; e.g. a local test server http://127.0.0.1:8080)"),
+    ("cli.arg.cache_dir", "Cache directory for textbook details and the catalog (default: sed-dl/http in the user cache directory)"),
+    ("cli.arg.cache_ttl", "Use cached responses for this long without asking the server (overrides Cache-Control; 0 always revalidates)"),
+    ("cli.arg.no_cache", "Do not read or write the details and catalog cache"),
+    ("cli.arg.offline", "Offline mode: only verify downloaded files against cached details; sends no requests and needs no token"),
+    ("cli.arg.har", "Record details, catalog and download requests to a HAR file for troubleshooting (tokens masked, no PDF content)"),
+    ("cli.arg.lang", "Interface language (default: from the LC_ALL/LANG environment variables)"),
+    ("cli.arg.color", "Whether to print colored text (auto follows NO_COLOR and turns off when not a terminal)"),
+    ("cli.arg.compact", "Show only the total progress bar in batch downloads, not one per item"),
+    ("cli.arg.dashboard", "Show progress in a full-screen dashboard where single tasks can be paused, cancelled or retried (needs a terminal)"),
+    ("cli.arg.no_journal", "Do not keep a download journal in the output directory for batch downloads ('sed-dl resume' will not work)"),
+    ("cli.arg.accounts", "Spread downloads over several saved tokens (comma-separated names, or 'all')"),
+    ("cli.arg.account_strategy", "Scheduling strategy for multiple accounts [default: round-robin]"),
+    ("cli.arg.account_cooldown", "Seconds an account is paused after a 429 (a Retry-After in the response takes precedence); a 403 pauses it for at most 30 seconds [default: 300]"),
+    ("cli.value.progress.auto", "progress bars when standard error is a terminal, otherwise plain"),
+    ("cli.value.progress.bars", "progress bars"),
+    ("cli.value.progress.plain", "one line per start, progress (every 25%), retry and result; suited to log files"),
+    ("cli.value.progress.none", "no progress, only logs and the summary"),
+    ("cli.value.progress.jsonl", "one JSON event per line on standard output, for scripts"),
+    ("cli.value.color.auto", "color when standard error is a terminal and NO_COLOR is not set"),
+    ("cli.value.log_format.text", "the same text format as the console"),
+    ("cli.value.log_format.json", "one JSON object per line"),
+    ("cli.value.lang.zh", "中文"),
+    ("cli.value.lang.en", "English"),
+    ("cli.value.account_strategy.round-robin", "use the accounts in turn"),
+    ("cli.value.account_strategy.lru", "prefer the least recently used account"),
+    ("cli.value.browser.chromium", "Chromium-based browsers such as Chrome, Edge and Brave"),
+    ("cli.resume.about", "Resume an interrupted batch download from the journal in its output directory"),
+    ("cli.resume.arg.dir", "Output directory of the earlier batch download"),
+    ("cli.browse.about", "Browse the textbook catalog full-screen and download the selection (saved to the directory given with -o)"),
+    ("cli.ctl.about", "Control a running batch download"),
+    ("cli.serve.about", "Run as a background service that accepts download jobs over a local HTTP/JSON API (saved to the directory given with -o)"),
+    ("cli.serve.arg.listen", "Listen address"),
+    ("cli.serve.arg.jobs_file", "Jobs file path (default: sed-dl/jobs.json in the user data directory)"),
+    ("cli.token.about", "Manage Access Tokens"),
+    ("cli.token.check.about", "Check whether the token is valid (exit code: 0 valid, 3 invalid, 4 forbidden)"),
+    ("cli.token.check.arg.probe", "Textbook URL or Content ID to probe with (default: token.probe from the config file)"),
+    ("cli.token.set.about", "Save a token (taken from -t, the environment, --token-file, --token-helper or a prompt)"),
+    ("cli.token.set.arg.name", "Token name"),
+    ("cli.token.set.arg.encrypt", "Encrypt with a passphrase (may be given in the SED_DL_PASSPHRASE environment variable)"),
+    ("cli.token.set.arg.default", "Also make it the default token"),
+    ("cli.token.import.about", "Import the token from a local browser's login data (no developer tools needed)"),
+    ("cli.token.import.arg.browser", "Browser type"),
+    ("cli.token.import.arg.profile", "Browser profile directory (default: found automatically)"),
+    ("cli.token.import.arg.name", "Name to save the token under"),
+    ("cli.token.import.arg.encrypt", "Encrypt with a passphrase"),
+    ("cli.token.import.arg.default", "Also make it the default token"),
+    ("cli.token.list.about", "List saved tokens"),
+    ("cli.token.remove.about", "Remove a saved token"),
+    ("cli.token.default.about", "Set the token used by default"),
+    ("cli.ctl.pause.about", "Stop starting new tasks; with an item, pause only that item (a running download is stopped and restarts when resumed)"),
+    ("cli.ctl.resume.about", "Resume the whole batch or a single item"),
+    ("cli.ctl.concurrency.about", "Change the maximum concurrency"),
+    ("cli.ctl.bump.about", "Move a queued item to the front of the queue"),
+    ("cli.ctl.cancel.about", "Cancel a queued or running item"),
+    ("cli.ctl.retry.about", "Download again an item that failed or was cancelled in this run"),
+    ("cli.ctl.state.about", "Show the current state"),
+    ("cli.ctl.state.arg.json", "Print as JSON"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DownloadStatus;
+    use regex::Regex;
+    use std::collections::HashSet;
+    use std::path::Path;
+
+    fn keys(table: &[(&'static str, &'static str)]) -> HashSet<&'static str> {
+        table.iter().map(|(key, _)| *key).collect()
+    }
+
+    #[test]
+    fn locales_have_the_same_keys() {
+        let (zh, en) = (keys(ZH), keys(EN));
+        let mut missing_en: Vec<_> = zh.difference(&en).collect();
+        let mut missing_zh: Vec<_> = en.difference(&zh).collect();
+        missing_en.sort();
+        missing_zh.sort();
+        assert!(missing_en.is_empty(), "英文缺少: {:?}", missing_en);
+        assert!(missing_zh.is_empty(), "中文缺少: {:?}", missing_zh);
+    }
+
+    #[test]
+    fn no_duplicate_keys() {
+        assert_eq!(keys(ZH).len(), ZH.len(), "中文有重复的键");
+        assert_eq!(keys(EN).len(), EN.len(), "英文有重复的键");
+    }
+
+    #[test]
+    fn placeholders_match() {
+        for (key, zh) in ZH {
+            let en = lookup(Lang::En, key).unwrap_or_default();
+            assert_eq!(zh.matches("{}").count(), en.matches("{}").count(), "'{}' 的占位符数量不一致", key);
+        }
+    }
+
+    #[test]
+    fn every_status_is_translated() {
+        for lang in Lang::ALL {
+            for status in DownloadStatus::ALL {
+                assert!(lookup(lang, &format!("status.label.{}", status.code())).is_some(), "{:?} 缺少 {} 的说明", lang, status.code());
+                let has_hint = !status.is_success() && status != DownloadStatus::Skipped;
+                assert_eq!(lookup(lang, &format!("status.hint.{}", status.code())).is_some(), has_hint, "{:?} 中 {} 的建议", lang, status.code());
+            }
+        }
+    }
+
+    /// `src/` 下的全部源文件
+    fn source_files() -> Vec<std::path::PathBuf> {
+        let mut stack = vec![Path::new(env!("CARGO_MANIFEST_DIR")).join("src")];
+        let mut files = Vec::new();
+        while let Some(dir) = stack.pop() {
+            for entry in std::fs::read_dir(dir).unwrap().map(Result::unwrap) {
+                let path = entry.path();
+                if path.is_dir() {
+                    stack.push(path);
+                } else if path.extension().is_some_and(|e| e == "rs") {
+                    files.push(path);
+                }
+            }
+        }
+        files
+    }
+
+    /// 源代码中用 `tr!`/`t` 引用的键都应存在于目录中
+    #[test]
+    fn referenced_keys_exist() {
+        let pattern = Regex::new(r#"(?:tr!|i18n::t)\(\s*"([a-z_]+\.[a-z0-9_.]+)""#).unwrap();
+        let mut referenced = Vec::new();
+        for path in source_files() {
+            let source = std::fs::read_to_string(&path).unwrap();
+            referenced.extend(pattern.captures_iter(&source).map(|c| (path.clone(), c[1].to_string())));
+        }
+        assert!(!referenced.is_empty());
+        for lang in Lang::ALL {
+            for (path, key) in &referenced {
+                assert!(lookup(lang, key).is_some(), "{:?} 缺少 {} 中引用的 '{}'", lang, path.display(), key);
+            }
+        }
+    }
+
+    /// 界面文字、日志和错误信息应通过 `tr!` 取自消息目录, 源代码中不能直接写中文。
+    /// 注释、测试模块和 `expect` 的说明 (写给开发者的断言) 不在此列。
+    #[test]
+    fn user_facing_strings_are_translated() {
+        let cjk = Regex::new(r"[\p{Han}]").unwrap();
+        let files: Vec<_> = source_files().into_iter().filter(|p| !p.ends_with("i18n.rs")).collect();
+        assert!(files.len() > 10);
+        for path in files {
+            let source = std::fs::read_to_string(&path).unwrap();
+            let code = source.split("#[cfg(test)]").next().unwrap();
+            for (start, literal) in string_literals(code) {
+                if !cjk.is_match(literal) || code[..start].trim_end().ends_with(".expect(") { continue; }
+                let line = code[..start].lines().count();
+                panic!("{}:{} 中有未翻译的字符串 \"{}\"", path.display(), line, literal);
+            }
+        }
+    }
+
+    /// 注释以外的字符串字面量及其起始位置
+    fn string_literals(source: &str) -> Vec<(usize, &str)> {
+        let mut literals = Vec::new();
+        let mut chars = source.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '/' if chars.peek().is_some_and(|(_, c)| *c == '/') => {
+                    chars.by_ref().find(|(_, c)| *c == '\n');
+                }
+                '/' if chars.peek().is_some_and(|(_, c)| *c == '*') => {
+                    let end = source[i + 2..].find("*/").map_or(source.len(), |e| i + 2 + e + 2);
+                    while chars.next_if(|(j, _)| *j < end).is_some() {}
+                }
+                // 原始字符串 r"..."、r#"..."#
+                'r' if !source[..i].ends_with(|c: char| c.is_alphanumeric() || c == '_')
+                    && source[i + 1..].trim_start_matches('#').starts_with('"') => {
+                    let hashes = source[i + 1..].len() - source[i + 1..].trim_start_matches('#').len();
+                    let open = i + hashes + 2;
+                    let close = format!("\"{}", "#".repeat(hashes));
+                    let end = source[open..].find(&close).map_or(source.len(), |e| open + e);
+                    literals.push((i, &source[open..end]));
+                    while chars.next_if(|(j, _)| *j < end + close.len()).is_some() {}
+                }
+                // 字符字面量 ('"'、'\'' 等); 生命周期标注不是字面量
+                '\'' => {
+                    let rest = &source[i + 1..];
+                    let len = if rest.starts_with('\\') { rest[2..].find('\'').map(|e| e + 3) } else {
+                        rest.chars().next().filter(|c| rest[c.len_utf8()..].starts_with('\'')).map(|c| c.len_utf8() + 1)
+                    };
+                    if let Some(len) = len {
+                        while chars.next_if(|(j, _)| *j <= i + len).is_some() {}
+                    }
+                }
+                '"' => {
+                    let mut escaped = false;
+                    for (j, c) in chars.by_ref() {
+                        match c {
+                            '\\' if !escaped => escaped = true,
+                            '"' if !escaped => {
+                                literals.push((i, &source[i + 1..j]));
+                                break;
+                            }
+                            _ => escaped = false,
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        literals
+    }
+
+    #[test]
+    fn detects_language_from_locale() {
+        assert_eq!(Lang::from_locale("zh_CN.UTF-8"), Lang::Zh);
+        assert_eq!(Lang::from_locale("C.UTF-8"), Lang::Zh);
+        assert_eq!(Lang::from_locale("POSIX"), Lang::Zh);
+        assert_eq!(Lang::from_locale("en_US.UTF-8"), Lang::En);
+        assert_eq!(Lang::from_locale("de_DE"), Lang::En);
+    }
+
+    #[test]
+    fn formats_placeholders_in_order() {
+        assert_eq!(format("{} / {}", &[&1, &"b"]), "1 / b");
+        assert_eq!(format("{} {}", &[&1]), "1 {}");
+    }
+}
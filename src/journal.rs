@@ -0,0 +1,157 @@
+// src/journal.rs
+
+use crate::{DownloadItem, TaskResult};
+use sed_dl::{tr, AppError, DownloadStatus};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+
+/// 批量下载日志的文件名, 位于输出目录下
+pub static JOURNAL_FILE: &str = ".sed-dl-journal.jsonl";
+
+/// 日志的每一行。第一行是批次信息, 之后每处理完一项追加一行, 同一项以最后一行为准。
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Record {
+    Batch { created_at: String, items: Vec<BatchItem> },
+    Item { content_id: String, status: DownloadStatus, file: Option<PathBuf>, size: Option<u64>, mtime_ms: Option<u64> },
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct BatchItem {
+    content_id: String,
+    input: String,
+}
+
+/// 续接时从日志中得到的状态
+#[derive(Debug, Default)]
+pub struct ResumeState {
+    /// 已完成且文件未被改动的项: Content ID -> 文件路径
+    pub done: HashMap<String, PathBuf>,
+    /// 上次失败的项
+    pub failed: HashSet<String>,
+}
+
+/// 逐项记录批量下载进度的日志 (JSON Lines, 只追加)
+pub struct Journal {
+    path: PathBuf,
+    file: Mutex<std::fs::File>,
+}
+
+fn file_stamp(path: &Path) -> Option<(u64, u64)> {
+    let metadata = std::fs::metadata(path).ok()?;
+    let mtime = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?.as_millis() as u64;
+    Some((metadata.len(), mtime))
+}
+
+fn read_records(path: &Path) -> Result<Vec<Record>, AppError> {
+    let file = std::fs::File::open(path)?;
+    let mut records = Vec::new();
+    for line in std::io::BufReader::new(file).lines() {
+        let line = line?;
+        if line.trim().is_empty() { continue; }
+        match serde_json::from_str(&line) {
+            Ok(record) => records.push(record),
+            // 进程崩溃时最后一行可能只写了一半
+            Err(e) => log::debug!("{}", tr!("journal.skipped_line", e)),
+        }
+    }
+    Ok(records)
+}
+
+fn batch_items(records: &[Record]) -> Option<&[BatchItem]> {
+    match records.first()? {
+        Record::Batch { items, .. } => Some(items),
+        Record::Item { .. } => None,
+    }
+}
+
+impl Journal {
+    pub fn path_in(dir: &Path) -> PathBuf {
+        dir.join(JOURNAL_FILE)
+    }
+
+    /// 读取日志中记录的批次, 供 `sed-dl resume` 使用
+    pub fn load_items(dir: &Path) -> Result<Vec<DownloadItem>, AppError> {
+        let path = Self::path_in(dir);
+        if !path.exists() {
+            return Err(AppError::InvalidInput(tr!("journal.missing", dir.display())));
+        }
+        let records = read_records(&path)?;
+        let items = batch_items(&records)
+            .ok_or_else(|| AppError::InvalidInput(tr!("journal.corrupted", path.display())))?;
+        Ok(items.iter().map(|i| (i.content_id.clone(), i.input.clone())).collect())
+    }
+
+    /// 打开输出目录下的日志。日志中的批次与本次的下载项相同时续接, 否则重新开始。
+    pub fn open(dir: &Path, items: &[DownloadItem]) -> Result<(Journal, ResumeState), AppError> {
+        let path = Self::path_in(dir);
+        let records = if path.exists() { read_records(&path)? } else { Vec::new() };
+        let wanted: HashSet<&str> = items.iter().map(|(id, _)| id.as_str()).collect();
+        let same_batch = batch_items(&records)
+            .is_some_and(|batch| batch.iter().map(|i| i.content_id.as_str()).collect::<HashSet<_>>() == wanted);
+
+        if !same_batch {
+            let mut file = std::fs::File::create(&path)?;
+            let header = Record::Batch {
+                created_at: chrono::Local::now().to_rfc3339(),
+                items: items.iter().map(|(id, input)| BatchItem { content_id: id.clone(), input: input.clone() }).collect(),
+            };
+            writeln!(file, "{}", serde_json::to_string(&header)?)?;
+            return Ok((Journal { path, file: Mutex::new(file) }, ResumeState::default()));
+        }
+
+        let mut state = ResumeState::default();
+        for record in records {
+            let Record::Item { content_id, status, file, size, mtime_ms } = record else { continue };
+            state.done.remove(&content_id);
+            state.failed.remove(&content_id);
+            if !status.is_success() {
+                state.failed.insert(content_id);
+                continue;
+            }
+            // 文件大小和修改时间都没变才视为已完成, 不必重新计算 MD5
+            if let (Some(file), Some(size), Some(mtime)) = (file, size, mtime_ms)
+                && file_stamp(&file) == Some((size, mtime))
+            {
+                state.done.insert(content_id, file);
+            }
+        }
+        let file = std::fs::OpenOptions::new().append(true).open(&path)?;
+        // 截掉崩溃时只写了一半的最后一行, 以免新记录接在它后面
+        let content = std::fs::read(&path)?;
+        let complete = content.iter().rposition(|&b| b == b'\n').map_or(0, |i| i + 1);
+        if complete < content.len() {
+            file.set_len(complete as u64)?;
+        }
+        Ok((Journal { path, file: Mutex::new(file) }, state))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 记录一项的结果。未开始处理的项 (中止、取消) 不记录。
+    pub fn record(&self, result: &TaskResult) {
+        if matches!(result.status, DownloadStatus::Aborted | DownloadStatus::Cancelled) { return; }
+        let stamp = result.path.as_deref().filter(|_| result.status.is_success()).and_then(file_stamp);
+        let record = Record::Item {
+            content_id: result.content_id.clone(),
+            status: result.status,
+            file: result.path.clone(),
+            size: stamp.map(|(size, _)| size),
+            mtime_ms: stamp.map(|(_, mtime)| mtime),
+        };
+        let line = match serde_json::to_string(&record) {
+            Ok(line) => line,
+            Err(e) => return log::warn!("{}", tr!("journal.write_failed", e)),
+        };
+        let mut file = self.file.lock().unwrap();
+        if let Err(e) = writeln!(file, "{}", line) {
+            log::warn!("{}", tr!("journal.write_failed", e));
+        }
+    }
+}
@@ -0,0 +1,177 @@
+// src/leveldb.rs
+
+//! 只读的最小 LevelDB 解析器, 仅用于读取 Chromium 的 Local Storage。
+//! 直接扫描目录中的 `.log` (预写日志) 和 `.ldb` (SSTable) 文件, 不解析 MANIFEST,
+//! 同一个键以序列号最大的记录为准。
+
+use crate::tr;
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+
+const LOG_BLOCK_SIZE: usize = 32 * 1024;
+const LOG_HEADER_SIZE: usize = 7;
+const TABLE_FOOTER_SIZE: usize = 48;
+const TABLE_MAGIC: u64 = 0xdb47_7524_8b80_fb57;
+
+struct Record {
+    seq: u64,
+    /// `None` 表示该键已被删除
+    value: Option<Vec<u8>>,
+}
+
+type Records = HashMap<Vec<u8>, Record>;
+
+/// 读取目录中所有仍然有效的键值对。无法解析的文件会被跳过。
+pub fn read_all(dir: &Path) -> io::Result<HashMap<Vec<u8>, Vec<u8>>> {
+    let mut records = Records::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let parse: fn(&[u8], &mut Records) -> Option<()> = match path.extension().and_then(|e| e.to_str()) {
+            Some("log") => read_log,
+            Some("ldb") | Some("sst") => read_table,
+            _ => continue,
+        };
+        let data = std::fs::read(&path)?;
+        if parse(&data, &mut records).is_none() {
+            log::debug!("{}", tr!("leveldb.partial", path.display()));
+        }
+    }
+    Ok(records.into_iter().filter_map(|(key, r)| r.value.map(|v| (key, v))).collect())
+}
+
+fn put(records: &mut Records, key: Vec<u8>, seq: u64, value: Option<Vec<u8>>) {
+    match records.get(&key) {
+        Some(existing) if existing.seq >= seq => {}
+        _ => { records.insert(key, Record { seq, value }); }
+    }
+}
+
+fn read_varint(data: &[u8], pos: &mut usize) -> Option<u64> {
+    let mut result = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = *data.get(*pos)?;
+        *pos += 1;
+        result |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 { return Some(result); }
+    }
+    None
+}
+
+fn read_slice<'a>(data: &'a [u8], pos: &mut usize) -> Option<&'a [u8]> {
+    let len = read_varint(data, pos)? as usize;
+    let slice = data.get(*pos..pos.checked_add(len)?)?;
+    *pos += len;
+    Some(slice)
+}
+
+fn read_u32(data: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn read_u64(data: &[u8], at: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(data.get(at..at + 8)?.try_into().ok()?))
+}
+
+// --- 预写日志 (.log) ---
+
+fn read_log(data: &[u8], records: &mut Records) -> Option<()> {
+    let mut pending = Vec::new();
+    let mut pos = 0;
+    while pos + LOG_HEADER_SIZE <= data.len() {
+        let block_remaining = LOG_BLOCK_SIZE - pos % LOG_BLOCK_SIZE;
+        if block_remaining < LOG_HEADER_SIZE {
+            // 块尾不足一个头部的部分是填充
+            pos += block_remaining;
+            continue;
+        }
+        let len = u16::from_le_bytes([data[pos + 4], data[pos + 5]]) as usize;
+        let kind = data[pos + 6];
+        let payload = data.get(pos + LOG_HEADER_SIZE..pos + LOG_HEADER_SIZE + len)?;
+        match kind {
+            1 => read_write_batch(payload, records)?, // FULL
+            2 => { pending.clear(); pending.extend_from_slice(payload); } // FIRST
+            3 => pending.extend_from_slice(payload), // MIDDLE
+            4 => { // LAST
+                pending.extend_from_slice(payload);
+                read_write_batch(&pending, records)?;
+                pending.clear();
+            }
+            _ => {}
+        }
+        pos += LOG_HEADER_SIZE + len;
+    }
+    Some(())
+}
+
+fn read_write_batch(data: &[u8], records: &mut Records) -> Option<()> {
+    let seq = read_u64(data, 0)?;
+    let count = read_u32(data, 8)?;
+    let mut pos = 12;
+    for i in 0..u64::from(count) {
+        let tag = *data.get(pos)?;
+        pos += 1;
+        let key = read_slice(data, &mut pos)?.to_vec();
+        let value = match tag {
+            1 => Some(read_slice(data, &mut pos)?.to_vec()),
+            _ => None,
+        };
+        put(records, key, seq + i, value);
+    }
+    Some(())
+}
+
+// --- SSTable (.ldb) ---
+
+fn read_table(data: &[u8], records: &mut Records) -> Option<()> {
+    let footer = data.get(data.len().checked_sub(TABLE_FOOTER_SIZE)?..)?;
+    if read_u64(footer, TABLE_FOOTER_SIZE - 8)? != TABLE_MAGIC { return None; }
+    let mut pos = 0;
+    let _metaindex = read_block_handle(footer, &mut pos)?;
+    let index = read_block_handle(footer, &mut pos)?;
+    for (_, handle) in block_entries(&read_block(data, index)?)? {
+        let block = read_block(data, read_block_handle(&handle, &mut 0)?)?;
+        for (internal_key, value) in block_entries(&block)? {
+            // 内部键 = 用户键 + 8 字节 (序列号 << 8 | 类型)
+            let Some(split) = internal_key.len().checked_sub(8) else { continue };
+            let trailer = read_u64(&internal_key, split)?;
+            let value = (trailer & 0xff == 1).then_some(value);
+            put(records, internal_key[..split].to_vec(), trailer >> 8, value);
+        }
+    }
+    Some(())
+}
+
+fn read_block_handle(data: &[u8], pos: &mut usize) -> Option<(usize, usize)> {
+    Some((read_varint(data, pos)? as usize, read_varint(data, pos)? as usize))
+}
+
+fn read_block(data: &[u8], (offset, size): (usize, usize)) -> Option<Vec<u8>> {
+    let raw = data.get(offset..offset.checked_add(size)?)?;
+    // 块后紧跟 1 字节压缩类型和 4 字节校验和
+    match *data.get(offset + size)? {
+        0 => Some(raw.to_vec()),
+        1 => snap::raw::Decoder::new().decompress_vec(raw).ok(),
+        _ => None,
+    }
+}
+
+fn block_entries(block: &[u8]) -> Option<Vec<(Vec<u8>, Vec<u8>)>> {
+    let num_restarts = read_u32(block, block.len().checked_sub(4)?)? as usize;
+    let data_end = block.len().checked_sub(4 + 4 * num_restarts)?;
+    let mut entries = Vec::new();
+    let mut key = Vec::new();
+    let mut pos = 0;
+    while pos < data_end {
+        let shared = read_varint(block, &mut pos)? as usize;
+        let non_shared = read_varint(block, &mut pos)? as usize;
+        let value_len = read_varint(block, &mut pos)? as usize;
+        key.truncate(shared);
+        key.extend_from_slice(block.get(pos..pos + non_shared)?);
+        pos += non_shared;
+        let value = block.get(pos..pos + value_len)?.to_vec();
+        pos += value_len;
+        entries.push((key.clone(), value));
+    }
+    Some(entries)
+}
@@ -0,0 +1,39 @@
+// src/lib.rs
+
+//! 国家中小学智慧教育平台教材下载库, 命令行工具 `sed-dl` 基于此实现。
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), sed_dl::AppError> {
+//! use sed_dl::SmartEduClient;
+//!
+//! let client = SmartEduClient::builder().token("<ACCESS_TOKEN>").build()?;
+//! let info = client.details("00000000-0000-0000-0000-000000000000").await?;
+//! let outcome = client.download_to_path(&info, info.filename.as_ref()).await;
+//! println!("{:?}", outcome.result?);
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod browser_import;
+mod catalog;
+mod client;
+pub mod credential;
+pub mod endpoints;
+mod error;
+pub mod events;
+pub mod har;
+pub mod http_cache;
+pub mod i18n;
+mod leveldb;
+pub mod preflight;
+pub mod retry;
+mod status;
+pub mod token_pool;
+pub mod token_store;
+
+pub use catalog::{CatalogAsset, CatalogEntry, CatalogTag, TagNode};
+pub use client::{get_content_id, sanitize_filename, verify_file, DownloadOutcome, SmartEduClient, SmartEduClientBuilder, TextbookInfo};
+pub use error::{mirror_of, AppError};
+pub use events::{Event, EventSink};
+pub use status::DownloadStatus;
+pub use tokio_util::sync::CancellationToken;
@@ -0,0 +1,390 @@
+// src/logfile.rs
+
+//! `--log-file`: 把日志和下载事件写入文件, 可按大小或日期轮转, 级别与控制台分开设置
+
+use crate::config::LogSection;
+use crate::Cli;
+use chrono::{Local, NaiveDate};
+use clap::ValueEnum;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use sed_dl::{tr, AppError, Event, EventSink};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+/// 日志文件默认记录的级别
+const DEFAULT_LEVEL: LevelFilter = LevelFilter::Debug;
+/// 默认保留的轮转文件数
+const DEFAULT_KEEP: usize = 5;
+
+static ANSI_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"\x1b\[[0-9;]*m").unwrap());
+
+/// 去掉彩色输出的控制字符
+pub fn strip_ansi(text: &str) -> std::borrow::Cow<'_, str> {
+    ANSI_REGEX.replace_all(text, "")
+}
+
+#[derive(ValueEnum, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// 与控制台相同的文本格式
+    #[default]
+    Text,
+    /// 每行一个 JSON 对象
+    Json,
+}
+
+/// 日志文件的轮转方式
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(try_from = "String")]
+pub enum Rotation {
+    #[default]
+    Never,
+    /// 每天一个文件, 旧文件改名为 `<文件名>.<日期>`
+    Daily,
+    /// 超过指定字节数时改名为 `<文件名>.1`, 已有的依次后移
+    Size(u64),
+}
+
+impl FromStr for Rotation {
+    type Err = String;
+
+    /// 接受 `never`、`daily` 或大小, 如 `10MB`、`512K`、`1048576`
+    fn from_str(s: &str) -> Result<Rotation, String> {
+        let lower = s.trim().to_ascii_lowercase();
+        match lower.as_str() {
+            "never" => return Ok(Rotation::Never),
+            "daily" => return Ok(Rotation::Daily),
+            _ => {}
+        }
+        let digits = lower.find(|c: char| !c.is_ascii_digit()).unwrap_or(lower.len());
+        let (number, unit) = lower.split_at(digits);
+        let multiplier = match unit.trim().trim_end_matches("ib").trim_end_matches('b') {
+            "" => 1,
+            "k" => 1 << 10,
+            "m" => 1 << 20,
+            "g" => 1 << 30,
+            _ => return Err(tr!("log.bad_rotation", s)),
+        };
+        match number.parse::<u64>() {
+            Ok(n) if n > 0 => Ok(Rotation::Size(n * multiplier)),
+            _ => Err(tr!("log.bad_rotation", s)),
+        }
+    }
+}
+
+impl TryFrom<String> for Rotation {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Rotation, String> {
+        s.parse()
+    }
+}
+
+/// 合并命令行和配置文件后的日志文件设置
+pub struct LogFileOptions {
+    pub path: PathBuf,
+    pub format: LogFormat,
+    pub level: LevelFilter,
+    pub rotation: Rotation,
+    pub keep: usize,
+}
+
+impl LogFileOptions {
+    /// 命令行优先于配置文件; 两者都未指定日志文件时返回 `None`
+    pub fn resolve(cli: &Cli, section: &LogSection) -> Result<Option<LogFileOptions>, AppError> {
+        let Some(path) = cli.log_file.clone().or_else(|| section.file.clone()) else { return Ok(None) };
+        let level = match (cli.log_level, &section.level) {
+            (Some(level), _) => level,
+            (None, Some(level)) => level.parse().map_err(|_| AppError::Config(tr!("log.bad_level", level)))?,
+            (None, None) => DEFAULT_LEVEL,
+        };
+        Ok(Some(LogFileOptions {
+            path,
+            format: cli.log_format.or(section.format).unwrap_or_default(),
+            level,
+            rotation: cli.log_rotate.or(section.rotate).unwrap_or_default(),
+            keep: cli.log_keep.or(section.keep).unwrap_or(DEFAULT_KEEP).max(1),
+        }))
+    }
+}
+
+/// 写入日志文件的一条记录的附加字段
+#[derive(Default, Clone)]
+struct Fields {
+    event: Option<&'static str>,
+    content_id: Option<String>,
+    filename: Option<String>,
+    attempt: Option<u32>,
+    mirror: Option<String>,
+    status: Option<&'static str>,
+}
+
+/// 下载项最近一次事件中的文件名、尝试次数和镜像, 用于补全后续事件的字段
+#[derive(Default)]
+struct ItemContext {
+    filename: Option<String>,
+    attempt: Option<u32>,
+    mirror: Option<String>,
+}
+
+/// 日志文件。既作为日志的输出目标, 也订阅下载事件, 把事件连同 Content ID、文件名等字段写入。
+pub struct FileLog {
+    inner: Mutex<FileLogInner>,
+    format: LogFormat,
+    level: LevelFilter,
+}
+
+struct FileLogInner {
+    file: RotatingFile,
+    items: HashMap<String, ItemContext>,
+}
+
+impl FileLog {
+    pub fn open(options: LogFileOptions) -> Result<FileLog, AppError> {
+        let file = RotatingFile::open(options.path, options.rotation, options.keep)?;
+        Ok(FileLog {
+            inner: Mutex::new(FileLogInner { file, items: HashMap::new() }),
+            format: options.format,
+            level: options.level,
+        })
+    }
+
+    fn write(&self, inner: &mut FileLogInner, level: Level, target: &str, message: &str, fields: Fields) {
+        let now = Local::now();
+        let line = match self.format {
+            LogFormat::Text => {
+                let mut context = Vec::new();
+                if let Some(id) = &fields.content_id { context.push(format!("contentId={}", id)); }
+                if let Some(filename) = &fields.filename { context.push(format!("filename={}", filename)); }
+                if let Some(attempt) = fields.attempt { context.push(format!("attempt={}", attempt)); }
+                if let Some(mirror) = &fields.mirror { context.push(format!("mirror={}", mirror)); }
+                let context = if context.is_empty() { String::new() } else { format!(" ({})", context.join(", ")) };
+                format!("{} [{}] - {}{}", now.format("%Y-%m-%d %H:%M:%S"), level, message, context)
+            }
+            LogFormat::Json => {
+                let mut record = Map::new();
+                record.insert("timestamp".into(), json!(now.to_rfc3339()));
+                record.insert("level".into(), json!(level.as_str()));
+                record.insert("target".into(), json!(target));
+                record.insert("message".into(), json!(message));
+                let optional = [
+                    ("event", fields.event.map(Value::from)),
+                    ("contentId", fields.content_id.map(Value::from)),
+                    ("filename", fields.filename.map(Value::from)),
+                    ("attempt", fields.attempt.map(Value::from)),
+                    ("mirror", fields.mirror.map(Value::from)),
+                    ("status", fields.status.map(Value::from)),
+                ];
+                record.extend(optional.into_iter().filter_map(|(k, v)| Some((k.to_string(), v?))));
+                Value::Object(record).to_string()
+            }
+        };
+        if let Err(e) = inner.file.write_line(&line) {
+            // 日志文件写不进去时不能再通过日志报告, 只能直接输出
+            eprintln!("{}", tr!("log.write_failed", inner.file.path.display(), e));
+        }
+    }
+
+    fn record(&self, record: &Record) {
+        // 依赖库的调试信息过多, 只记录本程序的, 以及所有警告和错误
+        if record.level() > Level::Info && !record.target().starts_with("sed_dl") { return; }
+        let message = record.args().to_string();
+        let mut inner = self.inner.lock().unwrap();
+        for line in message.lines().map(strip_ansi).filter(|l| !l.trim().is_empty()) {
+            self.write(&mut inner, record.level(), record.target(), &line, Fields::default());
+        }
+    }
+}
+
+impl EventSink for FileLog {
+    fn on_event(&self, event: &Event) {
+        let content_id = event.content_id();
+        let (level, name, message) = match event {
+            Event::Queued { .. } => (Level::Debug, "queued", tr!("log.event.queued")),
+            Event::DetailsResolved { .. } => (Level::Debug, "details_resolved", tr!("log.event.details_resolved")),
+            Event::DownloadStarted { .. } => (Level::Info, "download_started", tr!("log.event.download_started")),
+            // 进度事件过于频繁, 不写入文件
+            Event::Progress { .. } => return,
+            Event::Retry { delay_ms, reason, .. } => (Level::Warn, "retry", tr!("log.event.retry", reason, delay_ms)),
+            Event::AccountSwitched { account, http_status, .. } => (Level::Warn, "account_switched", tr!("log.event.account_switched", account, http_status)),
+            Event::Validated { status, .. } => (Level::Debug, "validated", tr!("log.event.validated", status.label())),
+            Event::Finished { status, error, .. } => {
+                let level = if status.is_success() || *status == sed_dl::DownloadStatus::Skipped { Level::Info } else { Level::Error };
+                let message = match error {
+                    Some(error) => tr!("log.event.finished_error", status.label(), error),
+                    None => tr!("log.event.finished", status.label()),
+                };
+                (level, "finished", message)
+            }
+            _ => return,
+        };
+        if level > self.level { return; }
+
+        let mut inner = self.inner.lock().unwrap();
+        let context = inner.items.entry(content_id.to_string()).or_default();
+        let mut status = None;
+        match event {
+            Event::DetailsResolved { filename, .. } => context.filename = Some(filename.clone()),
+            Event::DownloadStarted { filename, attempt, mirror, .. } => {
+                context.filename = Some(filename.clone());
+                context.attempt = Some(*attempt);
+                context.mirror = Some(mirror.clone());
+            }
+            Event::Retry { attempt, .. } => context.attempt = Some(*attempt),
+            Event::Validated { status: s, .. } => status = Some(s.code()),
+            Event::Finished { status: s, .. } => status = Some(s.code()),
+            _ => {}
+        }
+        let fields = Fields {
+            event: Some(name),
+            content_id: Some(content_id.to_string()),
+            filename: context.filename.clone(),
+            attempt: context.attempt,
+            mirror: context.mirror.clone(),
+            status,
+        };
+        if matches!(event, Event::Finished { .. }) { inner.items.remove(content_id); }
+        self.write(&mut inner, level, "sed_dl::events", &message, fields);
+    }
+}
+
+/// 按大小或日期轮转的日志文件
+struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    /// 当前文件开始记录的日期, 按日轮转时使用
+    date: NaiveDate,
+    rotation: Rotation,
+    keep: usize,
+}
+
+impl RotatingFile {
+    fn open(path: PathBuf, rotation: Rotation, keep: usize) -> io::Result<RotatingFile> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        let today = Local::now().date_naive();
+        // 上次运行留下的文件按其修改日期决定是否先轮转
+        let date = fs::metadata(&path).and_then(|m| m.modified())
+            .map(|t| chrono::DateTime::<Local>::from(t).date_naive())
+            .unwrap_or(today);
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+        let mut rotating = RotatingFile { path, file, size, date, rotation, keep };
+        if rotation == Rotation::Daily && date != today {
+            rotating.rotate()?;
+        }
+        Ok(rotating)
+    }
+
+    fn write_line(&mut self, line: &str) -> io::Result<()> {
+        let incoming = line.len() as u64 + 1;
+        let due = match self.rotation {
+            Rotation::Never => false,
+            Rotation::Daily => Local::now().date_naive() != self.date,
+            Rotation::Size(max) => self.size > 0 && self.size + incoming > max,
+        };
+        if due { self.rotate()?; }
+        writeln!(self.file, "{}", line)?;
+        self.size += incoming;
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        match self.rotation {
+            Rotation::Daily => {
+                let mut target = suffixed(&self.path, &self.date.to_string());
+                let mut n = 1;
+                while target.exists() {
+                    target = suffixed(&self.path, &format!("{}.{}", self.date, n));
+                    n += 1;
+                }
+                fs::rename(&self.path, target)?;
+                self.prune_dated()?;
+            }
+            _ => {
+                let _ = fs::remove_file(suffixed(&self.path, &self.keep.to_string()));
+                for n in (1..self.keep).rev() {
+                    let from = suffixed(&self.path, &n.to_string());
+                    if from.exists() { fs::rename(from, suffixed(&self.path, &(n + 1).to_string()))?; }
+                }
+                fs::rename(&self.path, suffixed(&self.path, "1"))?;
+            }
+        }
+        self.file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        self.size = 0;
+        self.date = Local::now().date_naive();
+        Ok(())
+    }
+
+    /// 只保留最近 `keep` 个按日轮转的文件
+    fn prune_dated(&self) -> io::Result<()> {
+        let (Some(dir), Some(name)) = (self.path.parent(), self.path.file_name().and_then(|n| n.to_str())) else { return Ok(()) };
+        let dir = if dir.as_os_str().is_empty() { Path::new(".") } else { dir };
+        let prefix = format!("{}.", name);
+        let mut rotated: Vec<String> = fs::read_dir(dir)?
+            .filter_map(|e| e.ok()?.file_name().into_string().ok())
+            .filter(|n| n.strip_prefix(&prefix).and_then(|rest| rest.get(..10)).is_some_and(|d| NaiveDate::from_str(d).is_ok()))
+            .collect();
+        rotated.sort();
+        let excess = rotated.len().saturating_sub(self.keep);
+        for old in &rotated[..excess] {
+            fs::remove_file(dir.join(old))?;
+        }
+        Ok(())
+    }
+}
+
+fn suffixed(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".");
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// 同时输出到控制台和日志文件, 两者的级别分别控制
+struct TeeLogger {
+    console: env_logger::Logger,
+    file: Option<Arc<FileLog>>,
+}
+
+impl Log for TeeLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.console.enabled(metadata) || self.file.as_ref().is_some_and(|f| metadata.level() <= f.level)
+    }
+
+    fn log(&self, record: &Record) {
+        if self.console.matches(record) {
+            self.console.log(record);
+        }
+        if let Some(file) = &self.file
+            && record.level() <= file.level
+        {
+            file.record(record);
+        }
+    }
+
+    fn flush(&self) {
+        self.console.flush();
+        if let Some(file) = &self.file {
+            let _ = file.inner.lock().unwrap().file.file.flush();
+        }
+    }
+}
+
+/// 安装全局日志: 控制台沿用 env_logger 的过滤规则, 日志文件使用自己的级别
+pub fn init(console: env_logger::Logger, file: Option<Arc<FileLog>>) {
+    let max_level = console.filter().max(file.as_ref().map_or(LevelFilter::Off, |f| f.level));
+    log::set_boxed_logger(Box::new(TeeLogger { console, file })).expect("日志只初始化一次");
+    log::set_max_level(max_level);
+}
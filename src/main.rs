@@ -1,23 +1,40 @@
 // src/main.rs
 
+mod browse;
+mod config;
+mod control;
+mod daemon;
+mod dashboard;
+mod journal;
+mod logfile;
+mod manifest;
+mod output;
+
 use chrono::Utc;
-use clap::Parser;
+use clap::builder::{PossibleValuesParser, TypedValueParser};
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
 use colored::*;
-use futures::stream::StreamExt;
-use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
-use log::{error, info, warn};
-use once_cell::sync::Lazy;
-use regex::Regex;
-use reqwest::Client;
-use serde::Deserialize;
-use std::collections::{HashMap, HashSet};
-use std::io::{self, BufRead, Write};
+use indicatif::{HumanBytes, HumanDuration, MultiProgress, ProgressBar, ProgressStyle};
+use log::{debug, error, info, warn};
+use once_cell::sync::{Lazy, OnceCell};
+use sed_dl::endpoints::Endpoints;
+use sed_dl::har::HarRecorder;
+use sed_dl::http_cache::HttpCache;
+use sed_dl::preflight::TokenState;
+use sed_dl::retry::RetryConfig;
+use sed_dl::token_pool::{ParkReason, TokenPool};
+use sed_dl::token_store::{self, TokenStore};
+use sed_dl::i18n::{self, Lang};
+use sed_dl::{browser_import, credential, get_content_id, mirror_of, tr, verify_file, AppError, CancellationToken, DownloadStatus, Event, EventSink, SmartEduClient, SmartEduClientBuilder};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::collections::VecDeque;
+use std::io::{self, BufRead, IsTerminal, Write};
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
-use std::time::Duration;
-use tokio::fs::{self, File};
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::sync::Semaphore;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::fs;
+use tokio::task::JoinSet;
 
 // --- 1. 全局常量和静态变量 ---
 static SYMBOL_PROMPT: &str = ">";
@@ -28,12 +45,18 @@ static SYMBOL_WARNING: &str = "[!]";
 static SYMBOL_END: &str = "[DONE]";
 static SYMBOL_STATS: &str = "---";
 static SYMBOL_DIVIDER: &str = "═";
-static TOKEN_FILE: &str = ".access_token";
-static MAX_RETRIES: u32 = 3;
-static RETRY_BASE_DELAY_MS: u64 = 500;
-
-static FILENAME_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r#"[<>:"/\\|?*]"#).unwrap());
-static UUID_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}$").unwrap());
+/// 旧版本保存在当前目录下的 Token 文件, 仅用于兼容读取
+static LEGACY_TOKEN_FILE: &str = ".access_token";
+/// 非交互模式下因 Token 失效而中止时的退出码
+static EXIT_TOKEN_INVALID: i32 = 3;
+/// `token check` 发现 Token 有效但无权访问探测资源时的退出码
+static EXIT_TOKEN_FORBIDDEN: i32 = 4;
+/// 被 Ctrl-C 中断时的退出码 (128 + SIGINT)
+static EXIT_INTERRUPTED: i32 = 130;
+/// 批量结束后重试临时失败项之前的默认等待时间(秒)
+static DEFAULT_RETRY_PASS_DELAY_SECS: u64 = 30;
+/// 账号因 429 被暂停后的默认冷却时间(秒), 403 的暂停时间不超过它
+static DEFAULT_ACCOUNT_COOLDOWN_SECS: u64 = 300;
 
 static PROGRESS_STYLE: Lazy<ProgressStyle> = Lazy::new(|| {
     ProgressStyle::default_bar()
@@ -42,6 +65,20 @@ static PROGRESS_STYLE: Lazy<ProgressStyle> = Lazy::new(|| {
         .progress_chars("━╸ ")
 });
 
+/// 大小未知时只显示已下载的字节数
+static SPINNER_STYLE: Lazy<ProgressStyle> = Lazy::new(|| {
+    ProgressStyle::default_bar()
+        .template("{msg:.cyan}\n{spinner:.green} [{elapsed_precise}] {bytes} ({bytes_per_sec})")
+        .unwrap()
+});
+
+static TOTAL_STYLE: Lazy<ProgressStyle> = Lazy::new(|| {
+    ProgressStyle::default_bar()
+        .template("{prefix:.bold} [{elapsed_precise}] [{bar:40.green/blue}] {bytes}/{total_bytes} {msg}")
+        .unwrap()
+        .progress_chars("━╸ ")
+});
+
 static FINISHED_STYLE: Lazy<ProgressStyle> = Lazy::new(|| {
     ProgressStyle::default_bar()
         .template("{msg}")
@@ -49,257 +86,581 @@ static FINISHED_STYLE: Lazy<ProgressStyle> = Lazy::new(|| {
 });
 
 
-// --- 2. 错误处理 ---
-#[derive(thiserror::Error, Debug)]
-enum AppError {
-    #[error("网络请求错误: {0}")]
-    Reqwest(#[from] reqwest::Error),
-    #[error("文件IO错误: {0}")]
-    Io(#[from] std::io::Error),
-    #[error("JSON解析错误: {0}")]
-    Json(#[from] serde_json::Error),
-    #[error("获取教材详情失败: {0}")]
-    DetailFetch(String),
-    #[error("无效的输入: {0}")]
-    InvalidInput(String),
-    #[error("目录创建失败: {0}")]
-    DirCreation(String),
-}
-
-// --- 3. 数据结构定义 ---
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-enum DownloadStatus {
-    Success,
-    SuccessNoValidation,
-    Skipped,
-    TokenError,
-    Md5ValidationFailed,
-    SizeValidationFailed,
-    NetworkError,
-    FailGetDetails,
-    UnexpectedError,
+// --- 2. 数据结构定义 ---
+/// 失败时的上下文信息
+#[derive(Debug, Clone, Default)]
+struct FailureInfo {
+    http_code: Option<u16>,
+    mirror: Option<String>,
+    message: Option<String>,
 }
 
+impl From<&AppError> for FailureInfo {
+    fn from(e: &AppError) -> Self {
+        FailureInfo { http_code: e.http_code(), mirror: e.mirror().map(str::to_string), message: Some(e.to_string()) }
+    }
+}
+
+/// 单个下载任务的最终结果
 #[derive(Debug)]
-struct TextbookInfo {
-    download_url: String,
+struct TaskResult {
+    content_id: String,
+    original: String,
     filename: String,
-    expected_md5: Option<String>,
-    expected_size: Option<u64>,
+    status: DownloadStatus,
+    failure: Option<FailureInfo>,
+    /// 实际发起下载的账号, 未进入下载阶段时为空
+    account: Option<String>,
+    /// 输出文件的完整路径, 未获取到详情时为空
+    path: Option<PathBuf>,
+}
+
+impl TaskResult {
+    fn new(item: DownloadItem, filename: String, status: DownloadStatus) -> Self {
+        let (content_id, original) = item;
+        TaskResult { content_id, original, filename, status, failure: None, account: None, path: None }
+    }
+
+    fn failed(item: DownloadItem, filename: String, error: &AppError) -> Self {
+        let mut result = TaskResult::new(item, filename, error.status());
+        result.failure = Some(error.into());
+        result
+    }
+
+    fn item(&self) -> DownloadItem {
+        (self.content_id.clone(), self.original.clone())
+    }
 }
 
-#[derive(Deserialize, Debug)]
-struct TextbookDetailsResponse {
-    ti_items: Vec<TechInfoItem>,
-    title: String,
+/// 待下载项: (Content ID, 原始输入)
+type DownloadItem = (String, String);
+
+/// Token 熔断器: 第一次确认认证失败后, 停止派发新任务
+#[derive(Debug, Default)]
+struct AuthBreaker {
+    tripped: AtomicBool,
+}
+
+impl AuthBreaker {
+    /// 触发熔断, 仅首次触发时返回 true
+    fn trip(&self) -> bool {
+        !self.tripped.swap(true, Ordering::SeqCst)
+    }
+
+    fn is_tripped(&self) -> bool {
+        self.tripped.load(Ordering::SeqCst)
+    }
 }
 
-#[derive(Deserialize, Debug)]
-struct TechInfoItem {
-    ti_file_flag: String,
-    ti_format: String,
-    ti_storages: Vec<String>,
-    ti_md5: Option<String>,
-    ti_size: Option<u64>,
+/// 一轮下载中所有任务共享的运行环境
+struct TaskContext {
+    client: SmartEduClient,
+    args: Arc<Cli>,
+    dest_folder: PathBuf,
+    /// 队列中有多个项目: 此时 `-o` 只指定目录, 不用作文件名
+    is_batch: bool,
+    breaker: AuthBreaker,
+    /// 第一次 Ctrl-C 后触发: 停止派发新任务
+    shutdown: CancellationToken,
+    journal: Option<Arc<journal::Journal>>,
+    control: Option<Arc<control::Controller>>,
 }
 
-// --- 4. 命令行参数定义 ---
+// --- 3. 命令行参数定义 ---
+// 命令和参数的说明文字按界面语言取自消息目录 (`cli.*`), 见 `localize_command`
 #[derive(Parser, Debug, Clone)]
 #[command(
     name = "sed-dl",
     author = "ds",
     version = "0.1.0",
-    about = "国家中小学智慧教育平台教材下载命令行工具 (Rust版)",
     long_about = None,
-    after_help = "示例:\n  # 下载单个URL\n  sed-dl -u \"<教材URL>\" -t \"<你的TOKEN>\"\n\n  # 从文件批量下载并指定输出目录\n  sed-dl -i urls.txt -o ./教材下载 -t \"<你的TOKEN>\""
 )]
 struct Cli {
-    #[arg(short, long, help = "一个或多个教材页面URL", action = clap::ArgAction::Append)]
+    #[arg(short, long, action = clap::ArgAction::Append)]
     url: Vec<String>,
-    #[arg(short, long, name = "content_id", help = "一个或多个教材Content ID", action = clap::ArgAction::Append)]
+    #[arg(short, long, name = "content_id", action = clap::ArgAction::Append)]
     content_id: Vec<String>,
-    #[arg(short, long, help = "包含URL/ID的文本文件路径")]
+    #[arg(short, long)]
     input_file: Option<PathBuf>,
-    #[arg(short, long, help = "访问令牌(Access Token)")]
+    #[arg(short, long, global = true)]
     token: Option<String>,
-    #[arg(short, long, help = "输出文件路径或目录")]
+    #[arg(long, global = true, value_name = "PATH")]
+    token_file: Option<PathBuf>,
+    #[arg(long, global = true, value_name = "CMD")]
+    token_helper: Option<String>,
+    #[arg(long, global = true, value_name = "NAME")]
+    token_name: Option<String>,
+    #[arg(long, global = true)]
+    no_input: bool,
+    #[arg(short, long)]
     output: Option<String>,
-    #[arg(short, long, help = "启用详细调试日志")]
+    #[arg(short, long, global = true)]
     debug: bool,
-    #[arg(long, help = "最大并发下载数", default_value_t = 5)]
+    #[arg(long, default_value_t = 5)]
     max_concurrent_downloads: usize,
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+    #[arg(long, value_name = "N")]
+    details_attempts: Option<u32>,
+    #[arg(long, value_name = "N")]
+    download_attempts: Option<u32>,
+    #[arg(long, value_name = "MS")]
+    retry_base_delay: Option<u64>,
+    #[arg(long, value_name = "MS")]
+    retry_max_delay: Option<u64>,
+    #[arg(long, value_name = "SECS")]
+    max_retry_after: Option<u64>,
+    #[arg(long)]
+    no_retry_jitter: bool,
+    #[arg(long)]
+    skip_token_check: bool,
+    #[arg(long, value_name = "PATH")]
+    manifest: Option<PathBuf>,
+    #[arg(long, value_name = "PATH")]
+    failed_out: Option<PathBuf>,
+    #[arg(long, value_name = "REPORT")]
+    retry_failed: Option<PathBuf>,
+    #[arg(long, value_name = "SECS")]
+    retry_pass_delay: Option<u64>,
+    #[arg(long)]
+    no_retry_pass: bool,
+    #[arg(long, global = true, value_name = "PATH")]
+    control_socket: Option<PathBuf>,
+    #[arg(long, value_enum, value_name = "MODE", default_value_t = output::ProgressMode::Auto)]
+    progress: output::ProgressMode,
+    #[arg(long, global = true, value_name = "PATH")]
+    log_file: Option<PathBuf>,
+    #[arg(long, global = true, value_enum, value_name = "FORMAT")]
+    log_format: Option<logfile::LogFormat>,
+    #[arg(long, global = true, value_name = "LEVEL")]
+    log_level: Option<log::LevelFilter>,
+    #[arg(long, global = true, value_name = "WHEN")]
+    log_rotate: Option<logfile::Rotation>,
+    #[arg(long, global = true, value_name = "N")]
+    log_keep: Option<usize>,
+    #[arg(long, global = true, value_name = "URL", action = clap::ArgAction::Append)]
+    api_host: Vec<String>,
+    #[arg(long, global = true, value_name = "DIR")]
+    cache_dir: Option<PathBuf>,
+    #[arg(long, global = true, value_name = "SECS")]
+    cache_ttl: Option<u64>,
+    #[arg(long, global = true, conflicts_with = "offline")]
+    no_cache: bool,
+    #[arg(long, global = true)]
+    offline: bool,
+    #[arg(long, global = true, value_name = "PATH")]
+    har: Option<PathBuf>,
+    #[arg(long, global = true, value_enum, value_name = "LANG")]
+    lang: Option<Lang>,
+    #[arg(long, global = true, value_enum, value_name = "WHEN", default_value_t = output::ColorMode::Auto)]
+    color: output::ColorMode,
+    #[arg(long)]
+    compact: bool,
+    #[arg(long)]
+    dashboard: bool,
+    #[arg(long)]
+    no_journal: bool,
+    #[arg(long, value_name = "NAMES", value_delimiter = ',')]
+    accounts: Vec<String>,
+    #[arg(long, value_enum, value_name = "STRATEGY")]
+    account_strategy: Option<sed_dl::token_pool::PoolStrategy>,
+    #[arg(long, value_name = "SECS")]
+    account_cooldown: Option<u64>,
+    #[command(subcommand)]
+    command: Option<Command>,
 }
 
-// --- 5. 核心及辅助功能函数 ---
+#[derive(Subcommand, Debug, Clone)]
+enum Command {
+    Resume {
+        #[arg(value_name = "DIR", default_value = ".")]
+        dir: PathBuf,
+    },
+    Browse,
+    Ctl {
+        #[command(subcommand)]
+        action: CtlAction,
+    },
+    Serve {
+        #[arg(long, value_name = "ADDR", default_value = "127.0.0.1:8787")]
+        listen: std::net::SocketAddr,
+        #[arg(long, value_name = "PATH")]
+        jobs_file: Option<PathBuf>,
+    },
+    Token {
+        #[command(subcommand)]
+        action: TokenAction,
+    },
+}
 
-fn get_content_id(input: &str) -> Option<String> {
-    if UUID_REGEX.is_match(input) {
-        Some(input.to_string())
-    } else if let Ok(url) = reqwest::Url::parse(input) {
-        url.query_pairs().find_map(|(key, value)| {
-            (key == "contentId" && UUID_REGEX.is_match(&value)).then(|| value.into_owned())
-        })
-    } else {
-        None
+#[derive(Subcommand, Debug, Clone)]
+enum TokenAction {
+    Check {
+        #[arg(long, value_name = "URL|ID")]
+        probe: Option<String>,
+    },
+    Set {
+        #[arg(default_value = token_store::DEFAULT_TOKEN_NAME)]
+        name: String,
+        #[arg(long)]
+        encrypt: bool,
+        #[arg(long)]
+        default: bool,
+    },
+    Import {
+        #[arg(long, value_enum)]
+        browser: browser_import::Browser,
+        #[arg(long, value_name = "PATH")]
+        profile: Option<PathBuf>,
+        #[arg(default_value = token_store::DEFAULT_TOKEN_NAME)]
+        name: String,
+        #[arg(long)]
+        encrypt: bool,
+        #[arg(long)]
+        default: bool,
+    },
+    List,
+    Remove {
+        name: String,
+    },
+    Default {
+        name: String,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+enum CtlAction {
+    Pause {
+        #[arg(value_name = "URL|ID")]
+        item: Option<String>,
+    },
+    Resume {
+        #[arg(value_name = "URL|ID")]
+        item: Option<String>,
+    },
+    Concurrency {
+        value: usize,
+    },
+    Bump {
+        #[arg(value_name = "URL|ID")]
+        item: String,
+    },
+    Cancel {
+        #[arg(value_name = "URL|ID")]
+        item: String,
+    },
+    Retry {
+        #[arg(value_name = "URL|ID")]
+        item: String,
+    },
+    State {
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+impl Cli {
+    /// 解析命令行。`--help` 的说明文字随界面语言变化, 因此先从参数和环境变量确定语言。
+    fn parse_localized() -> Cli {
+        let args: Vec<std::ffi::OsString> = std::env::args_os().collect();
+        i18n::set_lang(lang_from_args(&args).unwrap_or_else(Lang::detect));
+        let command = localize_command(Cli::command(), "cli")
+            .mut_arg("progress", localize_values::<output::ProgressMode>)
+            .mut_arg("color", localize_values::<output::ColorMode>)
+            .mut_arg("log_format", localize_values::<logfile::LogFormat>)
+            .mut_arg("lang", localize_values::<Lang>)
+            .mut_arg("account_strategy", localize_values::<sed_dl::token_pool::PoolStrategy>)
+            .mut_subcommand("token", |token| token.mut_subcommand("import", |import| import.mut_arg("browser", localize_values::<browser_import::Browser>)));
+        let matches = command.get_matches_from(args);
+        Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit())
     }
 }
 
-fn sanitize_filename(filename: &str) -> String {
-    FILENAME_REGEX.replace_all(filename, "_").to_string()
+/// 在正式解析前找出 `--lang <LANG>` / `--lang=<LANG>`
+fn lang_from_args(args: &[std::ffi::OsString]) -> Option<Lang> {
+    let mut args = args.iter().filter_map(|arg| arg.to_str());
+    while let Some(arg) = args.next() {
+        let value = match arg.strip_prefix("--lang") {
+            Some("") => args.next(),
+            Some(rest) => rest.strip_prefix('='),
+            None => continue,
+        };
+        return value.and_then(|v| Lang::from_str(v, true).ok());
+    }
+    None
 }
 
-async fn calculate_file_md5(path: &Path) -> Result<String, io::Error> {
-    let mut file = File::open(path).await?;
-    let mut context = md5::Context::new();
-    let mut buffer = [0; 8192];
-    loop {
-        let n = file.read(&mut buffer).await?;
-        if n == 0 { break; }
-        context.consume(&buffer[..n]);
-    }
-    Ok(format!("{:x}", context.compute()))
-}
-
-async fn get_textbook_details(client: &Client, content_id: &str, access_token: &str) -> Result<TextbookInfo, AppError> {
-    let url = format!("https://s-file-2.ykt.cbern.com.cn/zxx/ndrv2/resources/tch_material/details/{}.json", content_id);
-    let data = client.get(&url).send().await?.error_for_status()?.json::<TextbookDetailsResponse>().await?;
-    let source_item = data.ti_items.iter()
-        .find(|item| item.ti_file_flag == "source" && item.ti_format == "pdf")
-        .ok_or_else(|| AppError::DetailFetch(format!("在内容ID '{}' 中未找到源PDF文件信息", content_id)))?;
-    let pdf_url_base = source_item.ti_storages.first()
-        .ok_or_else(|| AppError::DetailFetch(format!("在内容ID '{}' 中未找到PDF下载地址", content_id)))?;
-    let is_pdf_pdf = pdf_url_base.to_lowercase().ends_with("pdf.pdf");
-    let mut final_filename = if is_pdf_pdf {
-        data.title.clone()
-    } else {
-        Path::new(pdf_url_base)
-            .file_name()
-            .map(|n| n.to_string_lossy().into_owned())
-            .unwrap_or_else(|| content_id.to_string())
-    };
-    if !final_filename.to_lowercase().ends_with(".pdf") {
-        final_filename.push_str(".pdf");
-    }
-    Ok(TextbookInfo {
-        download_url: format!("{}?accessToken={}", pdf_url_base, access_token),
-        filename: sanitize_filename(&final_filename),
-        expected_md5: if is_pdf_pdf { None } else { source_item.ti_md5.clone() },
-        expected_size: source_item.ti_size,
-    })
-}
-
-async fn validate_local_file(path: &Path, info: &TextbookInfo) -> Result<DownloadStatus, AppError> {
-    if !path.exists() { return Ok(DownloadStatus::SizeValidationFailed); }
-    if let Some(expected_md5) = &info.expected_md5 {
-        if let Ok(actual_md5) = calculate_file_md5(path).await {
-            if actual_md5 == *expected_md5 { return Ok(DownloadStatus::Success); }
-        }
-    }
-    if let Some(expected_size) = info.expected_size {
-        if let Ok(metadata) = fs::metadata(path).await {
-            if metadata.len() == expected_size { return Ok(DownloadStatus::Success); }
-        }
-    }
-    if info.expected_md5.is_none() && info.expected_size.is_none() { return Ok(DownloadStatus::SuccessNoValidation); }
-    if info.expected_md5.is_some() { Ok(DownloadStatus::Md5ValidationFailed) } 
-    else { Ok(DownloadStatus::SizeValidationFailed) }
-}
-
-async fn download_file(client: &Client, info: &TextbookInfo, dest_path: &Path, mp: Arc<MultiProgress>) -> Result<DownloadStatus, AppError> {
-    let pb = mp.add(ProgressBar::new(info.expected_size.unwrap_or(0)));
-    pb.set_style(PROGRESS_STYLE.clone());
-    pb.set_message(info.filename.clone());
-
-    // 将所有可能失败的逻辑放入一个 async 块中
-    let result: Result<DownloadStatus, AppError> = async {
-        let mut last_error: Option<AppError> = None;
-        for attempt in 0..MAX_RETRIES {
-            if attempt > 0 {
-                let wait_time = Duration::from_millis(RETRY_BASE_DELAY_MS * 2u64.pow(attempt - 1));
-                pb.println(format!("{} '{}' 第{}次下载失败, {:.1?}后重试...", SYMBOL_WARNING, info.filename, attempt, wait_time));
-                tokio::time::sleep(wait_time).await;
-            }
-            pb.set_position(0);
-
-            let response_result = client.get(&info.download_url).send().await;
-
-            match response_result {
-                Ok(response) => match response.error_for_status() {
-                    Ok(resp) => {
-                        let mut file = File::create(&dest_path).await?;
-                        let mut stream = resp.bytes_stream();
-                        while let Some(chunk_result) = stream.next().await {
-                            let chunk = chunk_result?;
-                            file.write_all(&chunk).await?;
-                            pb.inc(chunk.len() as u64);
-                        }
-                        file.flush().await?;
-                        
-                        // 下载成功，直接返回校验结果
-                        return validate_local_file(dest_path, info).await;
-                    }
-                    Err(e) => {
-                        // HTTP 状态码错误 (e.g., 404, 500)
-                        if e.status() == Some(reqwest::StatusCode::UNAUTHORIZED) {
-                            // 这是个不可重试的致命错误，直接返回
-                            return Ok(DownloadStatus::TokenError);
-                        }
-                        last_error = Some(e.into());
+/// 用消息目录替换枚举参数各个取值的说明 (`cli.value.<参数>.<取值>`), 取值本身不变
+fn localize_values<E: ValueEnum + Clone + Send + Sync + 'static>(arg: clap::Arg) -> clap::Arg {
+    let prefix = format!("cli.value.{}", arg.get_id());
+    let values = E::value_variants().iter().filter_map(ValueEnum::to_possible_value).map(|value| {
+        match i18n::message(&format!("{}.{}", prefix, value.get_name())) {
+            Some(help) => value.help(help),
+            None => value,
+        }
+    });
+    arg.value_parser(PossibleValuesParser::new(values).map(|name| E::from_str(&name, true).unwrap()))
+}
+
+/// 用消息目录替换命令的说明: `<prefix>.about`、`<prefix>.after_help`、参数 `<prefix>.arg.<id>`,
+/// 子命令递归使用 `<prefix>.<子命令名>`
+fn localize_command(mut command: clap::Command, prefix: &str) -> clap::Command {
+    if let Some(about) = i18n::message(&format!("{}.about", prefix)) { command = command.about(about); }
+    if let Some(after_help) = i18n::message(&format!("{}.after_help", prefix)) { command = command.after_help(after_help); }
+    command
+        .mut_args(|arg| match i18n::message(&format!("{}.arg.{}", prefix, arg.get_id())) {
+            Some(help) => arg.help(help),
+            None => arg,
+        })
+        .mut_subcommands(|sub| {
+            let prefix = format!("{}.{}", prefix, sub.get_name());
+            localize_command(sub, &prefix)
+        })
+}
+
+// --- 4. 核心及辅助功能函数 ---
+
+/// 订阅下载事件, 为每个下载项显示一个进度条, 批量下载时另有一个总进度条
+struct ProgressReporter {
+    mp: MultiProgress,
+    state: Mutex<ReporterState>,
+    /// 多账号时在结果中标明由哪个账号下载
+    show_accounts: bool,
+    /// 不显示每一项的进度条, 只输出失败的项
+    compact: bool,
+}
+
+#[derive(Default)]
+struct ReporterState {
+    /// Content ID -> (进度条, 文件名)
+    bars: HashMap<String, (ProgressBar, String)>,
+    batch: BatchProgress,
+}
+
+/// 批量下载的总进度。总量为已获取详情的项目的预期大小之和 (不含跳过和失败的项目),
+/// 尚未获取详情的项目按已知项目的平均大小估算。
+#[derive(Default)]
+struct BatchProgress {
+    bar: Option<ProgressBar>,
+    queued: usize,
+    succeeded: usize,
+    failed: usize,
+    /// 计入总量的项目的预期大小
+    sizes: HashMap<String, u64>,
+    /// 进行中的下载本次尝试已传输的字节数
+    current: HashMap<String, u64>,
+    /// 已结束的项目是否成功, 重新下载时据此修正计数
+    outcomes: HashMap<String, bool>,
+    total_bytes: u64,
+    /// 已下载完成的项目的字节数
+    finished_bytes: u64,
+    /// 累计传输的字节数 (含失败的尝试), 用于计算平均速度
+    transferred: u64,
+    started: Option<Instant>,
+    last_refresh: Option<Instant>,
+}
+
+impl BatchProgress {
+    fn on_event(&mut self, event: &Event) {
+        let id = event.content_id();
+        match event {
+            Event::Queued { .. } => self.queued += 1,
+            Event::DetailsResolved { total_bytes, .. } => {
+                // 失败后重新下载的项目
+                match self.outcomes.remove(id) {
+                    Some(true) => self.succeeded -= 1,
+                    Some(false) => self.failed -= 1,
+                    None => {}
+                }
+                if let Some(size) = total_bytes { self.set_size(id, *size); }
+            }
+            Event::DownloadStarted { total_bytes, .. } => {
+                self.started.get_or_insert_with(Instant::now);
+                self.current.insert(id.to_string(), 0);
+                if let Some(size) = total_bytes { self.set_size(id, *size); }
+            }
+            Event::Progress { bytes, .. } => {
+                if let Some(current) = self.current.get_mut(id) {
+                    self.transferred += bytes.saturating_sub(*current);
+                    *current = *bytes;
+                }
+            }
+            Event::Finished { status, .. } => {
+                let downloaded = self.current.remove(id);
+                match (status.is_success(), downloaded) {
+                    (true, Some(bytes)) => {
+                        // 大小未知的项目按实际下载的字节数计入
+                        if !self.sizes.contains_key(id) { self.set_size(id, bytes); }
+                        self.finished_bytes += self.sizes[id];
                     }
-                },
-                Err(e) => {
-                    // 网络层错误 (e.g., DNS, TCP)
-                    last_error = Some(e.into());
+                    // 已存在而跳过, 或失败的项目不计入总量
+                    _ => if let Some(size) = self.sizes.remove(id) { self.total_bytes -= size; },
                 }
+                if status.is_success() { self.succeeded += 1 } else { self.failed += 1 }
+                self.outcomes.insert(id.to_string(), status.is_success());
             }
+            _ => {}
         }
-        // 如果循环结束仍然失败，返回最后一次的错误
-        Err(last_error.unwrap_or(AppError::DetailFetch("未知下载错误".into())))
-    }.await;
+    }
 
-    // 在外部统一处理结果，并确保进度条被终结
-    pb.set_style(FINISHED_STYLE.clone());
-    match result {
-        Ok(DownloadStatus::Success) => {
-            pb.finish_with_message(format!("{} '{}' {}", SYMBOL_SUCCESS.green(), info.filename, "校验通过".green()));
-            Ok(DownloadStatus::Success)
-        }
-        Ok(DownloadStatus::SuccessNoValidation) => {
-            pb.finish_with_message(format!("{} '{}' {}", SYMBOL_WARNING.yellow(), info.filename, "无校验信息".yellow()));
-            Ok(DownloadStatus::SuccessNoValidation)
-        }
-        Ok(DownloadStatus::TokenError) => {
-            pb.finish_with_message(format!("{} '{}' {}", SYMBOL_ERROR.red(), info.filename, "Token错误或过期".red()));
-            Ok(DownloadStatus::TokenError)
-        }
-        Ok(status) => { // 其他校验失败的状态
-            pb.finish_with_message(format!("{} '{}' {}", SYMBOL_ERROR.red(), info.filename, "校验失败".red()));
-            Ok(status)
+    fn set_size(&mut self, content_id: &str, size: u64) {
+        if let Some(old) = self.sizes.insert(content_id.to_string(), size) { self.total_bytes -= old; }
+        self.total_bytes += size;
+    }
+
+    /// 更新总进度条; 下载过程中的进度事件很频繁, 非强制时最多每 200 毫秒更新一次
+    fn refresh(&mut self, force: bool) {
+        let Some(bar) = &self.bar else { return };
+        if !force && self.last_refresh.is_some_and(|t| t.elapsed() < Duration::from_millis(200)) { return; }
+        self.last_refresh = Some(Instant::now());
+
+        let remaining = self.queued.saturating_sub(self.succeeded + self.failed);
+        let pending_sized = self.sizes.keys().filter(|id| !self.outcomes.contains_key(*id)).count();
+        let average = if self.sizes.is_empty() { 0 } else { self.total_bytes / self.sizes.len() as u64 };
+        let estimated = self.total_bytes + average * remaining.saturating_sub(pending_sized) as u64;
+        let position = self.finished_bytes + self.current.values().sum::<u64>();
+        bar.set_length(estimated.max(position));
+        bar.set_position(position);
+
+        let mut message = tr!("progress.totals", self.succeeded, self.failed, remaining);
+        if let Some(started) = self.started {
+            let speed = self.transferred as f64 / started.elapsed().as_secs_f64().max(1.0);
+            message.push_str(&tr!("progress.speed", HumanBytes(speed as u64)));
+            if remaining > 0 && speed >= 1.0 {
+                let eta = Duration::from_secs_f64(estimated.saturating_sub(position) as f64 / speed);
+                message.push_str(&tr!("progress.eta", HumanDuration(eta)));
+            }
         }
-        Err(e) => { // 所有在 async 块中发生的 I/O 错误或重试耗尽后的网络错误
-            pb.finish_with_message(format!("{} '{}' {}: {}", SYMBOL_ERROR.red(), info.filename, "下载失败".red(), e));
-            Ok(DownloadStatus::NetworkError) // 将所有最终错误归类为网络错误
+        bar.set_message(message);
+    }
+}
+
+impl ProgressReporter {
+    fn new(mp: MultiProgress, show_accounts: bool, compact: bool) -> Self {
+        ProgressReporter { mp, state: Mutex::new(ReporterState::default()), show_accounts, compact }
+    }
+}
+
+impl EventSink for ProgressReporter {
+    fn on_event(&self, event: &Event) {
+        let mut state = self.state.lock().unwrap();
+        let ReporterState { bars, batch } = &mut *state;
+        batch.on_event(event);
+        match event {
+            // 多于一项时显示总进度条, 位于各项进度条下方
+            Event::Queued { .. } if batch.queued == 2 => {
+                let bar = self.mp.add(ProgressBar::new(0));
+                bar.set_style(TOTAL_STYLE.clone());
+                bar.set_prefix(tr!("progress.total"));
+                batch.bar = Some(bar);
+            }
+            Event::DownloadStarted { content_id, filename, total_bytes, .. } => {
+                let (pb, _) = bars.entry(content_id.clone()).or_insert_with(|| {
+                    let pb = match (&batch.bar, self.compact) {
+                        (_, true) => ProgressBar::hidden(),
+                        (Some(total), false) => self.mp.insert_before(total, ProgressBar::no_length()),
+                        (None, false) => self.mp.add(ProgressBar::no_length()),
+                    };
+                    pb.set_message(filename.clone());
+                    (pb, filename.clone())
+                });
+                match total_bytes {
+                    Some(total) => {
+                        pb.set_length(*total);
+                        pb.set_style(PROGRESS_STYLE.clone());
+                    }
+                    None => pb.set_style(SPINNER_STYLE.clone()),
+                }
+                pb.set_position(0);
+            }
+            Event::Progress { content_id, bytes, total_bytes } => {
+                if let Some((pb, _)) = bars.get(content_id) {
+                    // 详情中没有大小时, 从响应的 Content-Length 得知
+                    if let Some(total) = total_bytes && pb.length().is_none() {
+                        pb.set_length(*total);
+                        pb.set_style(PROGRESS_STYLE.clone());
+                    }
+                    pb.set_position(*bytes);
+                }
+            }
+            Event::Retry { content_id, attempt, delay_ms, reason } => {
+                if let Some((_, filename)) = bars.get(content_id).filter(|_| !self.compact) {
+                    let wait_time = Duration::from_millis(*delay_ms);
+                    let _ = self.mp.println(tr!("progress.retry", SYMBOL_WARNING, filename, attempt, reason, format!("{:.1?}", wait_time)));
+                }
+            }
+            Event::AccountSwitched { content_id, account, http_status } => {
+                if let Some((_, filename)) = bars.get(content_id).filter(|_| !self.compact) {
+                    let _ = self.mp.println(tr!("progress.account_switched", SYMBOL_WARNING, filename, account, http_status));
+                }
+            }
+            Event::Finished { content_id, status, account, error } => {
+                // 没有进度条的项目 (跳过、获取详情失败) 已在日志中输出
+                if let Some((pb, filename)) = bars.remove(content_id) {
+                    let by_account = match account {
+                        Some(name) if self.show_accounts => tr!("progress.by_account", name).dimmed().to_string(),
+                        _ => String::new(),
+                    };
+                    let message = match (status, error) {
+                        (DownloadStatus::Success, _) => format!("{} '{}' {}", SYMBOL_SUCCESS.green(), filename, status.label().green()),
+                        (DownloadStatus::SuccessNoValidation, _) => format!("{} '{}' {}", SYMBOL_WARNING.yellow(), filename, status.label().yellow()),
+                        (_, Some(error)) => format!("{} '{}' {}: {}", SYMBOL_ERROR.red(), filename, status.label().red(), error),
+                        (_, None) => format!("{} '{}' {}", SYMBOL_ERROR.red(), filename, status.label().red()),
+                    };
+                    if !self.compact {
+                        pb.set_style(FINISHED_STYLE.clone());
+                        pb.finish_with_message(format!("{}{}", message, by_account));
+                    } else if !status.is_success() {
+                        let _ = self.mp.println(format!("{}{}", message, by_account));
+                    }
+                }
+            }
+            _ => {}
         }
+        batch.refresh(!matches!(event, Event::Progress { .. }));
     }
 }
 
-async fn process_single_task(client: Arc<Client>, args: Arc<Cli>, item_data: (String, String), dest_folder: Arc<PathBuf>, mp: Arc<MultiProgress>) -> (String, String, DownloadStatus) {
-    let (content_id, original_input) = item_data;
-    let token = match args.token.as_deref() {
-        Some(t) => t,
-        None => return (original_input, String::new(), DownloadStatus::TokenError)
+/// 处理单个下载项, 并发出结束事件
+async fn process_single_task(ctx: Arc<TaskContext>, item: DownloadItem) -> TaskResult {
+    // 可单独取消的客户端, 供控制命令中止这一项
+    let client = match &ctx.control {
+        Some(control) => {
+            let client = ctx.client.child();
+            control.started(&item, client.cancellation_token().clone());
+            client
+        }
+        None => ctx.client.clone(),
     };
+    let result = run_single_task(&ctx, &client, item).await;
+    if let Some(control) = &ctx.control {
+        control.finished(&result);
+    }
+    if let Some(journal) = &ctx.journal {
+        journal.record(&result);
+    }
+    ctx.client.emit(Event::Finished {
+        content_id: result.content_id.clone(),
+        status: result.status,
+        account: result.account.clone(),
+        error: result.failure.as_ref().and_then(|f| f.message.clone()),
+    });
+    result
+}
 
-    let details = match get_textbook_details(&client, &content_id, token).await {
+async fn run_single_task(ctx: &TaskContext, client: &SmartEduClient, item: DownloadItem) -> TaskResult {
+    let args = &ctx.args;
+    let details = match client.details(&item.0).await {
         Ok(d) => d,
         Err(e) => {
             // 对于非下载阶段的错误，使用log打印，不干扰进度条
-            error!("{} 获取'{}' (ID: {}) 详情失败: {}", SYMBOL_ERROR, original_input, content_id, e);
-            return (original_input, String::new(), DownloadStatus::FailGetDetails);
+            error!("{}", tr!("task.details_failed", SYMBOL_ERROR, item.1, item.0, e));
+            let mut result = TaskResult::failed(item, String::new(), &e);
+            if result.status == DownloadStatus::UnexpectedError { result.status = DownloadStatus::FailGetDetails; }
+            return result;
         }
     };
-    let is_batch = args.url.len() + args.content_id.len() > 1 || args.input_file.is_some();
-    let final_filename = if !is_batch {
+    let final_filename = if !ctx.is_batch {
         if let Some(output) = &args.output {
             let output_path = Path::new(output);
             // 检查 output 参数是否看起来像一个文件名
@@ -314,31 +675,56 @@ async fn process_single_task(client: Arc<Client>, args: Arc<Cli>, item_data: (St
     } else {
         details.filename.clone()
     };
-    let full_output_path = dest_folder.join(&final_filename);
+    let full_output_path = ctx.dest_folder.join(&final_filename);
     
     if full_output_path.exists() {
-        match validate_local_file(&full_output_path, &details).await {
+        match verify_file(&full_output_path, &details).await {
             Ok(DownloadStatus::Success) | Ok(DownloadStatus::SuccessNoValidation) => {
-                info!("{} '{}' {}", SYMBOL_SUCCESS.green(), final_filename, "已存在且校验一致, 跳过".dimmed());
-                return (original_input, full_output_path.to_string_lossy().to_string(), DownloadStatus::Skipped);
+                info!("{} '{}' {}", SYMBOL_SUCCESS.green(), final_filename, tr!("task.skip_existing").dimmed());
+                let mut result = TaskResult::new(item, full_output_path.to_string_lossy().to_string(), DownloadStatus::Skipped);
+                result.path = Some(full_output_path);
+                return result;
             }
             _ => {
-                info!("{} '{}' {}", SYMBOL_WARNING.yellow(), final_filename, "校验不一致, 重新下载".dimmed());
+                info!("{} '{}' {}", SYMBOL_WARNING.yellow(), final_filename, tr!("task.redownload").dimmed());
             }
         }
     }
 
-    match download_file(&client, &details, &full_output_path, mp).await {
-        Ok(status) => (original_input, final_filename, status),
+    // 熔断后不再发起新的下载, 交由主循环重新排队
+    if ctx.breaker.is_tripped() {
+        return TaskResult::new(item, final_filename, DownloadStatus::Aborted);
+    }
+
+    let outcome = client.download_to_path(&details, &full_output_path).await;
+    let mut result = match outcome.result {
+        Ok(status) if status.is_success() => TaskResult::new(item, final_filename, status),
+        Ok(status) => {
+            let mut result = TaskResult::new(item, final_filename, status);
+            result.failure = Some(FailureInfo { mirror: Some(mirror_of(&details.source_url)), ..Default::default() });
+            result
+        }
         Err(e) => {
-            error!("下载'{}' (ID: {}) 时发生意外错误: {}", final_filename, content_id, e);
-            (original_input, final_filename, DownloadStatus::UnexpectedError)
+            // 错误已显示在进度条上, 这里只记录调试信息
+            debug!("{}", tr!("task.download_failed", final_filename, item.0, e));
+            let result = TaskResult::failed(item, final_filename, &e);
+            // 还有其他账号可用时不熔断
+            if result.status == DownloadStatus::TokenError && client.pool().all_unauthorized() && ctx.breaker.trip() {
+                warn!("{}", tr!("task.token_tripped", SYMBOL_WARNING));
+            }
+            result
         }
-    }
+    };
+    result.account = outcome.account;
+    result.path = Some(full_output_path);
+    result
 }
 
 fn print_token_guide() {
-    let content = include_str!("ACCESS_TOKEN_GUIDE.txt");
+    let content = match i18n::lang() {
+        Lang::Zh => include_str!("ACCESS_TOKEN_GUIDE.txt"),
+        Lang::En => include_str!("ACCESS_TOKEN_GUIDE.en.txt"),
+    };
     let lines: Vec<&str> = content.lines().collect();
     let divider = SYMBOL_DIVIDER.repeat(60);
     
@@ -377,7 +763,7 @@ fn print_token_guide() {
             
             // 查找 URL 的结束位置。这里我们定义为遇到空格或右括号就结束。
             // 如果没找到，就认为 URL 一直持续到行尾。
-            let url_end_offset = url_and_after.find(|c| c == ' ' || c == ')')
+            let url_end_offset = url_and_after.find([' ', ')'])
                 .unwrap_or(url_and_after.len());
 
             // 将行分割成三部分：URL前，URL本身，URL后
@@ -406,6 +792,8 @@ fn print_token_guide() {
     println!("{}", divider.blue().bold());
 }
 
+// 跳过无法解码的行 (如非 UTF-8 内容) 而不是在此处截断整个列表
+#[allow(clippy::lines_filter_map_ok)]
 fn read_input_file(path: &Path) -> Result<Vec<String>, AppError> {
     let file = std::fs::File::open(path)?;
     Ok(io::BufReader::new(file).lines().filter_map(Result::ok)
@@ -414,28 +802,107 @@ fn read_input_file(path: &Path) -> Result<Vec<String>, AppError> {
         .collect())
 }
 
-async fn handle_token_input(cli: &Cli) -> Result<String, AppError> {
-    if let Some(token) = &cli.token { return Ok(token.clone()); }
-    if let Ok(token_from_file) = fs::read_to_string(TOKEN_FILE).await {
-        print!("{} 检测到已保存的 Token，是否使用？(y/n): ", SYMBOL_PROMPT);
-        io::stdout().flush()?;
-        let mut input = String::new();
-        if io::stdin().read_line(&mut input).is_ok() && input.trim().to_lowercase().starts_with('y') {
-            return Ok(token_from_file.trim().to_string());
+fn credential_options(cli: &Cli) -> credential::CredentialOptions {
+    credential::CredentialOptions {
+        cli_token: cli.token.clone(),
+        token_file: cli.token_file.clone(),
+        helper: cli.token_helper.clone(),
+    }
+}
+
+fn confirm(prompt: &str) -> Result<bool, AppError> {
+    print!("{} {} {}: ", SYMBOL_PROMPT, prompt, tr!("prompt.yes_no"));
+    io::stdout().flush()?;
+    let mut input = String::new();
+    Ok(io::stdin().read_line(&mut input).is_ok() && input.trim().to_lowercase().starts_with('y'))
+}
+
+/// 读取加密 Token 的口令: 优先环境变量, 其次交互输入 (不回显)。
+fn read_passphrase(cli: &Cli, confirm_twice: bool) -> Result<String, AppError> {
+    if let Ok(passphrase) = std::env::var(token_store::PASSPHRASE_ENV) && !passphrase.is_empty() {
+        return Ok(passphrase);
+    }
+    if cli.no_input {
+        return Err(AppError::TokenStore(tr!("prompt.passphrase_required", token_store::PASSPHRASE_ENV)));
+    }
+    let passphrase = rpassword::prompt_password(format!("{} {}", SYMBOL_PROMPT, tr!("prompt.passphrase")))?;
+    if passphrase.is_empty() {
+        return Err(AppError::TokenStore(tr!("prompt.passphrase_empty")));
+    }
+    if confirm_twice && rpassword::prompt_password(format!("{} {}", SYMBOL_PROMPT, tr!("prompt.passphrase_again")))? != passphrase {
+        return Err(AppError::TokenStore(tr!("prompt.passphrase_mismatch")));
+    }
+    Ok(passphrase)
+}
+
+/// 从 Token 存储中读取: `--token-name` 指定的, 或默认 Token。
+fn load_stored_token(cli: &Cli) -> Result<Option<String>, AppError> {
+    let store = TokenStore::load()?;
+    let name = match (&cli.token_name, store.default_name()) {
+        (Some(name), _) => name.as_str(),
+        (None, Some(name)) => {
+            // 交互模式下沿用原来的确认提示, 方便在 Token 过期时直接换新的
+            if !cli.no_input && io::stdin().is_terminal() && !confirm(&tr!("prompt.use_saved_named", name))? {
+                return Ok(None);
+            }
+            name
+        }
+        (None, None) => return Ok(None),
+    };
+    let token = store.reveal(name, || read_passphrase(cli, false))?;
+    debug!("{}", tr!("token.using_saved", name, store.path().display()));
+    Ok(Some(token))
+}
+
+/// 把交互输入的 Token 存入 Token 存储。原来是加密保存的会继续加密。
+fn save_prompted_token(cli: &Cli, token: &str) -> Result<(), AppError> {
+    let mut store = TokenStore::load()?;
+    let name = cli.token_name.as_deref().unwrap_or(token_store::DEFAULT_TOKEN_NAME);
+    let passphrase = match store.get(name) {
+        Some(existing) if existing.is_encrypted() => Some(read_passphrase(cli, false)?),
+        _ => None,
+    };
+    store.set(name, token, passphrase.as_deref())?;
+    store.save()?;
+    info!("{}", tr!("prompt.token_saved", SYMBOL_INFO, name, store.path().display()));
+    Ok(())
+}
+
+/// 获取 Token。`force_prompt` 为 true 时忽略命令行和已保存的 Token, 直接提示重新输入。
+async fn handle_token_input(cli: &Cli, force_prompt: bool) -> Result<String, AppError> {
+    if !force_prompt {
+        if let Some(found) = credential::resolve(&credential_options(cli))? {
+            debug!("{}", tr!("token.using_source", found.source));
+            return Ok(found.token);
+        }
+        if let Some(token) = load_stored_token(cli)? {
+            return Ok(token);
         }
+        if !cli.no_input && let Ok(token_from_file) = fs::read_to_string(LEGACY_TOKEN_FILE).await {
+            warn!("{}", tr!("token.legacy_file", SYMBOL_WARNING, LEGACY_TOKEN_FILE));
+            if confirm(&tr!("prompt.use_saved"))? {
+                return Ok(token_from_file.trim().to_string());
+            }
+        }
+    }
+    if cli.no_input {
+        return Err(AppError::Credential(tr!("prompt.token_missing", credential::TOKEN_ENV)));
     }
     print_token_guide();
     loop {
-        print!("{} 请输入您的访问令牌 (Access Token): ", SYMBOL_PROMPT);
+        print!("{} {}", SYMBOL_PROMPT, tr!("prompt.token"));
         io::stdout().flush()?;
         let mut token_input = String::new();
-        io::stdin().read_line(&mut token_input)?;
+        if io::stdin().read_line(&mut token_input)? == 0 {
+            // 标准输入已关闭 (如在脚本中运行), 继续循环只会无限提示
+            return Err(AppError::InvalidInput(tr!("prompt.token_stdin_closed")));
+        }
         let token = token_input.trim();
         if !token.is_empty() {
-            if let Err(e) = fs::write(TOKEN_FILE, token).await { warn!("{} 无法保存Token到文件: {}", SYMBOL_WARNING, e); }
+            if let Err(e) = save_prompted_token(cli, token) { warn!("{}", tr!("token.save_failed", SYMBOL_WARNING, e)); }
             return Ok(token.to_string());
         }
-        println!("{} 访问令牌不能为空，请重新输入。", SYMBOL_ERROR);
+        println!("{} {}", SYMBOL_ERROR, tr!("prompt.token_empty"));
     }
 }
 
@@ -443,7 +910,7 @@ async fn determine_output_dir(cli: &Cli, is_batch: bool) -> Result<PathBuf, AppE
     let output_str = cli.output.as_deref().unwrap_or(".");
     let output_path = PathBuf::from(output_str);
     if is_batch && output_path.is_file() {
-        return Err(AppError::InvalidInput("批量下载时, '-o' 参数必须是目录, 不能是文件。".into()));
+        return Err(AppError::InvalidInput(tr!("input.output_is_file")));
     }
     let dest_folder = if output_str.ends_with('/') || output_str.ends_with('\\') || !output_path.exists() || output_path.is_dir() {
         output_path
@@ -451,63 +918,103 @@ async fn determine_output_dir(cli: &Cli, is_batch: bool) -> Result<PathBuf, AppE
         output_path.parent().unwrap_or_else(|| Path::new(".")).to_path_buf()
     };
     fs::create_dir_all(&dest_folder).await.map_err(|e| AppError::DirCreation(e.to_string()))?;
-    info!("文件将保存到目录: '{}'", dest_folder.display());
+    info!("{}", tr!("input.output_dir", dest_folder.display()));
     Ok(dest_folder)
 }
 
-fn collect_download_items(cli: &Cli) -> Result<Vec<(String, String)>, AppError> {
+fn build_retry_config(cli: &Cli, section: &config::RetrySection) -> RetryConfig {
+    let mut retry = RetryConfig::default();
+    let base_delay = cli.retry_base_delay.or(section.base_delay_ms).map(Duration::from_millis);
+    let max_delay = cli.retry_max_delay.or(section.max_delay_ms).map(Duration::from_millis);
+    let jitter = if cli.no_retry_jitter { Some(false) } else { section.jitter };
+    for policy in [&mut retry.details, &mut retry.download] {
+        if let Some(d) = base_delay { policy.base_delay = d; }
+        if let Some(d) = max_delay { policy.max_delay = d; }
+        if let Some(j) = jitter { policy.jitter = j; }
+    }
+    if let Some(n) = cli.details_attempts.or(section.details_attempts) { retry.details.max_attempts = n.max(1); }
+    if let Some(n) = cli.download_attempts.or(section.download_attempts) { retry.download.max_attempts = n.max(1); }
+    if let Some(secs) = cli.max_retry_after.or(section.max_retry_after_secs) { retry.max_retry_after = Duration::from_secs(secs); }
+    debug!("{}", tr!("settings.retry_config", format!("{:?}", retry)));
+    retry
+}
+
+fn collect_download_items(cli: &Cli) -> Result<Vec<DownloadItem>, AppError> {
     let mut download_items = Vec::new();
     let mut processed_ids = HashSet::new();
     let mut add_unique_item = |original: &str, source: &str| {
         if let Some(id) = get_content_id(original) {
             if processed_ids.insert(id.clone()) {
-                info!("{} 已添加: ID {}... (来源: {})", SYMBOL_SUCCESS, &id[..8], source);
+                info!("{}", tr!("input.added", SYMBOL_SUCCESS, &id[..8], source));
                 download_items.push((id, original.to_string()));
             } else {
-                info!("{} 检测到重复项，已跳过: '{}'", SYMBOL_INFO, original);
+                info!("{}", tr!("input.duplicate", SYMBOL_INFO, original));
             }
         } else {
-            warn!("{} 无效输入，已跳过: '{}' (来源: {})", SYMBOL_WARNING, original, source);
+            warn!("{}", tr!("input.invalid", SYMBOL_WARNING, original, source));
         }
     };
-    for url in &cli.url { add_unique_item(url, "命令行URL"); }
-    for cid in &cli.content_id { add_unique_item(cid, "命令行ID"); }
+    for url in &cli.url { add_unique_item(url, &tr!("input.source_url")); }
+    for cid in &cli.content_id { add_unique_item(cid, &tr!("input.source_id")); }
     if let Some(path) = &cli.input_file {
         for (i, line) in read_input_file(path)?.iter().enumerate() {
-            add_unique_item(line, &format!("文件第 {} 行", i + 1));
+            add_unique_item(line, &tr!("input.source_line", i + 1));
+        }
+    }
+    if let Some(report) = &cli.retry_failed {
+        for input in manifest::failed_inputs(report)? {
+            add_unique_item(&input, &tr!("input.source_manifest"));
         }
     }
     if download_items.is_empty() {
-        return Err(AppError::InvalidInput("未找到任何有效的下载项。请检查输入。".into()));
+        return Err(AppError::InvalidInput(tr!("input.no_items")));
     }
     Ok(download_items)
 }
 
-fn process_download_results(results: Vec<Result<(String, String, DownloadStatus), tokio::task::JoinError>>) {
+fn describe_failure(result: &TaskResult) -> String {
+    let mut line = format!("'{}': [{}] {}", result.original, result.status.code(), result.status.label());
+    if let Some(failure) = &result.failure {
+        let mut context = Vec::new();
+        if let Some(code) = failure.http_code { context.push(format!("HTTP {}", code)); }
+        if let Some(mirror) = &failure.mirror { context.push(tr!("summary.source", mirror)); }
+        if let Some(account) = &result.account { context.push(tr!("summary.account", account)); }
+        if !context.is_empty() { line.push_str(&format!(" ({})", context.join(", "))); }
+        // HTTP 错误的信息已包含在上面, 其他错误附上原始信息便于排查
+        if let (None, Some(message)) = (failure.http_code, &failure.message) { line.push_str(&format!(" - {}", message)); }
+    }
+    line
+}
+
+fn process_download_results(results: &[TaskResult]) {
     let mut stats = HashMap::new();
     let mut failed_details = Vec::new();
     let mut skipped_details = Vec::new();
+    let mut failed_statuses = Vec::new();
+    // 账号 -> (成功数, 失败数, 成功下载的文件)
+    let mut account_stats: BTreeMap<String, (usize, usize, Vec<String>)> = BTreeMap::new();
 
-    for res in results {
-        match res {
-            Ok((original, filename, status)) => {
-                *stats.entry(status).or_insert(0) += 1;
-                match status {
-                    DownloadStatus::Skipped => {
-                        skipped_details.push(format!("'{}'", filename));
-                    }
-                    DownloadStatus::Success | DownloadStatus::SuccessNoValidation => {
-                        // 成功状态，这里不需要额外操作
-                    }
-                    _ => { // 捕获所有其他失败状态
-                        let reason = format!("{:?}", status);
-                        failed_details.push(format!("'{}': {}", original, reason));
-                    }
-                }
+    for result in results {
+        *stats.entry(result.status).or_insert(0) += 1;
+        if let Some(account) = &result.account {
+            let entry = account_stats.entry(account.clone()).or_default();
+            if result.status.is_success() {
+                entry.0 += 1;
+                entry.2.push(format!("'{}'", result.filename));
+            } else {
+                entry.1 += 1;
+            }
+        }
+        match result.status {
+            DownloadStatus::Skipped => {
+                skipped_details.push(format!("'{}'", result.filename));
             }
-            Err(e) => { 
-                *stats.entry(DownloadStatus::UnexpectedError).or_insert(0) += 1;
-                failed_details.push(format!("任务执行时发生Panic: {}", e)); 
+            DownloadStatus::Success | DownloadStatus::SuccessNoValidation => {
+                // 成功状态，这里不需要额外操作
+            }
+            status => { // 捕获所有其他失败状态
+                failed_details.push(describe_failure(result));
+                if !failed_statuses.contains(&status) { failed_statuses.push(status); }
             }
         }
     }
@@ -517,26 +1024,471 @@ fn process_download_results(results: Vec<Result<(String, String, DownloadStatus)
     let failed_count = failed_details.len();
     let total_tasks = successful_count + skipped_count + failed_count;
 
-    info!("\n{0}\n{1} {2}下载任务完成！", SYMBOL_DIVIDER.repeat(50), SYMBOL_END, if total_tasks > 1 { "批量" } else { "单次" });
-    info!("{} 总计: {} | 成功: {} | 失败: {} | 跳过: {}", SYMBOL_STATS, total_tasks, successful_count, failed_count, skipped_count);
+    let done = tr!(if total_tasks > 1 { "summary.done_batch" } else { "summary.done_single" }, SYMBOL_END);
+    info!("\n{}\n{}", SYMBOL_DIVIDER.repeat(50), done);
+    info!("{}", tr!("summary.totals", SYMBOL_STATS, total_tasks, successful_count, failed_count, skipped_count));
     if !skipped_details.is_empty() {
-        info!("{} 跳过的文件 (已存在):", SYMBOL_STATS);
+        info!("{}", tr!("summary.skipped", SYMBOL_STATS));
         for item in skipped_details { info!("  - {}", item); }
     }
+    if account_stats.len() > 1 {
+        info!("{}", tr!("summary.accounts", SYMBOL_STATS));
+        for (account, (succeeded, failed, files)) in &account_stats {
+            info!("  {}", tr!("summary.account_line", account, succeeded, failed));
+            for file in files { info!("    - {}", file); }
+        }
+    }
     if !failed_details.is_empty() {
-        error!("{} 失败的详情:", SYMBOL_STATS);
+        error!("{}", tr!("summary.failed", SYMBOL_STATS));
         for item in failed_details { error!("  - {}", item); }
+        info!("{}", tr!("summary.hints", SYMBOL_STATS));
+        for status in failed_statuses {
+            if let Some(hint) = status.hint() {
+                info!("  [{}] {} x{}: {}", status.code(), status.label(), stats.get(&status).unwrap_or(&1), hint);
+            }
+        }
     }
     info!("{}", SYMBOL_DIVIDER.repeat(50));
 }
 
-// --- 6. 主程序 ---
+/// 用第一个下载项的资源对每个账号的 Token 做预检, 失效的账号不再使用。
+/// 所有账号都失效时, 交互模式下要求重新输入 Token。
+async fn preflight_accounts(client: &SmartEduClient, cli: &Cli, content_id: &str, interactive: bool) -> Result<(), AppError> {
+    let pool = client.pool();
+    let info = match client.details(content_id).await {
+        Ok(info) => info,
+        Err(e) => {
+            warn!("{}", tr!("preflight.skipped", SYMBOL_WARNING, e));
+            return Ok(());
+        }
+    };
+    let multi = pool.len() > 1;
+    loop {
+        for account in pool.accounts() {
+            let label = if multi { tr!("preflight.account", account.name) } else { String::new() };
+            match client.probe_token(&info, &account.token).await {
+                Ok(TokenState::Valid) => info!("{}", tr!("preflight.valid", SYMBOL_SUCCESS, label)),
+                Ok(TokenState::Forbidden) => warn!("{}", tr!("preflight.forbidden", SYMBOL_WARNING, label)),
+                Ok(TokenState::Inconclusive(status)) => warn!("{}", tr!("preflight.inconclusive", SYMBOL_WARNING, label, status)),
+                Ok(TokenState::Expired) => {
+                    warn!("{}", tr!("preflight.expired", SYMBOL_WARNING, label));
+                    pool.park(account.index, ParkReason::Unauthorized);
+                }
+                Err(e) => warn!("{}", tr!("preflight.failed", SYMBOL_WARNING, label, e)),
+            }
+        }
+        if !pool.all_unauthorized() {
+            return Ok(());
+        }
+        if !interactive {
+            return Err(AppError::TokenExpired);
+        }
+        warn!("{}", tr!("preflight.reenter", SYMBOL_WARNING));
+        renew_unauthorized_account(cli, pool).await?;
+    }
+}
+
+/// 为第一个失效的账号重新输入 Token。多账号时新 Token 保存在该账号名下。
+async fn renew_unauthorized_account(cli: &Cli, pool: &TokenPool) -> Result<(), AppError> {
+    let Some(name) = pool.first_unauthorized() else { return Ok(()) };
+    let mut cli = cli.clone();
+    if pool.len() > 1 {
+        info!("{}", tr!("prompt.renew_account", SYMBOL_INFO, name));
+        cli.token_name = Some(name.clone());
+    }
+    let token = handle_token_input(&cli, true).await?;
+    pool.replace_token(&name, &token);
+    Ok(())
+}
+
+/// 读取 `--accounts` 或配置文件中列出的已保存 Token, 加密的 Token 共用同一个口令。
+fn load_accounts(cli: &Cli, names: &[String]) -> Result<Vec<(String, String)>, AppError> {
+    let store = TokenStore::load()?;
+    let names: Vec<String> = if names.iter().any(|n| n == "all") {
+        store.entries().map(|(name, _)| name.clone()).collect()
+    } else {
+        let mut unique = Vec::new();
+        for name in names { if !unique.contains(name) { unique.push(name.clone()); } }
+        unique
+    };
+    if names.is_empty() {
+        return Err(AppError::TokenStore(tr!("accounts.none_saved")));
+    }
+    let mut passphrase: Option<String> = None;
+    let mut accounts = Vec::with_capacity(names.len());
+    for name in names {
+        let token = store.reveal(&name, || {
+            let p = match &passphrase { Some(p) => p.clone(), None => read_passphrase(cli, false)? };
+            passphrase = Some(p.clone());
+            Ok(p)
+        })?;
+        accounts.push((name, token));
+    }
+    Ok(accounts)
+}
+
+/// 读取账号的 Token: 指定了多个账号时读取已保存的 Token, 否则沿用单个 Token 的获取方式。
+async fn resolve_accounts(cli: &Cli, names: &[String]) -> Result<Vec<(String, String)>, AppError> {
+    if names.is_empty() {
+        let name = cli.token_name.clone().unwrap_or_else(|| token_store::DEFAULT_TOKEN_NAME.to_string());
+        Ok(vec![(name, handle_token_input(cli, false).await?)])
+    } else {
+        load_accounts(cli, names)
+    }
+}
+
+/// 账号池使用的账号名称, 命令行优先于配置文件
+fn account_names<'a>(cli: &'a Cli, section: &'a config::AccountsSection) -> &'a [String] {
+    if cli.accounts.is_empty() { &section.names } else { &cli.accounts }
+}
+
+/// 构建账号池
+async fn build_token_pool(cli: &Cli, section: &config::AccountsSection) -> Result<TokenPool, AppError> {
+    let accounts = resolve_accounts(cli, account_names(cli, section)).await?;
+    let strategy = cli.account_strategy.or(section.strategy).unwrap_or_default();
+    let cooldown = Duration::from_secs(cli.account_cooldown.or(section.cooldown_secs).unwrap_or(DEFAULT_ACCOUNT_COOLDOWN_SECS));
+    if accounts.len() > 1 {
+        let names: Vec<&str> = accounts.iter().map(|(name, _)| name.as_str()).collect();
+        info!("{}", tr!("accounts.using", SYMBOL_INFO, accounts.len(), names.join(", "), format!("{:?}", strategy)));
+    }
+    Ok(TokenPool::new(accounts, strategy, cooldown))
+}
+
+/// 保存 Token 到 Token 存储 (`token set` / `token import`)
+fn store_token(cli: &Cli, name: &str, token: &str, encrypt: bool, make_default: bool) -> Result<(), AppError> {
+    let passphrase = if encrypt { Some(read_passphrase(cli, true)?) } else { None };
+    let mut store = TokenStore::load()?;
+    store.set(name, token, passphrase.as_deref())?;
+    if make_default { store.set_default(name)?; }
+    store.save()?;
+    println!("{}", tr!("token.saved", SYMBOL_SUCCESS.green(), name, store.path().display()));
+    Ok(())
+}
+
+/// `--har` 开启时所有客户端共用的抓包记录及其写入路径
+static HAR: OnceCell<(PathBuf, Arc<HarRecorder>)> = OnceCell::new();
+
+/// 各子命令创建客户端时共用的设置
+#[derive(Clone)]
+struct ClientSettings {
+    retry: RetryConfig,
+    endpoints: Endpoints,
+    cache: Option<HttpCache>,
+    offline: bool,
+}
+
+impl ClientSettings {
+    fn resolve(cli: &Cli, config: &config::Config) -> Result<ClientSettings, AppError> {
+        let mut endpoints = config.endpoints.clone();
+        if !cli.api_host.is_empty() { endpoints.hosts = cli.api_host.clone(); }
+        endpoints.validate()?;
+        let cache = if cli.no_cache || config.cache.enabled == Some(false) {
+            None
+        } else {
+            let dir = cli.cache_dir.clone().or_else(|| config.cache.dir.clone()).or_else(HttpCache::default_dir)
+                .ok_or_else(|| AppError::Config(tr!("settings.no_cache_dir")))?;
+            let cache = HttpCache::new(dir);
+            Some(match cli.cache_ttl.or(config.cache.ttl_secs) {
+                Some(secs) => cache.ttl(Duration::from_secs(secs)),
+                None => cache,
+            })
+        };
+        if cli.offline && cache.is_none() {
+            return Err(AppError::Config(tr!("settings.offline_cache_disabled")));
+        }
+        Ok(ClientSettings { retry: build_retry_config(cli, &config.retry), endpoints, cache, offline: cli.offline })
+    }
+}
+
+/// 按共用设置创建客户端构建器, 开启抓包时同时记录请求
+fn client_builder(settings: &ClientSettings) -> SmartEduClientBuilder {
+    let mut builder = SmartEduClient::builder()
+        .retry(settings.retry.clone())
+        .endpoints(settings.endpoints.clone())
+        .offline(settings.offline);
+    if let Some(cache) = &settings.cache {
+        builder = builder.cache(cache.clone());
+    }
+    match HAR.get() {
+        Some((_, recorder)) => builder.har(recorder.clone()),
+        None => builder,
+    }
+}
+
+/// 写出 HAR 抓包。正常结束和提前退出时都会调用。
+fn save_har() {
+    let Some((path, recorder)) = HAR.get() else { return };
+    match recorder.save(path) {
+        Ok(()) => info!("{}", tr!("summary.har_written", SYMBOL_INFO, recorder.len(), path.display())),
+        Err(e) => error!("{}", tr!("summary.har_failed", SYMBOL_ERROR, e)),
+    }
+}
+
+/// 写出抓包后以指定的退出码退出
+fn exit(code: i32) -> ! {
+    save_har();
+    std::process::exit(code)
+}
+
+/// 获取目录后打开浏览界面, 返回选中的教材
+async fn browse_catalog(settings: &ClientSettings) -> Result<Vec<DownloadItem>, AppError> {
+    let client = client_builder(settings).build()?;
+    info!("{}", tr!("browse.fetching", SYMBOL_INFO));
+    let (tags, entries) = tokio::try_join!(client.catalog_tags(), client.catalog())?;
+    tokio::task::spawn_blocking(move || browse::run(tags, entries)).await.map_err(io::Error::other)?
+}
+
+/// `sed-dl ctl ...` 子命令
+async fn run_ctl_command(cli: &Cli, action: &CtlAction) -> Result<(), AppError> {
+    let resolve = |item: &str| get_content_id(item)
+        .ok_or_else(|| AppError::InvalidInput(tr!("input.no_content_id", item)));
+    let request = match action {
+        CtlAction::Pause { item } => control::Request::Pause { item: item.as_deref().map(resolve).transpose()? },
+        CtlAction::Resume { item } => control::Request::Resume { item: item.as_deref().map(resolve).transpose()? },
+        CtlAction::Concurrency { value } => control::Request::Concurrency { value: *value },
+        CtlAction::Bump { item } => control::Request::Bump { item: resolve(item)? },
+        CtlAction::Cancel { item } => control::Request::Cancel { item: resolve(item)? },
+        CtlAction::Retry { item } => control::Request::Retry { item: resolve(item)? },
+        CtlAction::State { .. } => control::Request::State,
+    };
+    let path = cli.control_socket.clone().unwrap_or_else(control::default_socket_path);
+    let response = control::send(&path, &request).await?;
+    if !response.ok {
+        return Err(AppError::InvalidInput(response.message));
+    }
+    let Some(state) = response.state else {
+        println!("{} {}", SYMBOL_SUCCESS.green(), response.message);
+        return Ok(());
+    };
+    if matches!(action, CtlAction::State { json: true }) {
+        println!("{}", serde_json::to_string_pretty(&state)?);
+        return Ok(());
+    }
+    let finished: usize = state.finished.values().sum();
+    let overall = if state.paused { tr!("ctl.paused").yellow() } else { tr!("ctl.running").green() };
+    println!("{}", tr!("ctl.state", SYMBOL_STATS, overall, state.max_concurrent, state.running.len(), state.queued.len(), finished));
+    for item in &state.running {
+        println!("  {} {} ({}s)", tr!("ctl.downloading").cyan(), item.input, item.elapsed_secs.unwrap_or(0));
+    }
+    for item in &state.queued {
+        let label = if item.paused { tr!("ctl.paused").yellow() } else { tr!("ctl.queued").normal() };
+        println!("  {} {}", label, item.input);
+    }
+    if !state.finished.is_empty() {
+        let counts: Vec<String> = state.finished.iter().map(|(code, n)| format!("{} x{}", code, n)).collect();
+        println!("  {} {}", tr!("ctl.finished").dimmed(), counts.join(", "));
+    }
+    Ok(())
+}
+
+/// `sed-dl token ...` 子命令
+async fn run_token_command(cli: &Cli, action: &TokenAction, config: &config::Config, settings: &ClientSettings) -> Result<(), AppError> {
+    match action {
+        TokenAction::Check { probe } => {
+            let probe = probe.as_deref().or(config.token.probe.as_deref())
+                .ok_or_else(|| AppError::InvalidInput(tr!("token.probe_missing")))?;
+            let content_id = get_content_id(probe)
+                .ok_or_else(|| AppError::InvalidInput(tr!("input.no_content_id", probe)))?;
+            let token = handle_token_input(cli, false).await?;
+            let client = client_builder(settings).build()?;
+            let info = client.details(&content_id).await?;
+            match client.probe_token(&info, &token).await? {
+                TokenState::Valid => {
+                    println!("{} {}", SYMBOL_SUCCESS.green(), tr!("token.valid").green());
+                }
+                TokenState::Expired => {
+                    println!("{} {}", SYMBOL_ERROR.red(), tr!("token.invalid").red());
+                    exit(EXIT_TOKEN_INVALID);
+                }
+                TokenState::Forbidden => {
+                    println!("{} {}", SYMBOL_WARNING.yellow(), tr!("token.forbidden").yellow());
+                    exit(EXIT_TOKEN_FORBIDDEN);
+                }
+                TokenState::Inconclusive(status) => {
+                    return Err(AppError::HttpStatus { status, mirror: mirror_of(&info.source_url) });
+                }
+            }
+            Ok(())
+        }
+        TokenAction::Set { name, encrypt, default } => {
+            let token = match credential::resolve(&credential_options(cli))? {
+                Some(found) => found.token,
+                None if cli.no_input => return Err(AppError::Credential(tr!("token.nothing_to_save"))),
+                None => rpassword::prompt_password(format!("{} {}", SYMBOL_PROMPT, tr!("prompt.token_to_save")))?.trim().to_string(),
+            };
+            if token.is_empty() {
+                return Err(AppError::InvalidInput(tr!("token.empty")));
+            }
+            store_token(cli, name, &token, *encrypt, *default)
+        }
+        TokenAction::Import { browser, profile, name, encrypt, default } => {
+            let token = browser_import::import_token(*browser, profile.as_deref())?;
+            println!("{}", tr!("token.imported", SYMBOL_SUCCESS.green()));
+            store_token(cli, name, &token, *encrypt, *default)
+        }
+        TokenAction::List => {
+            let store = TokenStore::load()?;
+            if store.entries().next().is_none() {
+                println!("{}", tr!("token.list_empty", SYMBOL_INFO));
+                return Ok(());
+            }
+            for (name, stored) in store.entries() {
+                let marker = if store.default_name() == Some(name) { "*" } else { " " };
+                let kind = if stored.is_encrypted() { tr!("token.encrypted") } else { tr!("token.plain") };
+                println!("{} {:<16} {}  {}", marker.green().bold(), name, kind, stored.created_at.dimmed());
+            }
+            Ok(())
+        }
+        TokenAction::Remove { name } => {
+            let mut store = TokenStore::load()?;
+            if !store.remove(name) {
+                return Err(AppError::TokenStore(tr!("token.not_found", name)));
+            }
+            store.save()?;
+            println!("{}", tr!("token.removed", SYMBOL_SUCCESS.green(), name));
+            Ok(())
+        }
+        TokenAction::Default { name } => {
+            let mut store = TokenStore::load()?;
+            store.set_default(name)?;
+            store.save()?;
+            println!("{}", tr!("token.default_set", SYMBOL_SUCCESS.green(), name));
+            Ok(())
+        }
+    }
+}
+
+/// 监听 Ctrl-C: 第一次停止派发新任务并等待进行中的下载完成, 第二次中止进行中的下载,
+/// 第三次直接退出 (如卡在交互输入时)。
+fn spawn_signal_handler(shutdown: CancellationToken, abort: CancellationToken) {
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_err() { return; }
+        warn!("{}", tr!("signal.stopping", SYMBOL_WARNING));
+        shutdown.cancel();
+        if tokio::signal::ctrl_c().await.is_err() { return; }
+        warn!("{}", tr!("signal.aborting", SYMBOL_WARNING));
+        abort.cancel();
+        if tokio::signal::ctrl_c().await.is_ok() {
+            exit(EXIT_INTERRUPTED);
+        }
+    });
+}
+
+/// 派发队列中的任务直到队列为空、Token 熔断或收到中断信号。未派发的项目保留在队列中。
+/// 派发过程中响应控制命令: 暂停、插队、取消和调整并发数。
+async fn run_download_round(ctx: Arc<TaskContext>, queue: &mut VecDeque<DownloadItem>) -> Vec<TaskResult> {
+    let control = ctx.control.clone().unwrap_or_else(|| Arc::new(control::Controller::new(ctx.args.max_concurrent_downloads)));
+    let semaphore = control.semaphore();
+    let mut changes = control.subscribe();
+    let mut tasks = JoinSet::new();
+    let mut running = HashMap::new();
+    let mut results = Vec::new();
+
+    control.begin_round();
+    loop {
+        changes.borrow_and_update();
+        control.apply(queue, &mut results);
+        let stopped = ctx.breaker.is_tripped() || ctx.shutdown.is_cancelled();
+        if tasks.is_empty() && (stopped || (queue.is_empty() && !control.keep_open())) {
+            // 最后一次检查后到达的重试命令在这里补上, 之后的命令返回错误
+            if control.end_round(queue, &mut results, stopped) { break; }
+            continue;
+        }
+        let next = if stopped || control.is_paused() { None } else { control.next_index(queue) };
+
+        tokio::select! {
+            permit = semaphore.clone().acquire_owned(), if next.is_some() => {
+                let permit = permit.expect("信号量不会被关闭");
+                let item = queue.remove(next.expect("已检查")).expect("位置有效");
+                let task_ctx = ctx.clone();
+                let task_item = item.clone();
+                let task_control = control.clone();
+                let handle = tasks.spawn(async move {
+                    let result = process_single_task(task_ctx, task_item).await;
+                    task_control.release(permit);
+                    result
+                });
+                running.insert(handle.id(), item);
+            }
+            Some(joined) = tasks.join_next_with_id() => {
+                let (item, result) = match joined {
+                    Ok((id, result)) => (running.remove(&id).expect("任务已登记"), result),
+                    Err(e) => {
+                        let item = running.remove(&e.id()).expect("任务已登记");
+                        let mut result = TaskResult::new(item.clone(), String::new(), DownloadStatus::UnexpectedError);
+                        result.failure = Some(FailureInfo { message: Some(tr!("run.task_panicked", e)), ..Default::default() });
+                        (item, result)
+                    }
+                };
+                // 下载中被单独暂停的项放回队首, 继续后重新下载
+                if result.status == DownloadStatus::Cancelled && control.is_held(&item.0) && !ctx.shutdown.is_cancelled() {
+                    queue.push_front(item);
+                } else {
+                    results.push(result);
+                }
+            }
+            _ = changes.changed() => {}
+            _ = ctx.shutdown.cancelled(), if !stopped => {}
+        }
+    }
+    results
+}
+
+/// 批量结束后冷却一段时间, 再重试一次因临时错误失败的项。未能重试的项保留原来的结果。
+async fn retry_transient_failures(ctx: Arc<TaskContext>, results: &mut Vec<TaskResult>, delay: Duration) {
+    let (transient, rest): (Vec<_>, Vec<_>) = std::mem::take(results).into_iter().partition(|r| r.status.is_transient());
+    *results = rest;
+    if transient.is_empty() { return; }
+
+    info!("{}", tr!("run.retry_pass", SYMBOL_INFO, transient.len(), delay.as_secs()));
+    let mut queue: VecDeque<DownloadItem> = transient.iter().map(TaskResult::item).collect();
+    tokio::select! {
+        _ = tokio::time::sleep(delay) => {}
+        _ = ctx.shutdown.cancelled() => {}
+    }
+    if !ctx.shutdown.is_cancelled() {
+        for (content_id, _) in &queue {
+            ctx.client.emit(Event::Queued { content_id: content_id.clone() });
+        }
+        results.extend(run_download_round(ctx.clone(), &mut queue).await);
+    }
+    let remaining: HashSet<String> = queue.into_iter().map(|(content_id, _)| content_id).collect();
+    results.extend(transient.into_iter().filter(|r| remaining.contains(&r.content_id)));
+}
+
+/// 按下载日志整理队列: 已完成且文件未变动的项直接记为跳过, 上次失败的项排在最前
+fn resume_queue(items: Vec<DownloadItem>, state: &journal::ResumeState, results: &mut Vec<TaskResult>) -> VecDeque<DownloadItem> {
+    let mut failed = VecDeque::new();
+    let mut pending = VecDeque::new();
+    for item in items {
+        if let Some(path) = state.done.get(&item.0) {
+            let mut result = TaskResult::new(item, path.to_string_lossy().to_string(), DownloadStatus::Skipped);
+            result.path = Some(path.clone());
+            results.push(result);
+        } else if state.failed.contains(&item.0) {
+            failed.push_back(item);
+        } else {
+            pending.push_back(item);
+        }
+    }
+    failed.extend(pending);
+    failed
+}
+
+// --- 5. 主程序 ---
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let cli = Cli::parse();
+    let result = run().await;
+    save_har();
+    result
+}
+
+async fn run() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cli = Cli::parse_localized();
+    output::init_color(cli.color);
+    let config = config::Config::load(cli.config.as_deref())?;
     let log_level = if cli.debug { "debug" } else { "info" };
-    
-    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(log_level))
+    let console_logger = env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(log_level))
         .format(|buf, record| {
             let now = Utc::now();
             let local_time = now.with_timezone(&chrono::Local);
@@ -548,43 +1500,270 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 record.args()
             )
         })
-        .init();
+        .target(env_logger::Target::Pipe(Box::new(dashboard::LogWriter)))
+        .build();
+    let file_log = match logfile::LogFileOptions::resolve(&cli, &config.log)? {
+        Some(options) => Some(Arc::new(logfile::FileLog::open(options)?)),
+        None => None,
+    };
+    logfile::init(console_logger, file_log.clone());
 
-    if cli.url.is_empty() && cli.content_id.is_empty() && cli.input_file.is_none() {
-        return Err(AppError::InvalidInput("必须至少提供一个输入源 (-u, -c, 或 -i)".into()).into());
+    let settings = ClientSettings::resolve(&cli, &config)?;
+    if let Some(path) = &cli.har {
+        let _ = HAR.set((path.clone(), Arc::new(HarRecorder::new())));
     }
-    
-    let token = handle_token_input(&cli).await?;
-    let mut final_args = cli.clone();
-    final_args.token = Some(token);
-    let final_args = Arc::new(final_args);
-    
-    let download_items = collect_download_items(&final_args)?;
-    let is_batch = download_items.len() > 1;
-    let dest_folder = Arc::new(determine_output_dir(&final_args, is_batch).await?);
-    
-    let multi_progress = Arc::new(MultiProgress::new());
-    let client = Arc::new(Client::new());
-    let semaphore = Arc::new(Semaphore::new(final_args.max_concurrent_downloads));
-    let mut tasks = Vec::new();
-    
-    for item in download_items {
-        let permit = semaphore.clone().acquire_owned().await?;
-        let client = client.clone();
-        let args = final_args.clone();
-        let dest = dest_folder.clone();
-        let mp = multi_progress.clone();
-        
-        tasks.push(tokio::spawn(async move {
-            let result = process_single_task(client, args, item, dest, mp).await;
-            drop(permit); // 明确释放信号量许可
-            result
-        }));
+    if cli.token_file.is_none() { cli.token_file = config.token.file.clone(); }
+    if cli.token_helper.is_none() { cli.token_helper = config.token.helper.clone(); }
+
+    if let Some(Command::Token { action }) = &cli.command {
+        return Ok(run_token_command(&cli, action, &config, &settings).await?);
     }
+
+    if let Some(Command::Ctl { action }) = &cli.command {
+        return Ok(run_ctl_command(&cli, action).await?);
+    }
+
+    if let Some(Command::Serve { listen, jobs_file }) = &cli.command {
+        let jobs_path = jobs_file.clone().or_else(daemon::default_jobs_path)
+            .ok_or_else(|| AppError::Config(tr!("run.no_jobs_file")))?;
+        let pool = build_token_pool(&cli, &config.accounts).await?;
+        let dest_folder = determine_output_dir(&cli, true).await?;
+        // 输出位置已确定为目录, 文件名一律取自教材详情
+        let mut args = cli.clone();
+        args.output = None;
+        let names = account_names(&cli, &config.accounts).to_vec();
+        let builder = client_builder(&settings).token_pool(pool);
+        return Ok(daemon::serve(builder, Arc::new(args), names, dest_folder, *listen, &jobs_path).await?);
+    }
+
+    let resume_dir = match &cli.command {
+        Some(Command::Resume { dir }) => Some(dir.clone()),
+        _ => None,
+    };
+    let browsed = match &cli.command {
+        Some(Command::Browse) => {
+            let items = browse_catalog(&settings).await?;
+            if items.is_empty() {
+                info!("{}", tr!("browse.nothing_selected", SYMBOL_INFO));
+                return Ok(());
+            }
+            Some(items)
+        }
+        _ => None,
+    };
+    if resume_dir.is_none() && browsed.is_none() && cli.url.is_empty() && cli.content_id.is_empty() && cli.input_file.is_none() && cli.retry_failed.is_none() {
+        return Err(AppError::InvalidInput(tr!("input.no_source")).into());
+    }
+
+    // 离线时不下载, 也就不需要 Token
+    let pool = if settings.offline {
+        TokenPool::new(Vec::new(), Default::default(), Duration::ZERO)
+    } else {
+        build_token_pool(&cli, &config.accounts).await?
+    };
+    let abort = CancellationToken::new();
+    let shutdown = abort.child_token();
+    let controller = Arc::new(control::Controller::new(cli.max_concurrent_downloads));
+    if cli.dashboard && !io::stdout().is_terminal() {
+        warn!("{}", tr!("run.not_a_terminal", SYMBOL_WARNING));
+    }
+    let dashboard = (cli.dashboard && io::stdout().is_terminal())
+        .then(|| Arc::new(dashboard::Dashboard::new(controller.clone(), shutdown.clone(), abort.clone())));
+    let event_sink: Option<Arc<dyn EventSink>> = match (&dashboard, cli.progress.resolve()) {
+        (Some(dashboard), _) => Some(dashboard.clone()),
+        (None, output::ProgressMode::Plain) => Some(Arc::new(output::PlainReporter::default())),
+        (None, output::ProgressMode::Jsonl) => Some(Arc::new(output::JsonLinesReporter)),
+        (None, output::ProgressMode::None) => None,
+        (None, _) => Some(Arc::new(ProgressReporter::new(MultiProgress::new(), pool.len() > 1, cli.compact))),
+    };
+    // 日志文件同时记录下载事件
+    let event_sink = match (event_sink, &file_log) {
+        (Some(sink), Some(file_log)) => Some(Arc::new(vec![sink, file_log.clone() as Arc<dyn EventSink>]) as Arc<dyn EventSink>),
+        (None, Some(file_log)) => Some(file_log.clone() as Arc<dyn EventSink>),
+        (sink, None) => sink,
+    };
+    let mut builder = client_builder(&settings)
+        .token_pool(pool)
+        .cancellation(abort.clone());
+    if let Some(sink) = event_sink {
+        builder = builder.event_sink(sink);
+    }
+    let client = builder.build()?;
+    let final_args = Arc::new(cli.clone());
     
-    let results = futures::future::join_all(tasks).await;
+    let (download_items, dest_folder, is_batch) = match &resume_dir {
+        Some(dir) => (journal::Journal::load_items(dir)?, dir.clone(), true),
+        None => {
+            let items = match browsed {
+                Some(items) => items,
+                None => collect_download_items(&final_args)?,
+            };
+            let is_batch = items.len() > 1;
+            let dest_folder = determine_output_dir(&final_args, is_batch).await?;
+            (items, dest_folder, is_batch)
+        }
+    };
+
+    let mut results = Vec::new();
+    let (journal, queue) = if is_batch && !final_args.no_journal {
+        match journal::Journal::open(&dest_folder, &download_items) {
+            Ok((journal, state)) => {
+                let queue = resume_queue(download_items, &state, &mut results);
+                if !results.is_empty() || !state.failed.is_empty() {
+                    info!("{}", tr!("run.resuming", SYMBOL_INFO, results.len(), state.failed.len()));
+                }
+                (Some(Arc::new(journal)), queue)
+            }
+            Err(e) => {
+                warn!("{}", tr!("run.journal_unavailable", SYMBOL_WARNING, e));
+                (None, download_items.into())
+            }
+        }
+    } else {
+        (None, download_items.into())
+    };
+    let mut queue: VecDeque<DownloadItem> = queue;
+
+    // 仪表盘占用终端时无法提示输入新的 Token
+    let interactive = !final_args.no_input && io::stdin().is_terminal() && dashboard.is_none();
+    let control = (is_batch || dashboard.is_some()).then_some(controller);
+    #[cfg(unix)]
+    let control_socket = match &control {
+        Some(controller) => {
+            let path = final_args.control_socket.clone().unwrap_or_else(control::default_socket_path);
+            match control::listen(controller.clone(), path.clone()).await {
+                Ok(socket) => {
+                    info!("{}", tr!("run.control_socket", SYMBOL_INFO, path.display()));
+                    Some(socket)
+                }
+                Err(e) => {
+                    warn!("{}", tr!("run.control_unavailable", SYMBOL_WARNING, e));
+                    None
+                }
+            }
+        }
+        None => None,
+    };
+
+    if let Some(first) = queue.front().filter(|_| !final_args.skip_token_check && !settings.offline) {
+        match preflight_accounts(&client, &final_args, &first.0, interactive).await {
+            Ok(()) => {}
+            Err(AppError::TokenExpired) => {
+                error!("{}", tr!("run.token_expired_before_start", SYMBOL_ERROR));
+                exit(EXIT_TOKEN_INVALID);
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+    for (content_id, _) in &queue {
+        client.emit(Event::Queued { content_id: content_id.clone() });
+    }
+    let mut token_aborted = false;
+    let started_at = chrono::Local::now().to_rfc3339();
+    spawn_signal_handler(shutdown.clone(), abort);
+    // 仪表盘显示期间, 全部任务结束后保持等待, 以便重试失败的项, 直到用户按 q 退出
+    let dashboard_ui = match (&dashboard, &control) {
+        (Some(dashboard), Some(control)) => {
+            control.set_keep_open(true);
+            let dashboard = dashboard.clone();
+            Some(tokio::task::spawn_blocking(move || dashboard.run()))
+        }
+        _ => None,
+    };
+
+    loop {
+        let ctx = Arc::new(TaskContext {
+            client: client.clone(),
+            args: final_args.clone(),
+            dest_folder: dest_folder.clone(),
+            is_batch,
+            breaker: AuthBreaker::default(),
+            shutdown: shutdown.clone(),
+            journal: journal.clone(),
+            control: control.clone(),
+        });
+        let round = run_download_round(ctx.clone(), &mut queue).await;
 
-    process_download_results(results);
+        if shutdown.is_cancelled() {
+            results.extend(round);
+            results.extend(queue.drain(..).map(|item| TaskResult::new(item, String::new(), DownloadStatus::Cancelled)));
+            break;
+        }
+        if !ctx.breaker.is_tripped() {
+            results.extend(round);
+            break;
+        }
+        if !interactive {
+            error!("{}", tr!("run.token_expired_stopped", SYMBOL_ERROR, queue.len()));
+            results.extend(round);
+            results.extend(queue.drain(..).map(|item| TaskResult::new(item, String::new(), DownloadStatus::Aborted)));
+            token_aborted = true;
+            break;
+        }
+
+        // Token 失败和尚未开始的项目放回队首, 换 Token 后优先处理
+        let (requeue, done): (Vec<_>, Vec<_>) = round.into_iter()
+            .partition(|r| matches!(r.status, DownloadStatus::TokenError | DownloadStatus::Aborted));
+        results.extend(done);
+        for result in requeue.iter().rev() { queue.push_front(result.item()); }
+
+        warn!("{}", tr!("run.token_expired_paused", SYMBOL_WARNING, queue.len()));
+        renew_unauthorized_account(&final_args, client.pool()).await?;
+    }
+    if let Some(dashboard) = &dashboard {
+        dashboard.close();
+    }
+    if let Some(ui) = dashboard_ui {
+        match ui.await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => warn!("{}", tr!("run.dashboard_error", SYMBOL_WARNING, e)),
+            Err(e) => warn!("{}", tr!("run.dashboard_panicked", SYMBOL_WARNING, e)),
+        }
+    }
+
+    if is_batch && !final_args.no_retry_pass && !settings.offline && !token_aborted && !shutdown.is_cancelled() {
+        let delay = final_args.retry_pass_delay.or(config.retry.pass_delay_secs).unwrap_or(DEFAULT_RETRY_PASS_DELAY_SECS);
+        let ctx = Arc::new(TaskContext {
+            client: client.clone(),
+            args: final_args.clone(),
+            dest_folder: dest_folder.clone(),
+            is_batch,
+            breaker: AuthBreaker::default(),
+            shutdown: shutdown.clone(),
+            journal: journal.clone(),
+            control: control.clone(),
+        });
+        retry_transient_failures(ctx.clone(), &mut results, Duration::from_secs(delay)).await;
+        token_aborted = ctx.breaker.is_tripped();
+    }
+
+    #[cfg(unix)]
+    drop(control_socket);
+    let interrupted = shutdown.is_cancelled();
+    process_download_results(&results);
+    if let Some(path) = &final_args.manifest {
+        match manifest::write(path, &started_at, interrupted, &dest_folder, &results) {
+            Ok(()) => info!("{}", tr!("summary.manifest_written", SYMBOL_INFO, path.display())),
+            Err(e) => error!("{}", tr!("summary.manifest_failed", SYMBOL_ERROR, e)),
+        }
+    }
+    if let Some(path) = &final_args.failed_out {
+        match manifest::write_failed_list(path, &results) {
+            Ok(count) => info!("{}", tr!("summary.failed_list_written", SYMBOL_INFO, count, path.display())),
+            Err(e) => error!("{}", tr!("summary.failed_list_failed", SYMBOL_ERROR, e)),
+        }
+    }
+    if let Some(journal) = &journal
+        && interrupted
+    {
+        info!("{}", tr!("summary.resume_hint", SYMBOL_INFO, journal.path().display(), dest_folder.display()));
+    }
+    if interrupted {
+        exit(EXIT_INTERRUPTED);
+    }
+    if token_aborted {
+        exit(EXIT_TOKEN_INVALID);
+    }
     
     Ok(())
-}
\ No newline at end of file
+}
@@ -0,0 +1,94 @@
+// src/manifest.rs
+
+use crate::TaskResult;
+use sed_dl::{tr, AppError, DownloadStatus};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// 一次批量下载的结果清单 (`--manifest`), 供脚本或其他工具读取
+#[derive(Serialize)]
+struct Manifest<'a> {
+    started_at: &'a str,
+    finished_at: String,
+    /// 是否被 Ctrl-C 中断
+    interrupted: bool,
+    output_dir: &'a Path,
+    items: Vec<ManifestItem<'a>>,
+}
+
+#[derive(Serialize)]
+struct ManifestItem<'a> {
+    content_id: &'a str,
+    input: &'a str,
+    #[serde(skip_serializing_if = "str::is_empty")]
+    file: &'a str,
+    status: DownloadStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    account: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    http_code: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mirror: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<&'a str>,
+}
+
+/// 写入清单。先写临时文件再重命名, 中断时不会留下半个文件。
+pub fn write(path: &Path, started_at: &str, interrupted: bool, output_dir: &Path, results: &[TaskResult]) -> Result<(), AppError> {
+    let items = results.iter().map(|r| {
+        let failure = r.failure.as_ref();
+        ManifestItem {
+            content_id: &r.content_id,
+            input: &r.original,
+            file: &r.filename,
+            status: r.status,
+            account: r.account.as_deref(),
+            http_code: failure.and_then(|f| f.http_code),
+            mirror: failure.and_then(|f| f.mirror.as_deref()),
+            error: failure.and_then(|f| f.message.as_deref()),
+        }
+    }).collect();
+    let manifest = Manifest {
+        started_at,
+        finished_at: chrono::Local::now().to_rfc3339(),
+        interrupted,
+        output_dir,
+        items,
+    };
+    let tmp_path = path.with_extension("tmp");
+    std::fs::write(&tmp_path, serde_json::to_string_pretty(&manifest)?)?;
+    std::fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+#[derive(Deserialize)]
+struct SavedManifest {
+    items: Vec<SavedItem>,
+}
+
+#[derive(Deserialize)]
+struct SavedItem {
+    input: String,
+    status: DownloadStatus,
+}
+
+/// 读取之前写入的清单, 返回其中未成功的项的原始输入 (`--retry-failed`)
+pub fn failed_inputs(path: &Path) -> Result<Vec<String>, AppError> {
+    let content = std::fs::read_to_string(path)?;
+    let manifest: SavedManifest = serde_json::from_str(&content)
+        .map_err(|e| AppError::InvalidInput(tr!("manifest.invalid", path.display(), e)))?;
+    Ok(manifest.items.into_iter().filter(|i| !i.status.is_success()).map(|i| i.input).collect())
+}
+
+/// 把未成功的项写成 `-i` 可读取的列表 (`--failed-out`), 失败原因写在注释行中
+pub fn write_failed_list(path: &Path, results: &[TaskResult]) -> Result<usize, AppError> {
+    let mut content = String::new();
+    let mut count = 0;
+    for r in results.iter().filter(|r| !r.status.is_success()) {
+        let message = r.failure.as_ref().and_then(|f| f.message.as_deref()).unwrap_or(r.status.label());
+        content.push_str(&format!("# [{}] {}\n{}\n", r.status.code(), message.replace('\n', " "), r.original));
+        count += 1;
+    }
+    std::fs::write(path, content)?;
+    Ok(count)
+}
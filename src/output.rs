@@ -0,0 +1,133 @@
+// src/output.rs
+
+//! `--progress` 和 `--color`: 非交互环境 (CI、cron) 下的输出方式
+
+use crate::{SYMBOL_ERROR, SYMBOL_INFO, SYMBOL_SUCCESS, SYMBOL_WARNING};
+use clap::ValueEnum;
+use indicatif::HumanBytes;
+use log::{error, info, warn};
+use sed_dl::{tr, Event, EventSink};
+use std::collections::HashMap;
+use std::io::{self, IsTerminal, Write};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// 下载进度的显示方式
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ProgressMode {
+    /// 标准错误是终端时显示进度条, 否则同 plain
+    #[default]
+    Auto,
+    /// 进度条
+    Bars,
+    /// 逐行输出开始、进度 (每 25%)、重试和结果, 适合写入日志
+    Plain,
+    /// 不输出进度, 只保留日志和汇总
+    None,
+    /// 在标准输出逐行输出 JSON 格式的事件, 供脚本解析
+    Jsonl,
+}
+
+impl ProgressMode {
+    /// 把 `auto` 解析为具体的方式
+    pub fn resolve(self) -> ProgressMode {
+        match self {
+            ProgressMode::Auto if io::stderr().is_terminal() => ProgressMode::Bars,
+            ProgressMode::Auto => ProgressMode::Plain,
+            mode => mode,
+        }
+    }
+}
+
+/// 是否输出彩色文字
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ColorMode {
+    /// 标准错误是终端且未设置 NO_COLOR 时使用颜色
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// 按 `--color` 统一设置 `colored` 和进度条的颜色输出
+pub fn init_color(mode: ColorMode) {
+    let enabled = match mode {
+        ColorMode::Always => true,
+        ColorMode::Never => false,
+        ColorMode::Auto => std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty()) && io::stderr().is_terminal(),
+    };
+    colored::control::set_override(enabled);
+    console::set_colors_enabled(enabled);
+    console::set_colors_enabled_stderr(enabled);
+}
+
+/// `--progress plain`: 通过日志逐行输出下载进度
+#[derive(Default)]
+pub struct PlainReporter {
+    items: Mutex<HashMap<String, PlainItem>>,
+}
+
+struct PlainItem {
+    filename: String,
+    total_bytes: Option<u64>,
+    /// 已输出的进度, 以 25% 计
+    reported: u64,
+}
+
+impl EventSink for PlainReporter {
+    fn on_event(&self, event: &Event) {
+        let mut items = self.items.lock().unwrap();
+        match event {
+            Event::DownloadStarted { content_id, filename, total_bytes, attempt, .. } => {
+                let size = total_bytes.map_or(tr!("output.size_unknown"), |t| HumanBytes(t).to_string());
+                if *attempt > 1 {
+                    info!("{}", tr!("output.attempt_started", SYMBOL_INFO, attempt, filename, size));
+                } else {
+                    info!("{}", tr!("output.started", SYMBOL_INFO, filename, size));
+                }
+                items.insert(content_id.clone(), PlainItem { filename: filename.clone(), total_bytes: *total_bytes, reported: 0 });
+            }
+            Event::Progress { content_id, bytes, total_bytes } => {
+                let Some(item) = items.get_mut(content_id) else { return };
+                let Some(total) = item.total_bytes.or(*total_bytes).filter(|&t| t > 0) else { return };
+                let quarter = bytes * 4 / total;
+                if quarter > item.reported && quarter < 4 {
+                    item.reported = quarter;
+                    info!("{}", tr!("output.progress", SYMBOL_INFO, item.filename, quarter * 25, HumanBytes(*bytes), HumanBytes(total)));
+                }
+            }
+            Event::Retry { content_id, attempt, delay_ms, reason } => {
+                if let Some(item) = items.get(content_id) {
+                    warn!("{}", tr!("progress.retry", SYMBOL_WARNING, item.filename, attempt, reason, format!("{:.1?}", Duration::from_millis(*delay_ms))));
+                }
+            }
+            Event::AccountSwitched { content_id, account, http_status } => {
+                if let Some(item) = items.get(content_id) {
+                    warn!("{}", tr!("progress.account_switched", SYMBOL_WARNING, item.filename, account, http_status));
+                }
+            }
+            Event::Finished { content_id, status, error, .. } => {
+                // 没有开始下载的项目 (跳过、获取详情失败) 已在日志中输出
+                let Some(item) = items.remove(content_id) else { return };
+                match error {
+                    _ if status.is_success() => info!("{} '{}' {}", SYMBOL_SUCCESS, item.filename, status.label()),
+                    Some(error) => error!("{} '{}' {}: {}", SYMBOL_ERROR, item.filename, status.label(), error),
+                    None => error!("{} '{}' {}", SYMBOL_ERROR, item.filename, status.label()),
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// `--progress jsonl`: 每个事件在标准输出写一行 JSON
+pub struct JsonLinesReporter;
+
+impl EventSink for JsonLinesReporter {
+    fn on_event(&self, event: &Event) {
+        let Ok(line) = serde_json::to_string(event) else { return };
+        let mut stdout = io::stdout().lock();
+        // 标准输出被关闭 (如管道另一端已退出) 时忽略
+        let _ = writeln!(stdout, "{}", line).and_then(|_| stdout.flush());
+    }
+}
@@ -0,0 +1,44 @@
+// src/preflight.rs
+
+use crate::{mirror_of, tr, AppError};
+use reqwest::header::RANGE;
+use reqwest::{Client, RequestBuilder, StatusCode};
+use std::time::Duration;
+
+static PROBE_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Token 预检结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenState {
+    Valid,
+    Expired,
+    /// Token 有效, 但账号无权访问探测用的资源
+    Forbidden,
+    /// 探测资源本身不可用 (如 404), 无法判断 Token 状态
+    Inconclusive(StatusCode),
+}
+
+/// 对带 accessToken 的资源地址发起只取 1 字节的请求, 判断 Token 状态。
+pub async fn probe_token(client: &Client, download_url: &str) -> Result<TokenState, AppError> {
+    let response = probe_request(client, download_url)
+        .send().await
+        .map_err(|e| AppError::transfer(download_url, e))?;
+    Ok(token_state(response.status(), download_url))
+}
+
+pub(crate) fn probe_request(client: &Client, download_url: &str) -> RequestBuilder {
+    client.get(download_url)
+        .header(RANGE, "bytes=0-0")
+        .timeout(PROBE_TIMEOUT)
+}
+
+pub(crate) fn token_state(status: StatusCode, download_url: &str) -> TokenState {
+    let state = match status {
+        s if s.is_success() => TokenState::Valid,
+        StatusCode::UNAUTHORIZED => TokenState::Expired,
+        StatusCode::FORBIDDEN => TokenState::Forbidden,
+        s => TokenState::Inconclusive(s),
+    };
+    log::debug!("{}", tr!("preflight.debug_state", mirror_of(download_url), format!("{:?}", state)));
+    state
+}
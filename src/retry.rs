@@ -0,0 +1,93 @@
+// src/retry.rs
+
+use rand::Rng;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::StatusCode;
+use std::time::Duration;
+
+// --- 重试策略 ---
+
+/// 单个阶段 (获取详情 / 下载文件) 的重试策略。
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// 总尝试次数 (包含首次请求), 至少为 1。
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    pub jitter: bool,
+}
+
+impl RetryPolicy {
+    /// 计算第 `attempt` 次重试前的等待时间 (从 1 开始计数)。
+    /// 服务器给出的 `Retry-After` 优先于指数退避, 但不超过 `retry_after_cap`。
+    pub fn delay_for(&self, attempt: u32, retry_after: Option<Duration>, retry_after_cap: Duration) -> Duration {
+        if let Some(wait) = retry_after {
+            return wait.min(retry_after_cap);
+        }
+        let exp = self.base_delay.saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)));
+        let capped = exp.min(self.max_delay);
+        if self.jitter && !capped.is_zero() {
+            // "等比抖动": 在 [d/2, d] 之间随机取值, 避免大量任务同时重试
+            let half = capped / 2;
+            half + rand::thread_rng().gen_range(Duration::ZERO..=half)
+        } else {
+            capped
+        }
+    }
+}
+
+/// 所有阶段的重试配置。
+#[derive(Debug, Clone)]
+pub struct RetryConfig {
+    pub details: RetryPolicy,
+    pub download: RetryPolicy,
+    /// 服务器 `Retry-After` 的最长等待上限
+    pub max_retry_after: Duration,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        let base = RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            jitter: true,
+        };
+        Self { details: base.clone(), download: base, max_retry_after: Duration::from_secs(120) }
+    }
+}
+
+// --- HTTP 状态码分类 ---
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusClass {
+    /// 认证失败, 重试无意义且应立即上报
+    Unauthorized,
+    /// 暂时性错误 (408/429/5xx), 可以重试
+    Transient,
+    /// 永久性错误 (404/410 及其他 4xx), 不再重试
+    Permanent,
+}
+
+pub fn classify_status(status: StatusCode) -> StatusClass {
+    match status {
+        StatusCode::UNAUTHORIZED => StatusClass::Unauthorized,
+        StatusCode::REQUEST_TIMEOUT | StatusCode::TOO_MANY_REQUESTS => StatusClass::Transient,
+        s if s.is_server_error() => StatusClass::Transient,
+        _ => StatusClass::Permanent,
+    }
+}
+
+/// 解析 429/503 响应中的 `Retry-After` 头 (秒数或 HTTP 日期)。
+pub fn retry_after(status: StatusCode, headers: &HeaderMap) -> Option<Duration> {
+    if status != StatusCode::TOO_MANY_REQUESTS && status != StatusCode::SERVICE_UNAVAILABLE {
+        return None;
+    }
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let wait = date.with_timezone(&chrono::Utc) - chrono::Utc::now();
+    Some(wait.to_std().unwrap_or(Duration::ZERO))
+}
@@ -0,0 +1,105 @@
+// src/status.rs
+
+use crate::{i18n, tr};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// 单个下载项的最终状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DownloadStatus {
+    Success,
+    SuccessNoValidation,
+    Skipped,
+    TokenError,
+    Forbidden,
+    ContentRemoved,
+    RateLimited,
+    HttpError,
+    TlsError,
+    Timeout,
+    NetworkError,
+    DiskFull,
+    PermissionDenied,
+    InvalidPdf,
+    Md5ValidationFailed,
+    SizeValidationFailed,
+    FailGetDetails,
+    Aborted,
+    Cancelled,
+    UnexpectedError,
+}
+
+impl DownloadStatus {
+    pub const ALL: [DownloadStatus; 20] = [
+        DownloadStatus::Success, DownloadStatus::SuccessNoValidation, DownloadStatus::Skipped,
+        DownloadStatus::TokenError, DownloadStatus::Forbidden, DownloadStatus::ContentRemoved,
+        DownloadStatus::RateLimited, DownloadStatus::HttpError, DownloadStatus::TlsError,
+        DownloadStatus::Timeout, DownloadStatus::NetworkError, DownloadStatus::DiskFull,
+        DownloadStatus::PermissionDenied, DownloadStatus::InvalidPdf, DownloadStatus::Md5ValidationFailed,
+        DownloadStatus::SizeValidationFailed, DownloadStatus::FailGetDetails, DownloadStatus::Aborted,
+        DownloadStatus::Cancelled, DownloadStatus::UnexpectedError,
+    ];
+
+    /// 由错误码还原状态, 如 `"E_TOKEN"`
+    pub fn from_code(code: &str) -> Option<DownloadStatus> {
+        Self::ALL.into_iter().find(|s| s.code() == code)
+    }
+
+    pub fn is_success(self) -> bool {
+        matches!(self, DownloadStatus::Success | DownloadStatus::SuccessNoValidation | DownloadStatus::Skipped)
+    }
+
+    /// 临时性的失败 (限流、服务端错误、超时、网络中断), 稍后重试可能成功
+    pub fn is_transient(self) -> bool {
+        matches!(self, DownloadStatus::RateLimited | DownloadStatus::HttpError | DownloadStatus::Timeout | DownloadStatus::NetworkError)
+    }
+
+    /// 稳定的错误码, 供脚本解析汇总输出。已发布的错误码不要修改。
+    pub fn code(self) -> &'static str {
+        match self {
+            DownloadStatus::Success => "OK",
+            DownloadStatus::SuccessNoValidation => "OK_UNVERIFIED",
+            DownloadStatus::Skipped => "SKIPPED",
+            DownloadStatus::TokenError => "E_TOKEN",
+            DownloadStatus::Forbidden => "E_FORBIDDEN",
+            DownloadStatus::ContentRemoved => "E_REMOVED",
+            DownloadStatus::RateLimited => "E_RATE_LIMITED",
+            DownloadStatus::HttpError => "E_HTTP",
+            DownloadStatus::TlsError => "E_TLS",
+            DownloadStatus::Timeout => "E_TIMEOUT",
+            DownloadStatus::NetworkError => "E_NETWORK",
+            DownloadStatus::DiskFull => "E_DISK_FULL",
+            DownloadStatus::PermissionDenied => "E_PERMISSION",
+            DownloadStatus::InvalidPdf => "E_INVALID_PDF",
+            DownloadStatus::Md5ValidationFailed => "E_MD5",
+            DownloadStatus::SizeValidationFailed => "E_SIZE",
+            DownloadStatus::FailGetDetails => "E_DETAILS",
+            DownloadStatus::Aborted => "E_ABORTED",
+            DownloadStatus::Cancelled => "E_CANCELLED",
+            DownloadStatus::UnexpectedError => "E_UNEXPECTED",
+        }
+    }
+
+    /// 简短的说明, 随界面语言变化
+    pub fn label(self) -> &'static str {
+        i18n::message(&format!("status.label.{}", self.code())).unwrap_or(self.code())
+    }
+
+    /// 面向用户的处理建议
+    pub fn hint(self) -> Option<&'static str> {
+        i18n::message(&format!("status.hint.{}", self.code()))
+    }
+}
+
+/// 序列化为稳定的错误码, 如 `"E_TOKEN"`
+impl Serialize for DownloadStatus {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.code())
+    }
+}
+
+impl<'de> Deserialize<'de> for DownloadStatus {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let code = String::deserialize(deserializer)?;
+        DownloadStatus::from_code(&code).ok_or_else(|| serde::de::Error::custom(tr!("status.unknown_code", code)))
+    }
+}
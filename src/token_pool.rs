@@ -0,0 +1,168 @@
+// src/token_pool.rs
+
+use crate::tr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 多账号之间的调度策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PoolStrategy {
+    /// 轮流使用各个账号
+    #[default]
+    RoundRobin,
+    /// 优先使用最久未使用的账号
+    Lru,
+}
+
+/// 账号被暂停使用的原因
+#[derive(Debug, Clone, Copy)]
+pub enum ParkReason {
+    /// 401: Token 失效, 直到替换 Token 前不再使用
+    Unauthorized,
+    /// 403: 短暂暂停, 期间由其他账号再试
+    Forbidden,
+    /// 429: 按 Retry-After 或冷却周期暂停
+    RateLimited(Option<Duration>),
+}
+
+/// 403 时暂停的最长时间。403 通常只针对单个资源, 不必等满整个冷却周期。
+const FORBIDDEN_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// 从账号池中借出的账号
+#[derive(Debug, Clone)]
+pub struct Lease {
+    pub index: usize,
+    pub name: String,
+    pub token: String,
+}
+
+#[derive(Debug)]
+struct Account {
+    name: String,
+    token: String,
+    last_used: Option<Instant>,
+    parked_until: Option<Instant>,
+    unauthorized: bool,
+}
+
+impl Account {
+    fn is_available(&self, now: Instant) -> bool {
+        !self.unauthorized && self.parked_until.is_none_or(|until| until <= now)
+    }
+}
+
+#[derive(Debug)]
+struct PoolState {
+    accounts: Vec<Account>,
+    cursor: usize,
+}
+
+/// 多账号 Token 池: 在账号之间分摊下载, 出错的账号暂停一段时间。
+#[derive(Debug)]
+pub struct TokenPool {
+    state: Mutex<PoolState>,
+    strategy: PoolStrategy,
+    cooldown: Duration,
+}
+
+impl TokenPool {
+    pub fn new(accounts: Vec<(String, String)>, strategy: PoolStrategy, cooldown: Duration) -> Self {
+        let accounts = accounts.into_iter()
+            .map(|(name, token)| Account { name, token, last_used: None, parked_until: None, unauthorized: false })
+            .collect();
+        TokenPool { state: Mutex::new(PoolState { accounts, cursor: 0 }), strategy, cooldown }
+    }
+
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().accounts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 按调度策略借出一个可用账号, 没有可用账号时返回 `None`
+    pub fn acquire(&self) -> Option<Lease> {
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+        let count = state.accounts.len();
+        let index = match self.strategy {
+            PoolStrategy::RoundRobin => (0..count)
+                .map(|offset| (state.cursor + offset) % count)
+                .find(|&i| state.accounts[i].is_available(now))?,
+            PoolStrategy::Lru => (0..count)
+                .filter(|&i| state.accounts[i].is_available(now))
+                .min_by_key(|&i| state.accounts[i].last_used)?,
+        };
+        state.cursor = (index + 1) % count;
+        let account = &mut state.accounts[index];
+        account.last_used = Some(now);
+        Some(Lease { index, name: account.name.clone(), token: account.token.clone() })
+    }
+
+    pub fn park(&self, index: usize, reason: ParkReason) {
+        let mut state = self.state.lock().unwrap();
+        let Some(account) = state.accounts.get_mut(index) else { return };
+        match reason {
+            ParkReason::Unauthorized => account.unauthorized = true,
+            ParkReason::Forbidden => account.parked_until = Some(Instant::now() + self.cooldown.min(FORBIDDEN_COOLDOWN)),
+            ParkReason::RateLimited(after) => {
+                account.parked_until = Some(Instant::now() + after.unwrap_or(self.cooldown));
+            }
+        }
+        log::debug!("{}", tr!("accounts.parked", account.name, format!("{:?}", reason)));
+    }
+
+    /// 没有可用账号时, 最早结束冷却的账号还需等待的时间。所有账号都已失效时返回 `None`。
+    pub fn next_available_in(&self) -> Option<Duration> {
+        let now = Instant::now();
+        self.state.lock().unwrap().accounts.iter()
+            .filter(|a| !a.unauthorized)
+            .map(|a| a.parked_until.map_or(Duration::ZERO, |until| until.saturating_duration_since(now)))
+            .min()
+    }
+
+    /// 所有账号的 Token 都已失效
+    pub fn all_unauthorized(&self) -> bool {
+        self.state.lock().unwrap().accounts.iter().all(|a| a.unauthorized)
+    }
+
+    /// 尚未失效的账号, 用于预检
+    pub fn accounts(&self) -> Vec<Lease> {
+        self.state.lock().unwrap().accounts.iter().enumerate()
+            .filter(|(_, a)| !a.unauthorized)
+            .map(|(index, a)| Lease { index, name: a.name.clone(), token: a.token.clone() })
+            .collect()
+    }
+
+    /// 第一个 Token 已失效的账号名称
+    pub fn first_unauthorized(&self) -> Option<String> {
+        self.state.lock().unwrap().accounts.iter().find(|a| a.unauthorized).map(|a| a.name.clone())
+    }
+
+    /// 用重新读取的 Token 更新同名账号: Token 有变化的账号恢复使用, 返回更新了的账号名称
+    pub fn reload(&self, accounts: &[(String, String)]) -> Vec<String> {
+        let mut state = self.state.lock().unwrap();
+        let mut reloaded = Vec::new();
+        for (name, token) in accounts {
+            for account in state.accounts.iter_mut().filter(|a| a.name == *name && a.token != *token) {
+                account.token = token.clone();
+                account.unauthorized = false;
+                account.parked_until = None;
+                reloaded.push(name.clone());
+            }
+        }
+        reloaded
+    }
+
+    /// 替换指定账号的 Token 并恢复使用
+    pub fn replace_token(&self, name: &str, token: &str) {
+        let mut state = self.state.lock().unwrap();
+        for account in state.accounts.iter_mut().filter(|a| a.name == name) {
+            account.token = token.to_string();
+            account.unauthorized = false;
+            account.parked_until = None;
+        }
+    }
+}
@@ -0,0 +1,205 @@
+// src/token_store.rs
+
+use crate::{tr, AppError};
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use argon2::Argon2;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// 未指定名称时使用的 Token 名
+pub static DEFAULT_TOKEN_NAME: &str = "default";
+/// 解密 Token 时读取口令的环境变量名
+pub static PASSPHRASE_ENV: &str = "SED_DL_PASSPHRASE";
+
+/// 用户配置目录下的 Token 存储, 如 Linux 下的 `~/.config/sed-dl/tokens.json`。
+/// 文件权限为 0600, 可选用口令加密 (Argon2id + AES-256-GCM)。
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct TokenStore {
+    #[serde(default)]
+    default: Option<String>,
+    #[serde(default)]
+    tokens: BTreeMap<String, StoredToken>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StoredToken {
+    #[serde(flatten)]
+    secret: Secret,
+    pub created_at: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+enum Secret {
+    Plain { token: String },
+    Encrypted { salt: String, nonce: String, ciphertext: String },
+}
+
+impl StoredToken {
+    pub fn is_encrypted(&self) -> bool {
+        matches!(self.secret, Secret::Encrypted { .. })
+    }
+}
+
+pub fn default_store_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("sed-dl").join("tokens.json"))
+}
+
+impl TokenStore {
+    /// 从默认位置加载, 文件不存在时返回空存储。
+    pub fn load() -> Result<TokenStore, AppError> {
+        let path = default_store_path()
+            .ok_or_else(|| AppError::TokenStore(tr!("token_store.no_config_dir")))?;
+        Self::load_from(&path)
+    }
+
+    pub fn load_from(path: &Path) -> Result<TokenStore, AppError> {
+        let mut store = match std::fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str::<TokenStore>(&content)
+                .map_err(|e| AppError::TokenStore(tr!("file.bad_format", path.display(), e)))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => TokenStore::default(),
+            Err(e) => return Err(AppError::TokenStore(tr!("file.read_failed", path.display(), e))),
+        };
+        store.path = path.to_path_buf();
+        Ok(store)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 原子地写回文件 (先写临时文件再重命名), 目录权限 0700, 文件权限 0600。
+    pub fn save(&self) -> Result<(), AppError> {
+        let err = |e: std::io::Error| AppError::TokenStore(tr!("file.write_failed", self.path.display(), e));
+        if let Some(dir) = self.path.parent() {
+            create_private_dir(dir).map_err(err)?;
+        }
+        let content = serde_json::to_string_pretty(self)?;
+        let tmp_path = self.path.with_extension("json.tmp");
+        {
+            let mut file = open_private_file(&tmp_path).map_err(err)?;
+            file.write_all(content.as_bytes()).map_err(err)?;
+            file.sync_all().map_err(err)?;
+        }
+        std::fs::rename(&tmp_path, &self.path).map_err(err)
+    }
+
+    pub fn default_name(&self) -> Option<&str> {
+        self.default.as_deref()
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = (&String, &StoredToken)> {
+        self.tokens.iter()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&StoredToken> {
+        self.tokens.get(name)
+    }
+
+    /// 保存 Token; 提供口令时加密存储。第一个保存的 Token 自动成为默认值。
+    pub fn set(&mut self, name: &str, token: &str, passphrase: Option<&str>) -> Result<(), AppError> {
+        let secret = match passphrase {
+            Some(p) => encrypt(token, p)?,
+            None => Secret::Plain { token: token.to_string() },
+        };
+        let created_at = chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        self.tokens.insert(name.to_string(), StoredToken { secret, created_at });
+        if self.default.is_none() {
+            self.default = Some(name.to_string());
+        }
+        Ok(())
+    }
+
+    pub fn remove(&mut self, name: &str) -> bool {
+        let removed = self.tokens.remove(name).is_some();
+        if removed && self.default.as_deref() == Some(name) {
+            self.default = None;
+        }
+        removed
+    }
+
+    pub fn set_default(&mut self, name: &str) -> Result<(), AppError> {
+        if !self.tokens.contains_key(name) {
+            return Err(AppError::TokenStore(tr!("token.not_found", name)));
+        }
+        self.default = Some(name.to_string());
+        Ok(())
+    }
+
+    /// 读取 Token 明文。加密的 Token 会调用 `passphrase` 获取口令。
+    pub fn reveal(&self, name: &str, passphrase: impl FnOnce() -> Result<String, AppError>) -> Result<String, AppError> {
+        let stored = self.get(name)
+            .ok_or_else(|| AppError::TokenStore(tr!("token.not_found", name)))?;
+        match &stored.secret {
+            Secret::Plain { token } => Ok(token.clone()),
+            Secret::Encrypted { salt, nonce, ciphertext } => decrypt(salt, nonce, ciphertext, &passphrase()?),
+        }
+    }
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; 32], AppError> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| AppError::TokenStore(tr!("token_store.key_derivation", e)))?;
+    Ok(key)
+}
+
+fn encrypt(token: &str, passphrase: &str) -> Result<Secret, AppError> {
+    let salt: [u8; 16] = rand::random();
+    let nonce: [u8; 12] = rand::random();
+    let key = derive_key(passphrase, &salt)?;
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key));
+    let ciphertext = cipher.encrypt(Nonce::from_slice(&nonce), token.as_bytes())
+        .map_err(|_| AppError::TokenStore(tr!("token_store.encrypt_failed")))?;
+    Ok(Secret::Encrypted {
+        salt: BASE64.encode(salt),
+        nonce: BASE64.encode(nonce),
+        ciphertext: BASE64.encode(ciphertext),
+    })
+}
+
+fn decrypt(salt: &str, nonce: &str, ciphertext: &str, passphrase: &str) -> Result<String, AppError> {
+    let decode = |v: &str| BASE64.decode(v).map_err(|_| AppError::TokenStore(tr!("token_store.corrupted")));
+    let (salt, nonce, ciphertext) = (decode(salt)?, decode(nonce)?, decode(ciphertext)?);
+    if nonce.len() != 12 {
+        return Err(AppError::TokenStore(tr!("token_store.corrupted")));
+    }
+    let key = derive_key(passphrase, &salt)?;
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key));
+    let plain = cipher.decrypt(Nonce::from_slice(&nonce), ciphertext.as_ref())
+        .map_err(|_| AppError::TokenStore(tr!("token_store.wrong_passphrase")))?;
+    String::from_utf8(plain).map_err(|_| AppError::TokenStore(tr!("token_store.corrupted")))
+}
+
+#[cfg(unix)]
+fn create_private_dir(dir: &Path) -> std::io::Result<()> {
+    use std::os::unix::fs::DirBuilderExt;
+    std::fs::DirBuilder::new().recursive(true).mode(0o700).create(dir)
+}
+
+#[cfg(not(unix))]
+fn create_private_dir(dir: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)
+}
+
+#[cfg(unix)]
+fn open_private_file(path: &Path) -> std::io::Result<std::fs::File> {
+    use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+    let file = std::fs::OpenOptions::new().write(true).create(true).truncate(true).mode(0o600).open(path)?;
+    // 文件已存在时 mode() 不生效, 显式收紧权限
+    file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+    Ok(file)
+}
+
+#[cfg(not(unix))]
+fn open_private_file(path: &Path) -> std::io::Result<std::fs::File> {
+    std::fs::OpenOptions::new().write(true).create(true).truncate(true).open(path)
+}
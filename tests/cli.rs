@@ -0,0 +1,227 @@
+// tests/cli.rs
+
+//! 针对模拟服务器运行命令行程序: 跳过已校验的文件、Token 失效时的退出码、批量结果清单和后台服务重新读取 Token。
+
+mod common;
+
+use common::{pdf, Book, Fault, MockServer, VALID_TOKEN};
+use serde_json::Value;
+use std::path::Path;
+use std::process::{Command, Output};
+
+const MATH: &str = "11111111-2222-3333-4444-555555555555";
+const ENGLISH: &str = "22222222-2222-3333-4444-555555555555";
+
+/// 在独立的配置和缓存目录下运行 sed-dl
+async fn run(server: &MockServer, dir: &Path, token: &str, args: &[&str]) -> Output {
+    let config = dir.join("config.toml");
+    std::fs::write(&config, "").unwrap();
+    let mut command = Command::new(env!("CARGO_BIN_EXE_sed-dl"));
+    command
+        .args(["--config", config.to_str().unwrap()])
+        .args(["--api-host", &server.base])
+        .args(["--cache-dir", dir.join("cache").to_str().unwrap()])
+        .args(["-t", token, "--no-input", "--skip-token-check", "--progress", "none", "--lang", "zh"])
+        .args(["--retry-base-delay", "1", "--no-retry-pass"])
+        .args(args)
+        .env("NO_COLOR", "1");
+    tokio::task::spawn_blocking(move || command.output().unwrap()).await.unwrap()
+}
+
+fn statuses(manifest: &Path) -> Vec<(String, String)> {
+    let manifest: Value = serde_json::from_str(&std::fs::read_to_string(manifest).unwrap()).unwrap();
+    manifest["items"].as_array().unwrap().iter()
+        .map(|item| (item["content_id"].as_str().unwrap().to_string(), item["status"].as_str().unwrap().to_string()))
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn existing_valid_file_is_skipped() {
+    let server = MockServer::start().await;
+    server.add_book(MATH, Book::new("数学", "math.pdf", pdf(10_000)));
+    let dir = tempfile::tempdir().unwrap();
+    let out = dir.path().join("out");
+    let manifest = dir.path().join("manifest.json");
+    let args = ["-c", MATH, "-o", &format!("{}/", out.display()), "--manifest", manifest.to_str().unwrap()];
+
+    let first = run(&server, dir.path(), VALID_TOKEN, &args).await;
+    assert!(first.status.success(), "{}", String::from_utf8_lossy(&first.stderr));
+    assert_eq!(statuses(&manifest), [(MATH.to_string(), "OK".to_string())]);
+
+    let second = run(&server, dir.path(), VALID_TOKEN, &args).await;
+    assert!(second.status.success());
+    assert_eq!(statuses(&manifest), [(MATH.to_string(), "SKIPPED".to_string())]);
+    assert_eq!(server.hits(&MockServer::pdf_path("math.pdf")), 1);
+
+    // 文件损坏后重新下载
+    std::fs::write(out.join("math.pdf"), b"%PDF-broken").unwrap();
+    let third = run(&server, dir.path(), VALID_TOKEN, &args).await;
+    assert!(third.status.success());
+    assert_eq!(statuses(&manifest), [(MATH.to_string(), "OK".to_string())]);
+    assert_eq!(std::fs::read(out.join("math.pdf")).unwrap(), pdf(10_000));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn expired_token_exits_with_token_error() {
+    let server = MockServer::start().await;
+    server.add_book(MATH, Book::new("数学", "math.pdf", pdf(10_000)));
+    let dir = tempfile::tempdir().unwrap();
+    let out = format!("{}/", dir.path().join("out").display());
+
+    let output = run(&server, dir.path(), "expired-token", &["-c", MATH, "-o", &out]).await;
+
+    assert_eq!(output.status.code(), Some(3), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(server.hits(&MockServer::pdf_path("math.pdf")), 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn batch_reports_each_item() {
+    let server = MockServer::start().await;
+    server.add_book(MATH, Book::new("数学", "math.pdf", pdf(10_000)));
+    let mut english = Book::new("英语", "english.pdf", pdf(10_000));
+    english.md5 = Some("0".repeat(32));
+    english.size = None;
+    server.add_book(ENGLISH, english);
+    server.fail(&MockServer::pdf_path("math.pdf"), [Fault::Status(503), Fault::Truncate(100)]);
+    let dir = tempfile::tempdir().unwrap();
+    let manifest = dir.path().join("manifest.json");
+    let out = dir.path().join("out");
+
+    let output = run(&server, dir.path(), VALID_TOKEN, &[
+        "-c", MATH, "-c", ENGLISH, "-o", out.to_str().unwrap(), "--manifest", manifest.to_str().unwrap(),
+    ]).await;
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let mut statuses = statuses(&manifest);
+    statuses.sort();
+    assert_eq!(statuses, [(MATH.to_string(), "OK".to_string()), (ENGLISH.to_string(), "E_MD5".to_string())]);
+    assert_eq!(server.hits(&MockServer::pdf_path("math.pdf")), 3);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn journal_resumes_after_a_torn_last_line() {
+    let server = MockServer::start().await;
+    server.add_book(MATH, Book::new("数学", "math.pdf", pdf(10_000)));
+    server.add_book(ENGLISH, Book::new("英语", "english.pdf", pdf(10_000)));
+    server.fail(&MockServer::pdf_path("english.pdf"), [Fault::Status(403)]);
+    let dir = tempfile::tempdir().unwrap();
+    let manifest = dir.path().join("manifest.json");
+    let out = dir.path().join("out");
+    let args = ["-c", MATH, "-c", ENGLISH, "-o", out.to_str().unwrap(), "--manifest", manifest.to_str().unwrap()];
+
+    run(&server, dir.path(), VALID_TOKEN, &args).await;
+    // 模拟上次进程在写日志时崩溃
+    let journal = out.join(".sed-dl-journal.jsonl");
+    let mut content = std::fs::read_to_string(&journal).unwrap();
+    content.push_str(r#"{"type":"item","content_id":"#);
+    std::fs::write(&journal, content).unwrap();
+
+    let second = run(&server, dir.path(), VALID_TOKEN, &args).await;
+    assert!(second.status.success(), "{}", String::from_utf8_lossy(&second.stderr));
+    for line in std::fs::read_to_string(&journal).unwrap().lines() {
+        serde_json::from_str::<Value>(line).unwrap();
+    }
+
+    run(&server, dir.path(), VALID_TOKEN, &args).await;
+    let mut statuses = statuses(&manifest);
+    statuses.sort();
+    assert_eq!(statuses, [(MATH.to_string(), "SKIPPED".to_string()), (ENGLISH.to_string(), "SKIPPED".to_string())]);
+    assert_eq!(server.hits(&MockServer::pdf_path("english.pdf")), 2);
+}
+
+#[test]
+fn help_follows_the_interface_language() {
+    let help = |env_lang: &str, args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_sed-dl")).args(args).env("LC_ALL", env_lang).output().unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+    let has_han = |text: &str| text.chars().any(|c| ('\u{4e00}'..='\u{9fff}').contains(&c));
+
+    for args in [&["-h"][..], &["token", "-h"], &["token", "import", "-h"], &["ctl", "-h"], &["ctl", "state", "-h"]] {
+        let english = help("zh_CN.UTF-8", &[&["--lang", "en"][..], args].concat());
+        assert!(!has_han(&english), "{:?}:\n{}", args, english);
+    }
+    assert!(help("en_US.UTF-8", &["-h"]).contains("One or more textbook page URLs"));
+    assert!(help("en_US.UTF-8", &["--lang=zh", "-h"]).contains("一个或多个教材页面URL"));
+    assert!(help("C", &["resume", "-h"]).contains("上次批量下载的输出目录"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn output_file_name_applies_to_a_single_queued_item() {
+    let server = MockServer::start().await;
+    server.add_book(MATH, Book::new("数学", "math.pdf", pdf(10_000)));
+    let dir = tempfile::tempdir().unwrap();
+    let out = dir.path().join("out");
+    std::fs::create_dir(&out).unwrap();
+    let target = out.join("book.pdf");
+    std::fs::write(&target, b"%PDF-old").unwrap();
+
+    // 重复的输入去重后只剩一项, `-o` 用作文件名
+    let output = run(&server, dir.path(), VALID_TOKEN, &["-c", MATH, "-c", MATH, "-o", target.to_str().unwrap()]).await;
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(std::fs::read(&target).unwrap(), pdf(10_000));
+}
+
+/// 轮询任务直到结束, 返回任务的 JSON
+async fn wait_for_job(http: &reqwest::Client, base: &str, id: u64) -> Value {
+    for _ in 0..100 {
+        let job: Value = http.get(format!("{}/jobs/{}", base, id)).send().await.unwrap().json().await.unwrap();
+        if !matches!(job["state"].as_str(), Some("queued" | "running")) { return job; }
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    }
+    panic!("任务 {} 未结束", id);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn daemon_reloads_tokens_after_all_accounts_are_unauthorized() {
+    let server = MockServer::start().await;
+    server.add_book(MATH, Book::new("数学", "math.pdf", pdf(10_000)));
+    server.add_book(ENGLISH, Book::new("英语", "english.pdf", pdf(10_000)));
+    let dir = tempfile::tempdir().unwrap();
+    let config = dir.path().join("config.toml");
+    std::fs::write(&config, "").unwrap();
+    let token_file = dir.path().join("token");
+    std::fs::write(&token_file, "expired-token").unwrap();
+    let listen = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+    let mut daemon = Command::new(env!("CARGO_BIN_EXE_sed-dl"))
+        .args(["--config", config.to_str().unwrap()])
+        .args(["--api-host", &server.base])
+        .args(["--cache-dir", dir.path().join("cache").to_str().unwrap()])
+        .args(["--token-file", token_file.to_str().unwrap(), "--no-input", "--lang", "zh", "--retry-base-delay", "1"])
+        .args(["-o", dir.path().join("out").to_str().unwrap(), "serve", "--listen", &listen.to_string()])
+        .args(["--jobs-file", dir.path().join("jobs.json").to_str().unwrap()])
+        .env("NO_COLOR", "1")
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .unwrap();
+    let base = format!("http://{}", listen);
+    let http = reqwest::Client::new();
+    let mut started = false;
+    for _ in 0..100 {
+        if http.get(format!("{}/jobs", base)).send().await.is_ok() { started = true; break; }
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    }
+    assert!(started, "服务未启动");
+
+    let add = |content_id: &'static str| {
+        let http = http.clone();
+        let base = base.clone();
+        async move {
+            let created: Value = http.post(format!("{}/jobs", base)).json(&serde_json::json!({ "items": [content_id] }))
+                .send().await.unwrap().json().await.unwrap();
+            created["jobs"][0]["id"].as_u64().unwrap()
+        }
+    };
+    let first = wait_for_job(&http, &base, add(MATH).await).await;
+    assert_eq!(first["status"], "E_TOKEN");
+
+    // 替换 Token 文件后, 下一个任务前重新读取
+    std::fs::write(&token_file, VALID_TOKEN).unwrap();
+    let second = wait_for_job(&http, &base, add(ENGLISH).await).await;
+    daemon.kill().unwrap();
+    let _ = daemon.wait();
+    assert_eq!(second["state"], "done", "{}", second);
+    assert!(dir.path().join("out/english.pdf").exists());
+}
@@ -0,0 +1,204 @@
+// tests/common/mod.rs
+
+//! 模拟智慧教育平台的本地服务器: 提供教材详情 JSON 和 PDF, 并可按脚本注入故障。
+
+#![allow(dead_code)]
+
+use axum::body::{Body, Bytes};
+use axum::extract::State;
+use axum::http::{header, HeaderMap, StatusCode, Uri};
+use axum::response::{IntoResponse, Response};
+use axum::Router;
+use sed_dl::endpoints::Endpoints;
+use sed_dl::retry::RetryConfig;
+use sed_dl::{SmartEduClient, SmartEduClientBuilder};
+use std::collections::{HashMap, VecDeque};
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+pub const VALID_TOKEN: &str = "valid-token";
+pub const DETAILS_PREFIX: &str = "/zxx/ndrv2/resources/tch_material/details/";
+
+/// 服务器上的一本教材
+#[derive(Clone)]
+pub struct Book {
+    pub title: String,
+    /// 存储地址中的文件名, 以 `pdf.pdf` 结尾时客户端改用标题命名
+    pub file: String,
+    pub content: Vec<u8>,
+    /// 详情中给出的 MD5, 默认为内容的真实 MD5
+    pub md5: Option<String>,
+    /// 详情中给出的大小, 默认为内容的真实大小
+    pub size: Option<u64>,
+}
+
+impl Book {
+    pub fn new(title: &str, file: &str, content: Vec<u8>) -> Book {
+        Book {
+            title: title.to_string(),
+            file: file.to_string(),
+            md5: Some(format!("{:x}", md5::compute(&content))),
+            size: Some(content.len() as u64),
+            content,
+        }
+    }
+}
+
+/// 生成指定大小的 PDF 内容
+pub fn pdf(size: usize) -> Vec<u8> {
+    let mut content = b"%PDF-1.4\n".to_vec();
+    content.extend((0..size.saturating_sub(content.len())).map(|i| b'a' + (i % 26) as u8));
+    content
+}
+
+/// 对某个路径的下一次请求注入的故障
+#[derive(Clone, Debug)]
+pub enum Fault {
+    /// 直接返回指定状态码
+    Status(u16),
+    /// 只发送前若干字节后断开连接
+    Truncate(usize),
+    /// 每发送一块内容就等待一段时间
+    Slow { chunk: usize, delay: Duration },
+}
+
+#[derive(Default)]
+struct Inner {
+    base: String,
+    books: HashMap<String, Book>,
+    faults: HashMap<String, VecDeque<Fault>>,
+    hits: HashMap<String, usize>,
+    not_modified: usize,
+}
+
+#[derive(Clone, Default)]
+struct Shared(Arc<Mutex<Inner>>);
+
+pub struct MockServer {
+    pub base: String,
+    shared: Shared,
+}
+
+impl MockServer {
+    pub async fn start() -> MockServer {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let shared = Shared::default();
+        shared.0.lock().unwrap().base = base.clone();
+        let app = Router::new().fallback(handle).with_state(shared.clone());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        MockServer { base, shared }
+    }
+
+    pub fn add_book(&self, id: &str, book: Book) {
+        self.shared.0.lock().unwrap().books.insert(id.to_string(), book);
+    }
+
+    /// 依次对该路径之后的请求注入故障
+    pub fn fail(&self, path: &str, faults: impl IntoIterator<Item = Fault>) {
+        self.shared.0.lock().unwrap().faults.entry(path.to_string()).or_default().extend(faults);
+    }
+
+    /// 该路径收到的请求数
+    pub fn hits(&self, path: &str) -> usize {
+        self.shared.0.lock().unwrap().hits.get(path).copied().unwrap_or(0)
+    }
+
+    /// 以 304 回应的条件请求数
+    pub fn not_modified(&self) -> usize {
+        self.shared.0.lock().unwrap().not_modified
+    }
+
+    pub fn details_path(id: &str) -> String {
+        format!("{}{}.json", DETAILS_PREFIX, id)
+    }
+
+    pub fn pdf_path(file: &str) -> String {
+        format!("/pdf/{}", file)
+    }
+
+    /// 指向本服务器、重试间隔很短的客户端构建器
+    pub fn client(&self) -> SmartEduClientBuilder {
+        SmartEduClient::builder()
+            .endpoints(Endpoints::with_hosts([self.base.clone()]))
+            .retry(fast_retry())
+            .token(VALID_TOKEN)
+    }
+}
+
+pub fn fast_retry() -> RetryConfig {
+    let mut retry = RetryConfig::default();
+    for policy in [&mut retry.details, &mut retry.download] {
+        policy.base_delay = Duration::from_millis(1);
+        policy.max_delay = Duration::from_millis(5);
+        policy.jitter = false;
+    }
+    retry.max_retry_after = Duration::from_millis(5);
+    retry
+}
+
+async fn handle(State(shared): State<Shared>, uri: Uri, headers: HeaderMap) -> Response {
+    let path = uri.path().to_string();
+    let (fault, base, book) = {
+        let mut inner = shared.0.lock().unwrap();
+        *inner.hits.entry(path.clone()).or_default() += 1;
+        let fault = inner.faults.get_mut(&path).and_then(VecDeque::pop_front);
+        let id = path.strip_prefix(DETAILS_PREFIX).and_then(|p| p.strip_suffix(".json"));
+        let book = match (id, path.strip_prefix("/pdf/")) {
+            (Some(id), _) => inner.books.get(id).cloned().map(|b| (true, b)),
+            (None, Some(file)) => inner.books.values().find(|b| b.file == file).cloned().map(|b| (false, b)),
+            _ => None,
+        };
+        (fault, inner.base.clone(), book)
+    };
+    if let Some(Fault::Status(code)) = fault {
+        return (StatusCode::from_u16(code).unwrap(), "injected failure").into_response();
+    }
+    let Some((is_details, book)) = book else {
+        return (StatusCode::NOT_FOUND, "not found").into_response();
+    };
+
+    if is_details {
+        let body = serde_json::json!({
+            "title": book.title,
+            "ti_items": [{
+                "ti_file_flag": "source",
+                "ti_format": "pdf",
+                "ti_storages": [format!("{}/pdf/{}", base, book.file)],
+                "ti_md5": book.md5,
+                "ti_size": book.size,
+            }],
+        }).to_string();
+        let etag = format!("\"{:x}\"", md5::compute(&body));
+        if headers.get(header::IF_NONE_MATCH).and_then(|v| v.to_str().ok()) == Some(etag.as_str()) {
+            shared.0.lock().unwrap().not_modified += 1;
+            return (StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response();
+        }
+        return ([(header::CONTENT_TYPE, "application/json".to_string()), (header::ETAG, etag)], body).into_response();
+    }
+
+    let token = uri.query().unwrap_or_default().split('&')
+        .find_map(|pair| pair.strip_prefix("accessToken="));
+    if token != Some(VALID_TOKEN) {
+        return (StatusCode::UNAUTHORIZED, "invalid token").into_response();
+    }
+    let content = Bytes::from(book.content);
+    let length = content.len();
+    let body = match fault {
+        Some(Fault::Truncate(n)) => {
+            let chunks: Vec<Result<Bytes, io::Error>> = vec![Ok(content.slice(..n.min(length))), Err(io::Error::other("truncated"))];
+            Body::from_stream(futures::stream::iter(chunks))
+        }
+        Some(Fault::Slow { chunk, delay }) => {
+            let chunks: Vec<Bytes> = (0..length).step_by(chunk).map(|i| content.slice(i..(i + chunk).min(length))).collect();
+            Body::from_stream(futures::stream::unfold(chunks.into_iter(), move |mut chunks| async move {
+                let chunk = chunks.next()?;
+                tokio::time::sleep(delay).await;
+                Some((Ok::<_, io::Error>(chunk), chunks))
+            }))
+        }
+        _ => Body::from(content),
+    };
+    ([(header::CONTENT_TYPE, "application/pdf".to_string()), (header::CONTENT_LENGTH, length.to_string())], body).into_response()
+}
@@ -0,0 +1,327 @@
+// tests/download.rs
+
+//! 针对模拟服务器的下载流程测试: 详情解析、重试、校验和账号切换。
+
+mod common;
+
+use common::{pdf, Book, Fault, MockServer, VALID_TOKEN};
+use sed_dl::endpoints::Endpoints;
+use sed_dl::har::HarRecorder;
+use sed_dl::http_cache::HttpCache;
+use sed_dl::token_pool::{PoolStrategy, TokenPool};
+use sed_dl::{verify_file, AppError, DownloadStatus, Event};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+const ID: &str = "11111111-2222-3333-4444-555555555555";
+
+async fn server_with_book(book: Book) -> MockServer {
+    let server = MockServer::start().await;
+    server.add_book(ID, book);
+    server
+}
+
+#[tokio::test]
+async fn downloads_and_validates() {
+    let server = server_with_book(Book::new("数学 一年级上册", "math.pdf", pdf(20_000))).await;
+    let client = server.client().build().unwrap();
+    let dir = tempfile::tempdir().unwrap();
+
+    let info = client.details(ID).await.unwrap();
+    assert_eq!(info.filename, "math.pdf");
+    let path = dir.path().join(&info.filename);
+    let outcome = client.download_to_path(&info, &path).await;
+
+    assert_eq!(outcome.result.unwrap(), DownloadStatus::Success);
+    assert_eq!(std::fs::read(&path).unwrap(), pdf(20_000));
+    assert_eq!(verify_file(&path, &info).await.unwrap(), DownloadStatus::Success);
+}
+
+#[tokio::test]
+async fn pdf_pdf_storage_is_named_after_title() {
+    let server = server_with_book(Book::new("语文/七年级:上册", "abcdef.pdf.pdf", pdf(5_000))).await;
+    let client = server.client().build().unwrap();
+    let dir = tempfile::tempdir().unwrap();
+
+    let info = client.details(ID).await.unwrap();
+    assert_eq!(info.filename, "语文_七年级_上册.pdf");
+    // 这类文件的 MD5 不可信, 只按大小校验
+    assert_eq!(info.expected_md5, None);
+    let outcome = client.download_to_path(&info, &dir.path().join(&info.filename)).await;
+    assert_eq!(outcome.result.unwrap(), DownloadStatus::Success);
+}
+
+#[tokio::test]
+async fn server_errors_are_retried() {
+    let server = server_with_book(Book::new("英语", "english.pdf", pdf(8_000))).await;
+    server.fail(&MockServer::details_path(ID), [Fault::Status(503)]);
+    server.fail(&MockServer::pdf_path("english.pdf"), [Fault::Status(500), Fault::Status(502)]);
+    let client = server.client().build().unwrap();
+    let dir = tempfile::tempdir().unwrap();
+
+    let info = client.details(ID).await.unwrap();
+    let outcome = client.download_to_path(&info, &dir.path().join(&info.filename)).await;
+
+    assert_eq!(outcome.result.unwrap(), DownloadStatus::Success);
+    assert_eq!(server.hits(&MockServer::details_path(ID)), 2);
+    assert_eq!(server.hits(&MockServer::pdf_path("english.pdf")), 3);
+}
+
+#[tokio::test]
+async fn retries_give_up_after_max_attempts() {
+    let server = server_with_book(Book::new("英语", "english.pdf", pdf(8_000))).await;
+    server.fail(&MockServer::pdf_path("english.pdf"), [Fault::Status(503), Fault::Status(503), Fault::Status(503)]);
+    let client = server.client().build().unwrap();
+    let dir = tempfile::tempdir().unwrap();
+
+    let info = client.details(ID).await.unwrap();
+    let path = dir.path().join(&info.filename);
+    let outcome = client.download_to_path(&info, &path).await;
+
+    assert_eq!(outcome.result.unwrap_err().status(), DownloadStatus::HttpError);
+    assert_eq!(server.hits(&MockServer::pdf_path("english.pdf")), 3);
+    assert!(!path.exists());
+}
+
+#[tokio::test]
+async fn truncated_transfer_is_retried_without_leaving_partial_file() {
+    let server = server_with_book(Book::new("物理", "physics.pdf", pdf(50_000))).await;
+    server.fail(&MockServer::pdf_path("physics.pdf"), [Fault::Truncate(10_000)]);
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let sink = events.clone();
+    let client = server.client()
+        .event_sink(Arc::new(move |event: &Event| sink.lock().unwrap().push(event.clone())))
+        .build().unwrap();
+    let dir = tempfile::tempdir().unwrap();
+
+    let info = client.details(ID).await.unwrap();
+    let path = dir.path().join(&info.filename);
+    let outcome = client.download_to_path(&info, &path).await;
+
+    assert_eq!(outcome.result.unwrap(), DownloadStatus::Success);
+    assert_eq!(std::fs::read(&path).unwrap().len(), 50_000);
+    assert!(!dir.path().join("physics.pdf.part").exists());
+    assert!(events.lock().unwrap().iter().any(|e| matches!(e, Event::Retry { attempt: 1, .. })));
+}
+
+#[tokio::test]
+async fn unauthorized_token_is_not_retried() {
+    let server = server_with_book(Book::new("化学", "chemistry.pdf", pdf(8_000))).await;
+    let client = server.client().token("expired-token").build().unwrap();
+    let dir = tempfile::tempdir().unwrap();
+
+    let info = client.details(ID).await.unwrap();
+    let outcome = client.download_to_path(&info, &dir.path().join(&info.filename)).await;
+
+    let err = outcome.result.unwrap_err();
+    assert_eq!(err.status(), DownloadStatus::TokenError);
+    assert_eq!(err.http_code(), Some(401));
+    assert_eq!(server.hits(&MockServer::pdf_path("chemistry.pdf")), 1);
+    assert!(client.pool().all_unauthorized());
+}
+
+#[tokio::test]
+async fn unauthorized_account_switches_to_next() {
+    let server = server_with_book(Book::new("化学", "chemistry.pdf", pdf(8_000))).await;
+    let accounts = vec![("old".to_string(), "expired-token".to_string()), ("new".to_string(), VALID_TOKEN.to_string())];
+    let client = server.client()
+        .token_pool(TokenPool::new(accounts, PoolStrategy::RoundRobin, Duration::from_secs(60)))
+        .build().unwrap();
+    let dir = tempfile::tempdir().unwrap();
+
+    let info = client.details(ID).await.unwrap();
+    let outcome = client.download_to_path(&info, &dir.path().join(&info.filename)).await;
+
+    assert_eq!(outcome.result.unwrap(), DownloadStatus::Success);
+    assert_eq!(outcome.account.as_deref(), Some("new"));
+}
+
+#[tokio::test]
+async fn forbidden_item_is_retried_once_on_another_account() {
+    const OTHER: &str = "22222222-2222-3333-4444-555555555555";
+    let server = server_with_book(Book::new("历史", "history.pdf", pdf(8_000))).await;
+    server.add_book(OTHER, Book::new("政治", "politics.pdf", pdf(8_000)));
+    server.fail(&MockServer::pdf_path("history.pdf"), [Fault::Status(403), Fault::Status(403)]);
+    let accounts = vec![("alice".to_string(), VALID_TOKEN.to_string()), ("bob".to_string(), VALID_TOKEN.to_string())];
+    let client = server.client()
+        .token_pool(TokenPool::new(accounts, PoolStrategy::RoundRobin, Duration::from_secs(300)))
+        .build().unwrap();
+    let dir = tempfile::tempdir().unwrap();
+
+    let info = client.details(ID).await.unwrap();
+    let outcome = client.download_to_path(&info, &dir.path().join(&info.filename)).await;
+    assert_eq!(outcome.result.unwrap_err().status(), DownloadStatus::Forbidden);
+    assert_eq!(server.hits(&MockServer::pdf_path("history.pdf")), 2);
+
+    // 第一个账号短暂暂停, 第二个账号仍可下载其他教材
+    let info = client.details(OTHER).await.unwrap();
+    let outcome = client.download_to_path(&info, &dir.path().join(&info.filename)).await;
+    assert_eq!(outcome.result.unwrap(), DownloadStatus::Success);
+    assert_eq!(outcome.account.as_deref(), Some("bob"));
+}
+
+#[tokio::test]
+async fn rate_limited_single_account_waits_for_cooldown() {
+    let server = server_with_book(Book::new("音乐", "music.pdf", pdf(8_000))).await;
+    server.fail(&MockServer::pdf_path("music.pdf"), [Fault::Status(429)]);
+    let cooldown = Duration::from_millis(200);
+    let client = server.client()
+        .token_pool(TokenPool::new(vec![("solo".to_string(), VALID_TOKEN.to_string())], PoolStrategy::RoundRobin, cooldown))
+        .build().unwrap();
+    let dir = tempfile::tempdir().unwrap();
+
+    let info = client.details(ID).await.unwrap();
+    let started = std::time::Instant::now();
+    let outcome = client.download_to_path(&info, &dir.path().join(&info.filename)).await;
+
+    assert_eq!(outcome.result.unwrap(), DownloadStatus::Success);
+    assert!(started.elapsed() >= cooldown);
+    assert_eq!(server.hits(&MockServer::pdf_path("music.pdf")), 2);
+}
+
+#[tokio::test]
+async fn wrong_md5_fails_validation() {
+    let mut book = Book::new("生物", "biology.pdf", pdf(8_000));
+    book.md5 = Some("0".repeat(32));
+    book.size = None;
+    let server = server_with_book(book).await;
+    let client = server.client().build().unwrap();
+    let dir = tempfile::tempdir().unwrap();
+
+    let info = client.details(ID).await.unwrap();
+    let path = dir.path().join(&info.filename);
+    std::fs::write(&path, b"%PDF-previous").unwrap();
+    let outcome = client.download_to_path(&info, &path).await;
+
+    assert_eq!(outcome.result.unwrap(), DownloadStatus::Md5ValidationFailed);
+    // 校验失败时不替换已有文件, 也不留下临时文件
+    assert_eq!(std::fs::read(&path).unwrap(), b"%PDF-previous");
+    assert!(!dir.path().join("biology.pdf.part").exists());
+}
+
+#[tokio::test]
+async fn html_error_page_is_not_a_pdf() {
+    let server = server_with_book(Book::new("地理", "geography.pdf", b"<html>error</html>".to_vec())).await;
+    let client = server.client().build().unwrap();
+    let dir = tempfile::tempdir().unwrap();
+
+    let info = client.details(ID).await.unwrap();
+    let outcome = client.download_to_path(&info, &dir.path().join(&info.filename)).await;
+
+    assert_eq!(outcome.result.unwrap(), DownloadStatus::InvalidPdf);
+}
+
+#[tokio::test]
+async fn slow_stream_completes_with_progress() {
+    let server = server_with_book(Book::new("历史", "history.pdf", pdf(8_000))).await;
+    server.fail(&MockServer::pdf_path("history.pdf"), [Fault::Slow { chunk: 1_000, delay: Duration::from_millis(10) }]);
+    let progress = Arc::new(Mutex::new(Vec::new()));
+    let sink = progress.clone();
+    let client = server.client()
+        .event_sink(Arc::new(move |event: &Event| {
+            if let Event::Progress { bytes, .. } = event { sink.lock().unwrap().push(*bytes); }
+        }))
+        .build().unwrap();
+    let dir = tempfile::tempdir().unwrap();
+
+    let info = client.details(ID).await.unwrap();
+    let outcome = client.download_to_path(&info, &dir.path().join(&info.filename)).await;
+
+    assert_eq!(outcome.result.unwrap(), DownloadStatus::Success);
+    let progress = progress.lock().unwrap();
+    assert!(progress.len() > 1);
+    assert_eq!(progress.last(), Some(&8_000));
+}
+
+#[tokio::test]
+async fn slow_stream_times_out() {
+    let server = server_with_book(Book::new("历史", "history.pdf", pdf(8_000))).await;
+    let slow = Fault::Slow { chunk: 1_000, delay: Duration::from_millis(100) };
+    server.fail(&MockServer::pdf_path("history.pdf"), [slow.clone(), slow.clone(), slow]);
+    let client = server.client().timeout(Duration::from_millis(300)).build().unwrap();
+    let dir = tempfile::tempdir().unwrap();
+
+    let info = client.details(ID).await.unwrap();
+    let outcome = client.download_to_path(&info, &dir.path().join(&info.filename)).await;
+
+    assert_eq!(outcome.result.unwrap_err().status(), DownloadStatus::Timeout);
+}
+
+#[tokio::test]
+async fn missing_details_is_content_removed() {
+    let server = MockServer::start().await;
+    let client = server.client().build().unwrap();
+
+    let err = client.details(ID).await.unwrap_err();
+
+    assert_eq!(err.status(), DownloadStatus::ContentRemoved);
+    assert_eq!(server.hits(&MockServer::details_path(ID)), 1);
+}
+
+#[tokio::test]
+async fn details_fail_over_to_next_host() {
+    let server = server_with_book(Book::new("音乐", "music.pdf", pdf(1_000))).await;
+    // 端口 1 上没有服务, 连接会被立即拒绝
+    let endpoints = Endpoints::with_hosts(["http://127.0.0.1:1".to_string(), server.base.clone()]);
+    let client = server.client().endpoints(endpoints).build().unwrap();
+
+    let info = client.details(ID).await.unwrap();
+    assert_eq!(info.filename, "music.pdf");
+    // 之后的请求直接使用可用的主机
+    client.details(ID).await.unwrap();
+    assert_eq!(server.hits(&MockServer::details_path(ID)), 2);
+}
+
+#[tokio::test]
+async fn details_are_cached_and_revalidated() {
+    let server = server_with_book(Book::new("美术", "art.pdf", pdf(1_000))).await;
+    let cache_dir = tempfile::tempdir().unwrap();
+    let client = server.client().cache(HttpCache::new(cache_dir.path())).build().unwrap();
+
+    client.details(ID).await.unwrap();
+    client.details(ID).await.unwrap();
+    assert_eq!(server.hits(&MockServer::details_path(ID)), 2);
+    assert_eq!(server.not_modified(), 1);
+
+    let fresh = server.client().cache(HttpCache::new(cache_dir.path()).ttl(Duration::from_secs(3600))).build().unwrap();
+    fresh.details(ID).await.unwrap();
+    assert_eq!(server.hits(&MockServer::details_path(ID)), 2);
+}
+
+#[tokio::test]
+async fn offline_mode_uses_only_the_cache() {
+    let server = server_with_book(Book::new("美术", "art.pdf", pdf(1_000))).await;
+    let cache_dir = tempfile::tempdir().unwrap();
+    server.client().cache(HttpCache::new(cache_dir.path())).build().unwrap().details(ID).await.unwrap();
+    let offline = server.client().cache(HttpCache::new(cache_dir.path())).offline(true).build().unwrap();
+    let dir = tempfile::tempdir().unwrap();
+
+    let info = offline.details(ID).await.unwrap();
+    let outcome = offline.download_to_path(&info, &dir.path().join(&info.filename)).await;
+
+    assert!(matches!(outcome.result, Err(AppError::Offline(_))));
+    assert!(matches!(offline.details("99999999-2222-3333-4444-555555555555").await, Err(AppError::Offline(_))));
+    assert_eq!(server.hits(&MockServer::details_path(ID)), 1);
+    assert_eq!(server.hits(&MockServer::pdf_path("art.pdf")), 0);
+}
+
+#[tokio::test]
+async fn har_capture_redacts_tokens() {
+    let server = server_with_book(Book::new("体育", "sports.pdf", pdf(2_000))).await;
+    let recorder = Arc::new(HarRecorder::new());
+    let client = server.client().har(recorder.clone()).build().unwrap();
+    let dir = tempfile::tempdir().unwrap();
+
+    let info = client.details(ID).await.unwrap();
+    client.download_to_path(&info, &dir.path().join(&info.filename)).await.result.unwrap();
+    let har_path = dir.path().join("trace.har");
+    recorder.save(&har_path).unwrap();
+
+    let har = std::fs::read_to_string(&har_path).unwrap();
+    assert_eq!(recorder.len(), 2);
+    assert!(!har.contains(VALID_TOKEN));
+    assert!(har.contains("accessToken=REDACTED"));
+    // JSON 响应记录内容, PDF 不记录
+    assert!(har.contains("ti_storages"));
+    assert!(!har.contains("%PDF"));
+}